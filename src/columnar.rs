@@ -0,0 +1,189 @@
+use std::fmt;
+use std::io::{self, BufReader, BufWriter, Seek, Write};
+use std::path::Path;
+
+use arrow::datatypes::SchemaRef;
+use clap::ValueEnum;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::{RecordStream, TypedRecordStream};
+use crate::output::OutputFormat;
+use crate::reader::limits::read_error;
+use crate::reader::record_batch::{
+    DEFAULT_BATCH_SIZE, RecordBatchStream, arrow_schema_from_nc_types, infer_typed_columns,
+    json_records_to_batches,
+};
+use crate::record::NcValue;
+
+/// Compression codec for columnar output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum,)]
+pub enum ColumnarCompression {
+    None,
+    Snappy,
+    Gzip,
+    Zstd,
+    Lz4,
+    Brotli,
+}
+
+impl fmt::Display for ColumnarCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            ColumnarCompression::None => write!(f, "none"),
+            ColumnarCompression::Snappy => write!(f, "snappy"),
+            ColumnarCompression::Gzip => write!(f, "gzip"),
+            ColumnarCompression::Zstd => write!(f, "zstd"),
+            ColumnarCompression::Lz4 => write!(f, "lz4"),
+            ColumnarCompression::Brotli => write!(f, "brotli"),
+        }
+    }
+}
+
+/// How Parquet and Arrow IPC files are written.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct ColumnarOptions {
+    /// `None` uses the format's default: Snappy for Parquet, uncompressed for Arrow IPC. Arrow
+    /// IPC output is always uncompressed.
+    pub compression:    Option<ColumnarCompression,>,
+    /// Maximum rows per Parquet row group; `None` keeps the Parquet writer's default.
+    pub row_group_size: Option<usize,>,
+    /// Rows per record batch decoded from the input.
+    pub batch_size:     usize,
+}
+
+impl Default for ColumnarOptions {
+    fn default() -> Self {
+        Self {
+            compression:    None,
+            row_group_size: None,
+            batch_size:     DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+/// Writes record batches as a Parquet or Arrow IPC file, one batch at a time. Returns the
+/// number of rows written.
+pub fn write_batches<W: Write + Send,>(
+    schema: SchemaRef,
+    batches: RecordBatchStream,
+    format: OutputFormat,
+    options: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let mut rows = 0u64;
+    match format {
+        OutputFormat::Parquet => {
+            let compression = match options.compression.unwrap_or(ColumnarCompression::Snappy,) {
+                ColumnarCompression::None => Compression::UNCOMPRESSED,
+                ColumnarCompression::Snappy => Compression::SNAPPY,
+                ColumnarCompression::Gzip => Compression::GZIP(GzipLevel::default(),),
+                ColumnarCompression::Zstd => Compression::ZSTD(ZstdLevel::default(),),
+                ColumnarCompression::Lz4 => Compression::LZ4_RAW,
+                ColumnarCompression::Brotli => Compression::BROTLI(BrotliLevel::default(),),
+            };
+            let mut properties = WriterProperties::builder().set_compression(compression,);
+            if let Some(row_group_size,) = options.row_group_size {
+                properties = properties.set_max_row_group_size(row_group_size.max(1,),);
+            }
+            let mut parquet = ArrowWriter::try_new(writer, schema, Some(properties.build(),),)
+                .map_err(|e| write_error(format, e,),)?;
+            for batch in batches {
+                let batch = batch?;
+                rows += batch.num_rows() as u64;
+                parquet.write(&batch,).map_err(|e| write_error(format, e,),)?;
+            }
+            parquet.close().map_err(|e| write_error(format, e,),)?;
+        },
+        OutputFormat::ArrowIpc => {
+            if let Some(compression,) = options.compression
+                && compression != ColumnarCompression::None
+            {
+                return Err(DataReaderError::InternalError(format!(
+                    "Arrow IPC output does not support {} compression",
+                    compression
+                ),),);
+            }
+            let mut ipc = arrow::ipc::writer::FileWriter::try_new(writer, &schema,)
+                .map_err(|e| write_error(format, e,),)?;
+            for batch in batches {
+                let batch = batch?;
+                rows += batch.num_rows() as u64;
+                ipc.write(&batch,).map_err(|e| write_error(format, e,),)?;
+            }
+            ipc.finish().map_err(|e| write_error(format, e,),)?;
+        },
+        other => {
+            return Err(DataReaderError::InternalError(format!(
+                "{} is not a columnar output format",
+                other
+            ),),);
+        },
+    }
+    Ok(rows,)
+}
+
+/// Writes a typed record stream as a Parquet or Arrow IPC file. The input is read once: its
+/// records are copied to a temporary file while their types are inferred, and the copy is then
+/// decoded into batches. Date, timestamp and binary values get Arrow columns of their type.
+/// Non-object records are written as `{"value": ...}`. `source_path` labels errors. Returns the
+/// number of rows written.
+pub fn write_records<W: Write + Send,>(
+    records: TypedRecordStream,
+    source_path: &Path,
+    format: OutputFormat,
+    options: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let spool_error = |e: io::Error| read_error(source_path, e,);
+    let mut spool = BufWriter::new(tempfile::tempfile().map_err(spool_error,)?,);
+    let mut spool_failure = None;
+    let spooled = as_objects(records,).map(|record| -> Result<NcValue, DataReaderError,> {
+        let record = record?;
+        let written = serde_json::to_writer(&mut spool, &record.to_json(),)
+            .map_err(io::Error::from,)
+            .and_then(|()| spool.write_all(b"\n",),);
+        if let Err(e,) = written {
+            spool_failure.get_or_insert(e,);
+        }
+        Ok(record,)
+    },);
+    let (columns, types,) = infer_typed_columns(spooled,)?;
+    if let Some(e,) = spool_failure {
+        return Err(spool_error(e,),);
+    }
+    let schema = arrow_schema_from_nc_types(&columns, &types,);
+
+    let mut spool = spool.into_inner().map_err(|e| spool_error(e.into_error(),),)?;
+    spool.rewind().map_err(spool_error,)?;
+    let path = source_path.to_path_buf();
+    let records: RecordStream = Box::new(
+        serde_json::Deserializer::from_reader(BufReader::new(spool,),)
+            .into_iter::<serde_json::Value>()
+            .map(move |record| {
+                record.map_err(|e| DataReaderError::ParseError {
+                    path:     path.clone(),
+                    source:   Box::new(e,),
+                    position: None,
+                },)
+            },),
+    );
+    let batches =
+        json_records_to_batches(schema.clone(), records, options.batch_size, source_path,)?;
+    write_batches(schema, batches, format, options, writer,)
+}
+
+fn as_objects(stream: TypedRecordStream,) -> TypedRecordStream {
+    Box::new(stream.map(|record| {
+        record.map(|value| match value {
+            NcValue::Object(_,) => value,
+            other => NcValue::Object([("value".to_string(), other,),].into(),),
+        },)
+    },),)
+}
+
+fn write_error(format: OutputFormat, e: impl fmt::Display,) -> DataReaderError {
+    DataReaderError::InternalError(format!("Failed to write {} output: {}", format, e),)
+}
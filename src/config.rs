@@ -0,0 +1,533 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use tracing::level_filters::LevelFilter;
+
+use crate::columnar::{ColumnarCompression, ColumnarOptions};
+use crate::error::DataReaderError;
+use crate::file_reader::FileReaderOptions;
+use crate::logging::{LogFormat, LogOptions};
+use crate::output::OutputFormat;
+use crate::reader::coercion::{ValueCoercion, ValueType};
+use crate::reader::csv_dialect::{CsvOptions, parse_dialect_char};
+use crate::reader::error_policy::ErrorPolicy;
+use crate::reader::filter::RecordFilter;
+use crate::reader::limits::ReadLimits;
+use crate::reader::read_config::ReadConfig;
+use crate::reader::sample::SampleOptions;
+use crate::reader::type_inference::{ColumnHint, InferenceOptions, NumberLocale};
+use crate::sink::{SinkCompression, SinkOptions};
+
+/// The machine-wide configuration file.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/nc_reader/cli.yml";
+/// The project configuration file, looked up in the current directory.
+pub const PROJECT_CONFIG_FILE: &str = "nc_reader.yml";
+/// Prefix of the environment variables that set a setting: `NC_READER_MAX_DEPTH=64`.
+pub const ENV_PREFIX: &str = "NC_READER_";
+
+/// Settings shared by configuration files, `NC_READER_*` environment variables and CLI flags.
+/// Keys are the flag names without the dashes (`max-depth: 64`). `None` leaves a setting to
+/// the next layer down, or to the built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format:                 Option<String,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_type:              Option<String,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head:                   Option<usize,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive:              Option<bool,>,
+    /// Comma-separated extensions, e.g. `csv,json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_ext:             Option<String,>,
+    /// Files read concurrently in a directory read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs:                   Option<usize,>,
+    /// Rows per record batch for columnar output and Arrow Flight.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size:             Option<usize,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_policy:           Option<String,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejects_path:           Option<PathBuf,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression:            Option<String,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_group_size:         Option<usize,>,
+    /// Compression of the whole output: `none`, `gzip` or `zstd`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_compression:     Option<String,>,
+    /// Write output files through a temporary file renamed into place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atomic:                 Option<bool,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes:              Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_decompressed_bytes: Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_records:            Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth:              Option<usize,>,
+    /// Seconds allowed for reading one file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout:                Option<u64,>,
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_clause:           Option<String,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub select:                 Option<String,>,
+    /// CSV field delimiter, one character or `tab`; detected when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_delimiter:          Option<String,>,
+    /// CSV quote character; detected when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_quote:              Option<String,>,
+    /// Read the first CSV row as data rather than as column names.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_header:              Option<bool,>,
+    /// Number separators of CSV fields: `en`, `de`, `fr` or `ch`; guessed when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale:                 Option<String,>,
+    /// Date formats tried before the built-in ones, e.g. `%d %b %Y`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_formats:           Option<Vec<String,>,>,
+    /// A locale or date format per CSV column, e.g. `price: de`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_formats:         Option<BTreeMap<String, String,>,>,
+    /// Text read as null in CSV, XML and spreadsheet values, e.g. `NA`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub null_values:            Option<Vec<String,>,>,
+    /// Words read as `true`, replacing `true` and `yes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub true_values:            Option<Vec<String,>,>,
+    /// Words read as `false`, replacing `false` and `no`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub false_values:           Option<Vec<String,>,>,
+    /// Keep CSV and XML values as text instead of reading numbers and booleans.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preserve_strings:       Option<bool,>,
+    /// A declared type per field, e.g. `zip: string`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types:                  Option<BTreeMap<String, String,>,>,
+    /// Rows a CSV summary keeps from the start of the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_first:           Option<usize,>,
+    /// Rows a CSV summary picks at random from the rest of the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_reservoir:       Option<usize,>,
+    /// Also append diagnostics to this file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_file:               Option<PathBuf,>,
+    /// `text` or `json` diagnostics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_format:             Option<String,>,
+}
+
+impl Settings {
+    /// Layers `over` on top of `self`: every setting `over` has replaces the one in `self`.
+    pub fn merge(self, over: Settings,) -> Settings {
+        Settings {
+            format:                 over.format.or(self.format,),
+            file_type:              over.file_type.or(self.file_type,),
+            head:                   over.head.or(self.head,),
+            recursive:              over.recursive.or(self.recursive,),
+            filter_ext:             over.filter_ext.or(self.filter_ext,),
+            jobs:                   over.jobs.or(self.jobs,),
+            batch_size:             over.batch_size.or(self.batch_size,),
+            error_policy:           over.error_policy.or(self.error_policy,),
+            rejects_path:           over.rejects_path.or(self.rejects_path,),
+            compression:            over.compression.or(self.compression,),
+            row_group_size:         over.row_group_size.or(self.row_group_size,),
+            output_compression:     over.output_compression.or(self.output_compression,),
+            atomic:                 over.atomic.or(self.atomic,),
+            max_bytes:              over.max_bytes.or(self.max_bytes,),
+            max_decompressed_bytes: over.max_decompressed_bytes.or(self.max_decompressed_bytes,),
+            max_records:            over.max_records.or(self.max_records,),
+            max_depth:              over.max_depth.or(self.max_depth,),
+            timeout:                over.timeout.or(self.timeout,),
+            where_clause:           over.where_clause.or(self.where_clause,),
+            select:                 over.select.or(self.select,),
+            csv_delimiter:          over.csv_delimiter.or(self.csv_delimiter,),
+            csv_quote:              over.csv_quote.or(self.csv_quote,),
+            no_header:              over.no_header.or(self.no_header,),
+            locale:                 over.locale.or(self.locale,),
+            date_formats:           over.date_formats.or(self.date_formats,),
+            column_formats:         over.column_formats.or(self.column_formats,),
+            null_values:            over.null_values.or(self.null_values,),
+            true_values:            over.true_values.or(self.true_values,),
+            false_values:           over.false_values.or(self.false_values,),
+            preserve_strings:       over.preserve_strings.or(self.preserve_strings,),
+            types:                  over.types.or(self.types,),
+            sample_first:           over.sample_first.or(self.sample_first,),
+            sample_reservoir:       over.sample_reservoir.or(self.sample_reservoir,),
+            log_file:               over.log_file.or(self.log_file,),
+            log_format:             over.log_format.or(self.log_format,),
+        }
+    }
+
+    /// The output format, if one is set.
+    pub fn output_format(&self,) -> Result<Option<OutputFormat,>, DataReaderError,> {
+        self.format
+            .as_deref()
+            .map(|format| {
+                OutputFormat::from_str(format, true,).map_err(|e| invalid("format", e,),)
+            },)
+            .transpose()
+    }
+
+    pub fn error_policy(&self,) -> Result<ErrorPolicy, DataReaderError,> {
+        match self.error_policy.as_deref() {
+            Some(policy,) => policy.parse().map_err(|e| invalid("error-policy", e,),),
+            None => Ok(ErrorPolicy::default(),),
+        }
+    }
+
+    pub fn compression(&self,) -> Result<Option<ColumnarCompression,>, DataReaderError,> {
+        self.compression
+            .as_deref()
+            .map(|codec| {
+                ColumnarCompression::from_str(codec, true,)
+                    .map_err(|e| invalid("compression", e,),)
+            },)
+            .transpose()
+    }
+
+    pub fn sink_options(&self,) -> Result<SinkOptions, DataReaderError,> {
+        Ok(SinkOptions {
+            compression: self
+                .output_compression
+                .as_deref()
+                .map(|codec| {
+                    SinkCompression::from_str(codec, true,)
+                        .map_err(|e| invalid("output-compression", e,),)
+                },)
+                .transpose()?,
+            atomic:      self.atomic.unwrap_or(false,),
+        },)
+    }
+
+    /// Logging at `level` (from `-v`/`-q`) with these settings' file and format.
+    pub fn log_options(&self, level: LevelFilter,) -> Result<LogOptions, DataReaderError,> {
+        Ok(LogOptions {
+            level,
+            format: self
+                .log_format
+                .as_deref()
+                .map(|format| {
+                    LogFormat::from_str(format, true,).map_err(|e| invalid("log-format", e,),)
+                },)
+                .transpose()?
+                .unwrap_or_default(),
+            file: self.log_file.clone(),
+        },)
+    }
+
+    pub fn filter(&self,) -> Result<RecordFilter, DataReaderError,> {
+        Ok(RecordFilter {
+            predicate:  self
+                .where_clause
+                .as_deref()
+                .map(str::parse,)
+                .transpose()
+                .map_err(|e| invalid("where", e,),)?,
+            projection: self
+                .select
+                .as_deref()
+                .map(str::parse,)
+                .transpose()
+                .map_err(|e| invalid("select", e,),)?,
+        },)
+    }
+
+    pub fn csv_options(&self,) -> Result<CsvOptions, DataReaderError,> {
+        Ok(CsvOptions {
+            delimiter:  self
+                .csv_delimiter
+                .as_deref()
+                .map(parse_dialect_char,)
+                .transpose()
+                .map_err(|e| invalid("csv-delimiter", e,),)?,
+            quote:      self
+                .csv_quote
+                .as_deref()
+                .map(parse_dialect_char,)
+                .transpose()
+                .map_err(|e| invalid("csv-quote", e,),)?,
+            has_header: self.no_header.map(|no_header| !no_header,),
+        },)
+    }
+
+    pub fn inference(&self,) -> Result<InferenceOptions, DataReaderError,> {
+        let mut inference = InferenceOptions {
+            locale: self
+                .locale
+                .as_deref()
+                .map(str::parse::<NumberLocale,>,)
+                .transpose()
+                .map_err(|e| invalid("locale", e,),)?,
+            ..InferenceOptions::default()
+        };
+        if let Some(formats,) = &self.date_formats {
+            inference = inference.with_date_formats(formats.clone(),);
+        }
+        for (column, hint,) in self.column_formats.iter().flatten() {
+            let hint = hint.parse::<ColumnHint>().map_err(|e| invalid("column-formats", e,),)?;
+            inference = inference.with_column_hint(column, hint,);
+        }
+        Ok(inference,)
+    }
+
+    pub fn coercion(&self,) -> Result<ValueCoercion, DataReaderError,> {
+        let defaults = ValueCoercion::default();
+        let true_values = self.true_values.clone().unwrap_or_else(|| defaults.true_values.clone(),);
+        let false_values =
+            self.false_values.clone().unwrap_or_else(|| defaults.false_values.clone(),);
+        let mut coercion = defaults
+            .with_null_values(self.null_values.clone().unwrap_or_default(),)
+            .with_booleans(true_values, false_values,)
+            .map_err(|e| invalid("true-values", e,),)?
+            .with_preserve_strings(self.preserve_strings.unwrap_or(false,),);
+        for (field, value_type,) in self.types.iter().flatten() {
+            let value_type = value_type.parse::<ValueType>().map_err(|e| invalid("types", e,),)?;
+            coercion = coercion.with_type(field, value_type,);
+        }
+        Ok(coercion,)
+    }
+
+    pub fn sample(&self,) -> SampleOptions {
+        let defaults = SampleOptions::default();
+        SampleOptions {
+            first: self.sample_first.unwrap_or(defaults.first,),
+            reservoir: self.sample_reservoir.unwrap_or(defaults.reservoir,),
+            ..defaults
+        }
+    }
+
+    /// The error policy, limits, filter, CSV dialect, type inference, value coercion and summary
+    /// sample of a read.
+    pub fn read_config(&self,) -> Result<ReadConfig, DataReaderError,> {
+        Ok(ReadConfig::default()
+            .with_error_policy(self.error_policy()?,)
+            .with_limits(ReadLimits {
+                max_bytes:              self.max_bytes,
+                max_decompressed_bytes: self.max_decompressed_bytes,
+                max_records:            self.max_records,
+                max_depth:              self.max_depth,
+                timeout:                self.timeout.map(Duration::from_secs,),
+            },)
+            .with_filter(self.filter()?,)
+            .with_csv(self.csv_options()?,)
+            .with_inference(self.inference()?,)
+            .with_coercion(self.coercion()?,)
+            .with_sample(self.sample(),),)
+    }
+
+    /// `FileReaderOptions` with these settings. The output mode and path are left at their
+    /// defaults for the caller to fill in.
+    pub fn reader_options(&self,) -> Result<FileReaderOptions, DataReaderError,> {
+        let defaults = FileReaderOptions::default();
+        Ok(FileReaderOptions {
+            head: self.head,
+            file_type_override: self.file_type.clone(),
+            output_format: self.output_format()?.unwrap_or_default(),
+            recursive: self.recursive.unwrap_or(defaults.recursive,),
+            filter_exts: self.filter_ext.as_deref().map(|exts| {
+                exts.split(',',)
+                    .map(str::trim,)
+                    .filter(|ext| !ext.is_empty(),)
+                    .map(str::to_string,)
+                    .collect()
+            },),
+            jobs: self.jobs.unwrap_or(defaults.jobs,),
+            read_config: self.read_config()?,
+            ..defaults
+        },)
+    }
+
+    pub fn columnar_options(&self,) -> Result<ColumnarOptions, DataReaderError,> {
+        let defaults = ColumnarOptions::default();
+        Ok(ColumnarOptions {
+            compression:    self.compression()?,
+            row_group_size: self.row_group_size,
+            batch_size:     self.batch_size.unwrap_or(defaults.batch_size,),
+        },)
+    }
+}
+
+/// Where `Settings` are loaded from. Files are applied in order, so later files override
+/// earlier ones; missing files are skipped. `env` (name, value) pairs are applied last.
+#[derive(Debug, Clone, Default,)]
+pub struct ConfigSources {
+    pub files: Vec<PathBuf,>,
+    pub env:   Vec<(String, String,),>,
+}
+
+/// Settings after layering, with the sources they came from.
+#[derive(Debug, Clone, Default,)]
+pub struct LoadedSettings {
+    pub settings: Settings,
+    /// The files that were read, then `environment` if any variable was set.
+    pub sources:  Vec<String,>,
+    /// Problems that did not stop loading, such as variables that name no setting. Loading
+    /// happens before logging is set up, so the caller logs these.
+    pub warnings: Vec<String,>,
+}
+
+impl ConfigSources {
+    /// The standard layers: [`SYSTEM_CONFIG_PATH`], the user's `cli.yml` (`$NC_READER_CONFIG`,
+    /// or `$XDG_CONFIG_HOME/nc_reader/cli.yml` defaulting to `~/.config/nc_reader/cli.yml`),
+    /// [`PROJECT_CONFIG_FILE`] in the current directory, then the `NC_READER_*` variables.
+    pub fn from_environment() -> Self {
+        let env_path = |name: &str| std::env::var_os(name,).filter(|v| !v.is_empty(),);
+        let user = env_path("NC_READER_CONFIG",).map(PathBuf::from,).or_else(|| {
+            env_path("XDG_CONFIG_HOME",)
+                .map(PathBuf::from,)
+                .or_else(|| env_path("HOME",).map(|home| Path::new(&home,).join(".config",),),)
+                .map(|dir| dir.join("nc_reader",).join("cli.yml",),)
+        },);
+        let mut files = vec![PathBuf::from(SYSTEM_CONFIG_PATH,)];
+        files.extend(user,);
+        files.push(PathBuf::from(PROJECT_CONFIG_FILE,),);
+        ConfigSources {
+            files,
+            env: std::env::vars().collect(),
+        }
+    }
+
+    /// Layers every source. With a `profile`, the profile's settings in each file are applied
+    /// right after that file's top-level settings; naming a profile that no file defines is an
+    /// error.
+    pub fn load(&self, profile: Option<&str,>,) -> Result<LoadedSettings, DataReaderError,> {
+        let mut loaded = LoadedSettings::default();
+        let mut profile_found = profile.is_none();
+        for path in &self.files {
+            if !path.is_file() {
+                continue;
+            }
+            let (settings, mut profiles,) = read_config_file(path,)?;
+            loaded.settings = loaded.settings.merge(settings,);
+            if let Some(name,) = profile
+                && let Some(profile_settings,) = profiles.remove(name,)
+            {
+                loaded.settings = loaded.settings.merge(profile_settings,);
+                profile_found = true;
+            }
+            loaded.sources.push(path.display().to_string(),);
+        }
+
+        let (env, warnings,) = settings_from_env(&self.env,)?;
+        loaded.warnings.extend(warnings,);
+        if env != Settings::default() {
+            loaded.settings = loaded.settings.merge(env,);
+            loaded.sources.push("environment".to_string(),);
+        }
+
+        match profile {
+            Some(name,) if !profile_found => {
+                Err(DataReaderError::InvalidConfig(format!("Unknown profile: {}", name),),)
+            },
+            _ => Ok(loaded,),
+        }
+    }
+}
+
+/// Reads one configuration file: its top-level settings and its named `profiles`.
+fn read_config_file(
+    path: &Path,
+) -> Result<(Settings, BTreeMap<String, Settings,>,), DataReaderError,> {
+    let parse_error = |e: serde_yaml::Error| DataReaderError::ParseError {
+        path:     path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    };
+    let text = std::fs::read_to_string(path,).map_err(|e| DataReaderError::FileReadError {
+        path:   path.to_path_buf(),
+        source: e,
+    },)?;
+    let mut document = match serde_yaml::from_str::<Value,>(&text,).map_err(parse_error,)? {
+        Value::Null => Mapping::new(),
+        Value::Mapping(mapping,) => mapping,
+        _ => {
+            return Err(DataReaderError::InvalidConfig(format!(
+                "{} must be a mapping of settings",
+                path.display()
+            ),),);
+        },
+    };
+    let profiles = match document.remove("profiles",) {
+        Some(profiles,) => serde_yaml::from_value(profiles,).map_err(parse_error,)?,
+        None => BTreeMap::new(),
+    };
+    let settings = serde_yaml::from_value(Value::Mapping(document,),).map_err(parse_error,)?;
+    Ok((settings, profiles,),)
+}
+
+/// Settings from `NC_READER_<KEY>` variables, where `<KEY>` is the setting name upper-cased
+/// with `_` for `-`. `NC_READER_CONFIG` and `NC_READER_PROFILE` select files and profiles
+/// rather than setting anything. Variables that name no setting are skipped with a warning.
+fn settings_from_env(
+    env: &[(String, String,)],
+) -> Result<(Settings, Vec<String,>,), DataReaderError,> {
+    let mut settings = Settings::default();
+    let mut warnings = Vec::new();
+    for (name, value,) in env {
+        let Some(key,) = name.strip_prefix(ENV_PREFIX,) else {
+            continue;
+        };
+        if matches!(key, "CONFIG" | "PROFILE") {
+            continue;
+        }
+        let key = Value::String(key.to_ascii_lowercase().replace('_', "-",),);
+        // Every setting is optional, so only a key that names no setting fails on null.
+        if setting(&key, Value::Null,).is_err() {
+            warnings.push(format!("Ignoring {}: there is no such setting", name),);
+            continue;
+        }
+        let env_setting = setting(&key, Value::String(value.clone(),),)
+            .or_else(|e| {
+                env_values(value,)
+                    .into_iter()
+                    .find_map(|value| setting(&key, value,).ok(),)
+                    .ok_or(e,)
+            },)
+            .map_err(|e| DataReaderError::InvalidConfig(format!("{}: {}", name, e),),)?;
+        settings = settings.merge(env_setting,);
+    }
+    Ok((settings, warnings,),)
+}
+
+/// `Settings` with only `key` set to `value`.
+fn setting(key: &Value, value: Value,) -> Result<Settings, serde_yaml::Error,> {
+    let mut mapping = Mapping::new();
+    mapping.insert(key.clone(), value,);
+    serde_yaml::from_value(Value::Mapping(mapping,),)
+}
+
+/// What the text of a variable stands for when its setting does not take text, in the order
+/// tried: a number, a boolean, a comma-separated list and comma-separated `key:value` pairs.
+fn env_values(text: &str,) -> Vec<Value,> {
+    let items = || text.split(',',).map(str::trim,).filter(|item| !item.is_empty(),);
+    let string = |item: &str| Value::String(item.trim().to_string(),);
+    let mut values = Vec::new();
+    if let Ok(number,) = text.parse::<u64>() {
+        values.push(Value::from(number,),);
+    }
+    if let Ok(flag,) = text.parse::<bool>() {
+        values.push(Value::Bool(flag,),);
+    }
+    values.push(Value::Sequence(items().map(string,).collect(),),);
+    let pairs = items()
+        .map(|item| item.split_once(':',).map(|(key, value,)| (string(key,), string(value,),),),)
+        .collect::<Option<Mapping,>>();
+    values.extend(pairs.map(Value::Mapping,),);
+    values
+}
+
+fn invalid(setting: &str, message: String,) -> DataReaderError {
+    DataReaderError::InvalidConfig(format!("{}: {}", setting, message),)
+}
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use encoding_rs::Encoding;
+use serde_json::{Map, Value};
+
+use crate::columnar::{ColumnarCompression, ColumnarOptions, write_records};
+use crate::error::{DataReaderError, ErrorPosition};
+use crate::file_reader::{
+    format_for_file, reader_for_bytes, reader_for_format, reader_for_input, start_read,
+};
+use crate::nc_reader_result::{RecordStream, TypedRecordStream, typed_to_json_stream};
+use crate::output::OutputFormat;
+use crate::reader::coercion::{ValueCoercion, ValueType};
+use crate::reader::error_policy::apply_error_policy;
+use crate::reader::charset::decode_reader;
+use crate::reader::limits::{BudgetedReader, ReadBudget, apply_limits, nc_depth, read_error};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::registry::{FormatReader, ReadSource};
+use crate::record::NcValue;
+use crate::writer::write_record_stream;
+
+/// How `convert_file` and `convert_bytes` read their input and write the output.
+#[derive(Debug, Clone, Default,)]
+pub struct ConvertOptions {
+    /// Reader name used instead of detecting the input format, as for `--file-type`.
+    pub file_type_override: Option<String,>,
+    /// Output format. `Text` is written as NDJSON.
+    pub format:             OutputFormat,
+    /// Error policy, reject collector and limits of the read.
+    pub read_config:        ReadConfig,
+    /// Character encoding of the input; `None` detects it. An input with an explicit encoding
+    /// is transcoded to UTF-8 as it is read; binary formats (Parquet, spreadsheets, ...) refuse
+    /// one.
+    pub encoding:           Option<&'static Encoding,>,
+    /// Column types forced on every record.
+    pub schema:             Option<SchemaOverride,>,
+    /// Compression and row groups of Parquet and Arrow IPC output. The other formats are written
+    /// uncompressed and accept only `none`; compress them in the writer, as an `OutputSink` does.
+    pub columnar:           ColumnarOptions,
+}
+
+/// What a conversion did.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct ConvertSummary {
+    pub rows_converted: u64,
+    /// Records skipped under a lenient error policy.
+    pub rows_rejected:  usize,
+    pub elapsed:        Duration,
+}
+
+impl fmt::Display for ConvertSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        write!(
+            f,
+            "Converted {} rows ({} rejected) in {:.2?}",
+            self.rows_converted, self.rows_rejected, self.elapsed
+        )
+    }
+}
+
+/// Converts `value` to `value_type`, reading it as `--type` reads text: booleans with the
+/// configured words, integers of any size. Other values are read from their JSON text, except
+/// that a whole float is an integer and a boolean stays a boolean. Nulls stay null.
+fn cast(
+    value_type: ValueType,
+    coercion: &ValueCoercion,
+    value: &NcValue,
+) -> Result<NcValue, String,> {
+    let text = match (value_type, value,) {
+        (_, NcValue::Null,) => return Ok(NcValue::Null,),
+        (ValueType::String, NcValue::String(_,),)
+        | (ValueType::Boolean, NcValue::Boolean(_,),) => return Ok(value.clone(),),
+        (ValueType::Integer, NcValue::Float(f,),) if fits_i64(*f,) => {
+            return Ok(NcValue::Integer(*f as i64,),);
+        },
+        (_, NcValue::Array(_,) | NcValue::Object(_,),) => {
+            return Err(format!("cannot convert {} to {}", value, value_type),);
+        },
+        (ValueType::String, other,) => other.to_string(),
+        (_, other,) => other.to_string().trim().to_string(),
+    };
+    value_type.parse(coercion, &text,)
+}
+
+/// Whether `f` is a whole number that `i64` holds exactly. `i64::MAX as f64` rounds up to
+/// 2^63, so the upper bound is exclusive.
+fn fits_i64(f: f64,) -> bool {
+    f.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(&f,)
+}
+
+/// Column types forced on converted records instead of the ones the reader produced. Columns
+/// a record does not have are left out; a value that cannot be converted fails the record,
+/// which the error policy then handles like any malformed record.
+#[derive(Debug, Clone, Default, PartialEq, Eq,)]
+pub struct SchemaOverride {
+    columns: Vec<(String, ValueType,),>,
+}
+
+impl SchemaOverride {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_column(mut self, name: impl Into<String,>, column_type: ValueType,) -> Self {
+        self.columns.push((name.into(), column_type,),);
+        self
+    }
+
+    /// Reads a JSON object mapping column names to the type names of `--type`, e.g.
+    /// `{"id": "integer", "zip": "string"}`.
+    pub fn from_file(path: &Path,) -> Result<Self, DataReaderError,> {
+        let text = std::fs::read_to_string(path,).map_err(|e| DataReaderError::FileReadError {
+            path:   path.to_path_buf(),
+            source: e,
+        },)?;
+        let parse_error = |source: Box<dyn std::error::Error + Send + Sync,>| {
+            DataReaderError::ParseError {
+                path: path.to_path_buf(),
+                source,
+                position: None,
+            }
+        };
+        let columns: Map<String, Value,> =
+            serde_json::from_str(&text,).map_err(|e| parse_error(Box::new(e,),),)?;
+        let mut schema = Self::new();
+        for (name, type_name,) in columns {
+            let column_type = type_name
+                .as_str()
+                .ok_or_else(|| format!("the type of column '{}' must be a string", name),)
+                .and_then(|type_name| type_name.parse::<ValueType>(),)
+                .map_err(|e| parse_error(e.into(),),)?;
+            schema = schema.with_column(name, column_type,);
+        }
+        Ok(schema,)
+    }
+
+    /// Applies the override to every record of `stream`, reading booleans with the words of
+    /// `coercion`. `source_path` labels errors.
+    pub fn apply(
+        &self,
+        stream: RecordStream,
+        source_path: &Path,
+        coercion: &ValueCoercion,
+    ) -> RecordStream {
+        let stream = Box::new(stream.map(|record| record.map(NcValue::from,),),);
+        typed_to_json_stream(self.apply_typed(stream, source_path, coercion,),)
+    }
+
+    /// `apply` for typed records.
+    pub fn apply_typed(
+        &self,
+        stream: TypedRecordStream,
+        source_path: &Path,
+        coercion: &ValueCoercion,
+    ) -> TypedRecordStream {
+        let schema = self.clone();
+        let coercion = coercion.clone();
+        let path = source_path.to_path_buf();
+        let mut record_index = 0u64;
+        Box::new(stream.map(move |record| {
+            let index = record_index;
+            record_index += 1;
+            match record? {
+                NcValue::Object(mut fields,) => match schema.cast_fields(&fields, &coercion,) {
+                    Ok(casts,) => {
+                        fields.extend(casts,);
+                        Ok(NcValue::Object(fields,),)
+                    },
+                    Err(message,) => {
+                        let record = NcValue::Object(fields,).to_json();
+                        Err(cast_error(&path, index, message, &record,),)
+                    },
+                },
+                other => Ok(other,),
+            }
+        },),)
+    }
+
+    /// The converted values of the overridden columns of one record.
+    fn cast_fields(
+        &self,
+        fields: &BTreeMap<String, NcValue,>,
+        coercion: &ValueCoercion,
+    ) -> Result<Vec<(String, NcValue,),>, String,> {
+        let mut casts = Vec::new();
+        for (name, column_type,) in &self.columns {
+            if let Some(value,) = fields.get(name,) {
+                let cast = cast(*column_type, coercion, value,)
+                    .map_err(|e| format!("column '{}': {}", name, e),)?;
+                casts.push((name.clone(), cast,),);
+            }
+        }
+        Ok(casts,)
+    }
+}
+
+fn cast_error(path: &Path, record_index: u64, message: String, record: &Value,) -> DataReaderError {
+    DataReaderError::ParseError {
+        path:     path.to_path_buf(),
+        source:   message.into(),
+        position: Some(Box::new(
+            ErrorPosition::default()
+                .with_record_index(record_index,)
+                .with_raw(record.to_string(),),
+        ),),
+    }
+}
+
+/// Converts a file to `options.format`, streaming its records from the reader to `writer`.
+/// Parquet and Arrow IPC output copies the records to a temporary file while it infers the
+/// schema.
+/// Only formats with a record stream (CSV, JSON/JSONL, XML, Parquet, ...) can be converted.
+pub fn convert_file<W: Write + Send,>(
+    input: &Path,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<ConvertSummary, DataReaderError,> {
+    let started = Instant::now();
+    let file_format = format_for_file(input, options.file_type_override.as_deref(),)?;
+    let reader = reader_for_format(input, &file_format,)?;
+    check_encoding(reader.as_ref(), options,)?;
+    let budget = start_read(input, &options.read_config,)?;
+    let source = match options.encoding {
+        Some(encoding,) => {
+            let file = File::open(input,).map_err(|e| read_error(input, e,),)?;
+            let file = Box::new(decode_reader(file, encoding,),);
+            ReadSource::Reader(file, input.to_path_buf(),)
+        },
+        None => ReadSource::file(input,),
+    };
+    let records = open_stream(reader.as_ref(), source, options,)?;
+    let rows = write_converted(records, input, budget, options, writer,)?;
+    Ok(summary(input, options, rows, started,),)
+}
+
+/// `convert_file` for in-memory data. `source_path` picks the reader by its extension, unless
+/// `options.file_type_override` is set, and labels errors.
+pub fn convert_bytes<W: Write + Send,>(
+    data: Bytes,
+    source_path: &Path,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<ConvertSummary, DataReaderError,> {
+    let started = Instant::now();
+    let budget = options.read_config.budget(source_path,);
+    budget.check()?;
+    budget.check_bytes(data.len() as u64,)?;
+    let reader = reader_for_bytes(&data, source_path, options.file_type_override.as_deref(),)?;
+    check_encoding(reader.as_ref(), options,)?;
+    let source = match options.encoding {
+        Some(encoding,) => {
+            let data = Box::new(decode_reader(Cursor::new(data,), encoding,),);
+            ReadSource::Reader(data, source_path.to_path_buf(),)
+        },
+        None => ReadSource::Bytes(data, source_path.to_path_buf(),),
+    };
+    let records = open_stream(reader.as_ref(), source, options,)?;
+    let rows = write_converted(records, source_path, budget, options, writer,)?;
+    Ok(summary(source_path, options, rows, started,),)
+}
+
+/// `convert_bytes` for a source that can only be read once, such as stdin. Records are
+/// converted as the input arrives.
+pub fn convert_reader<W: Write + Send,>(
+    input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<ConvertSummary, DataReaderError,> {
+    let started = Instant::now();
+    let budget = options.read_config.budget(source_path,);
+    budget.check()?;
+    let input = Box::new(BudgetedReader::new(input, budget.clone(),),);
+    let (reader, input,) =
+        reader_for_input(input, source_path, options.file_type_override.as_deref(),)?;
+    check_encoding(reader.as_ref(), options,)?;
+    let input: Box<dyn Read + Send,> = match options.encoding {
+        Some(encoding,) => Box::new(decode_reader(input, encoding,),),
+        None => input,
+    };
+    let source = ReadSource::Reader(input, source_path.to_path_buf(),);
+    let records = open_stream(reader.as_ref(), source, options,)?;
+    let rows = write_converted(records, source_path, budget, options, writer,)?;
+    Ok(summary(source_path, options, rows, started,),)
+}
+
+/// Fails when an encoding is given for a binary format, which transcoding would corrupt.
+fn check_encoding(
+    reader: &dyn FormatReader,
+    options: &ConvertOptions,
+) -> Result<(), DataReaderError,> {
+    match options.encoding {
+        Some(encoding,) if !reader.is_text() => Err(DataReaderError::InvalidConfig(format!(
+            "{} input is binary and cannot be read with the {} encoding",
+            reader.name(),
+            encoding.name()
+        ),),),
+        _ => Ok((),),
+    }
+}
+
+fn open_stream(
+    reader: &dyn FormatReader,
+    source: ReadSource,
+    options: &ConvertOptions,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    reader
+        .read_typed_stream(source, &options.read_config,)?
+        .ok_or_else(|| not_streaming(reader.name(),),)
+}
+
+/// Writes `records` in `options.format`, after the schema override, error policy, limits and
+/// filter.
+fn write_converted<W: Write + Send,>(
+    mut records: TypedRecordStream,
+    source_path: &Path,
+    budget: ReadBudget,
+    options: &ConvertOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let config = &options.read_config;
+    if let Some(schema,) = &options.schema {
+        records = schema.apply_typed(records, source_path, &config.coercion,);
+    }
+    let records =
+        apply_error_policy(records, source_path, config.error_policy, config.rejects.clone(),);
+    let records = config.filter.apply_typed(apply_limits(records, budget, nc_depth,),);
+
+    if options.format.is_columnar() {
+        return write_records(records, source_path, options.format, &options.columnar, writer,);
+    }
+
+    if let Some(codec,) = options.columnar.compression
+        && codec != ColumnarCompression::None
+    {
+        return Err(DataReaderError::InternalError(format!(
+            "{} output does not support {} compression; compress the writer instead",
+            options.format, codec
+        ),),);
+    }
+    let records = typed_to_json_stream(records,);
+    let mut writer = writer;
+    let rows = write_record_stream(records, options.format, &mut writer,)?;
+    flush(&mut writer,)?;
+    Ok(rows,)
+}
+
+fn summary(
+    source_path: &Path,
+    options: &ConvertOptions,
+    rows_converted: u64,
+    started: Instant,
+) -> ConvertSummary {
+    ConvertSummary {
+        rows_converted,
+        rows_rejected: options.read_config.rejects.report_for(source_path,).skipped,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Flushes `writer`, treating a closed pipe as success like `RecordWriter` does.
+fn flush<W: Write + ?Sized,>(writer: &mut W,) -> Result<(), DataReaderError,> {
+    match writer.flush() {
+        Err(e,) if e.kind() != io::ErrorKind::BrokenPipe => Err(write_error(e,),),
+        _ => Ok((),),
+    }
+}
+
+fn write_error(e: io::Error,) -> DataReaderError {
+    DataReaderError::InternalError(format!("Failed to write output: {}", e),)
+}
+
+fn not_streaming(reader_name: &str,) -> DataReaderError {
+    DataReaderError::UnsupportedFileFormat(format!(
+        "Format {} does not stream records and cannot be converted",
+        reader_name
+    ),)
+}
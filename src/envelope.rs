@@ -0,0 +1,264 @@
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::{DataReaderResult, FileErrorData, FileMetadata};
+
+/// Version of the [`ResultEnvelope`] layout. It changes whenever a field is renamed, removed or
+/// changes meaning; readers reject envelopes of any other version instead of misreading them.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// What an envelope holds: the reader that produced `data`, or one of the result kinds that are
+/// not tied to a reader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResultFormat {
+    Csv,
+    Gzip,
+    Image,
+    Json,
+    Markdown,
+    Parquet,
+    ParquetAnalysis,
+    Pdf,
+    Spreadsheet,
+    Sqlite,
+    Toml,
+    Text,
+    Xml,
+    Yaml,
+    Zip,
+    /// A reader registered from outside this crate; `data.format` names it.
+    Custom,
+    /// The file contents as read with `--all`.
+    Raw,
+    /// A file that could not be read; see `errors`.
+    Error,
+    /// Several files; `data` is an array of envelopes, each with its `path`.
+    Directory,
+    /// A record stream, which is written record by record and never enveloped.
+    Stream,
+    /// One record of a file in streamed directory output; `data` is the record.
+    Record,
+}
+
+/// The JSON and YAML output of a read: a tagged, versioned wrapper around a
+/// [`DataReaderResult`] that deserializes back into one with [`ResultEnvelope::into_result`].
+///
+/// `data` is the reader's summary, shaped by `format`. `schema` repeats the inferred schema of
+/// the summary, when the format has one. `errors` lists why the file could not be read; records
+/// skipped under a lenient error policy stay in `data.rejects`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize,)]
+pub struct ResultEnvelope {
+    pub version:  u32,
+    pub format:   ResultFormat,
+    /// The file the result is for; set on the entries of a directory result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path:     Option<PathBuf,>,
+    pub metadata: FileMetadata,
+    #[serde(default)]
+    pub schema:   Option<Value,>,
+    #[serde(default)]
+    pub data:     Value,
+    #[serde(default)]
+    pub errors:   Vec<FileErrorData,>,
+}
+
+impl ResultEnvelope {
+    /// Wraps a result. Streams and pending reads cannot be wrapped; they become a `stream`
+    /// envelope carrying an error.
+    pub fn from_result(result: &DataReaderResult, path: Option<&Path,>,) -> ResultEnvelope {
+        let (format, metadata, data, schema,) = match result {
+            DataReaderResult::Csv(d, m,) => {
+                (ResultFormat::Csv, m, to_value(d,), d.inferred_schema.as_ref().map(to_value,),)
+            },
+            DataReaderResult::Gzip(d, m,) => (ResultFormat::Gzip, m, to_value(d,), None,),
+            DataReaderResult::Image(d, m,) => (ResultFormat::Image, m, to_value(d,), None,),
+            DataReaderResult::Json(d, m,) => {
+                (ResultFormat::Json, m, to_value(d,), d.inferred_schema.as_ref().map(to_value,),)
+            },
+            DataReaderResult::Markdown(d, m,) => (ResultFormat::Markdown, m, to_value(d,), None,),
+            DataReaderResult::Parquet(d, m,) => {
+                (ResultFormat::Parquet, m, to_value(d,), Some(to_value(&d.column_schemas,),),)
+            },
+            DataReaderResult::ParquetAnalysis(d, m,) => {
+                (ResultFormat::ParquetAnalysis, m, to_value(d,), None,)
+            },
+            DataReaderResult::Pdf(d, m,) => (ResultFormat::Pdf, m, to_value(d,), None,),
+            DataReaderResult::Spreadsheet(d, m,) => {
+                (ResultFormat::Spreadsheet, m, to_value(d,), None,)
+            },
+            DataReaderResult::Sqlite(d, m,) => {
+                let schema = d
+                    .tables
+                    .iter()
+                    .map(|table| (table.name.clone(), to_value(&table.schema,),),)
+                    .collect::<serde_json::Map<_, _,>>();
+                (ResultFormat::Sqlite, m, to_value(d,), Some(Value::Object(schema,),),)
+            },
+            DataReaderResult::Toml(d, m,) => (ResultFormat::Toml, m, to_value(d,), None,),
+            DataReaderResult::Text(d, m,) => (ResultFormat::Text, m, to_value(d,), None,),
+            DataReaderResult::Xml(d, m,) => {
+                (ResultFormat::Xml, m, to_value(d,), d.inferred_schema.as_ref().map(to_value,),)
+            },
+            DataReaderResult::Yaml(d, m,) => (ResultFormat::Yaml, m, to_value(d,), None,),
+            DataReaderResult::Zip(d, m,) => (ResultFormat::Zip, m, to_value(d,), None,),
+            DataReaderResult::Custom(d, m,) => (ResultFormat::Custom, m, to_value(d,), None,),
+            DataReaderResult::RawContent(d, m,) => (ResultFormat::Raw, m, to_value(d,), None,),
+            DataReaderResult::Error(e, m,) => {
+                return ResultEnvelope::new(ResultFormat::Error, path, m, None, Value::Null,)
+                    .with_errors(vec![e.clone()],);
+            },
+            DataReaderResult::DirectoryResults(results, m,) => {
+                let entries = results
+                    .iter()
+                    .map(|(path, result,)| {
+                        to_value(&ResultEnvelope::from_result(result, Some(path,),),)
+                    },)
+                    .collect();
+                (ResultFormat::Directory, m, Value::Array(entries,), None,)
+            },
+            DataReaderResult::Stream(_, m,) => {
+                let error = FileErrorData {
+                    error:    "Record streams are written record by record, not as one result"
+                        .to_string(),
+                    position: None,
+                };
+                return ResultEnvelope::new(ResultFormat::Stream, path, m, None, Value::Null,)
+                    .with_errors(vec![error],);
+            },
+            DataReaderResult::Pending(_, m,) => {
+                let error = FileErrorData {
+                    error:    "Pending reads are opened when streamed, not wrapped as a result"
+                        .to_string(),
+                    position: None,
+                };
+                return ResultEnvelope::new(ResultFormat::Stream, path, m, None, Value::Null,)
+                    .with_errors(vec![error],);
+            },
+        };
+        ResultEnvelope::new(format, path, metadata, schema, data,)
+    }
+
+    /// Frames one record of the file at `path`, as streamed directory output writes it.
+    pub fn record(path: &Path, metadata: &FileMetadata, record: Value,) -> ResultEnvelope {
+        ResultEnvelope::new(ResultFormat::Record, Some(path,), metadata, None, record,)
+    }
+
+    fn new(
+        format: ResultFormat,
+        path: Option<&Path,>,
+        metadata: &FileMetadata,
+        schema: Option<Value,>,
+        data: Value,
+    ) -> ResultEnvelope {
+        ResultEnvelope {
+            version: ENVELOPE_VERSION,
+            format,
+            path: path.map(Path::to_path_buf,),
+            metadata: metadata.clone(),
+            schema,
+            data,
+            errors: Vec::new(),
+        }
+    }
+
+    fn with_errors(mut self, errors: Vec<FileErrorData,>,) -> ResultEnvelope {
+        self.errors = errors;
+        self
+    }
+
+    /// Parses JSON or YAML output (JSON is valid YAML), failing on any other
+    /// [`ENVELOPE_VERSION`].
+    pub fn parse(text: &str,) -> Result<ResultEnvelope, DataReaderError,> {
+        let envelope: ResultEnvelope = serde_yaml::from_str(text,).map_err(|e| {
+            DataReaderError::InternalError(format!("Invalid result envelope: {}", e),)
+        },)?;
+        envelope.check_version()?;
+        Ok(envelope,)
+    }
+
+    fn check_version(&self,) -> Result<(), DataReaderError,> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(DataReaderError::InternalError(format!(
+                "Unsupported result envelope version {} (expected {})",
+                self.version, ENVELOPE_VERSION
+            ),),);
+        }
+        Ok((),)
+    }
+
+    /// The result this envelope was made from. Fails for another version, for `stream` and
+    /// `record` envelopes, and when `data` does not have the shape `format` calls for.
+    pub fn into_result(self,) -> Result<DataReaderResult, DataReaderError,> {
+        self.check_version()?;
+        let m = self.metadata;
+        let data = self.data;
+        Ok(match self.format {
+            ResultFormat::Csv => DataReaderResult::Csv(from_value(data,)?, m,),
+            ResultFormat::Gzip => DataReaderResult::Gzip(from_value(data,)?, m,),
+            ResultFormat::Image => DataReaderResult::Image(from_value(data,)?, m,),
+            ResultFormat::Json => DataReaderResult::Json(from_value(data,)?, m,),
+            ResultFormat::Markdown => DataReaderResult::Markdown(from_value(data,)?, m,),
+            ResultFormat::Parquet => DataReaderResult::Parquet(from_value(data,)?, m,),
+            ResultFormat::ParquetAnalysis => {
+                DataReaderResult::ParquetAnalysis(from_value(data,)?, m,)
+            },
+            ResultFormat::Pdf => DataReaderResult::Pdf(from_value(data,)?, m,),
+            ResultFormat::Spreadsheet => DataReaderResult::Spreadsheet(from_value(data,)?, m,),
+            ResultFormat::Sqlite => DataReaderResult::Sqlite(from_value(data,)?, m,),
+            ResultFormat::Toml => DataReaderResult::Toml(from_value(data,)?, m,),
+            ResultFormat::Text => DataReaderResult::Text(from_value(data,)?, m,),
+            ResultFormat::Xml => DataReaderResult::Xml(from_value(data,)?, m,),
+            ResultFormat::Yaml => DataReaderResult::Yaml(from_value(data,)?, m,),
+            ResultFormat::Zip => DataReaderResult::Zip(from_value(data,)?, m,),
+            ResultFormat::Custom => DataReaderResult::Custom(from_value(data,)?, m,),
+            ResultFormat::Raw => DataReaderResult::RawContent(from_value(data,)?, m,),
+            ResultFormat::Error => {
+                let error = self.errors.into_iter().next().ok_or_else(|| {
+                    DataReaderError::InternalError(
+                        "Error envelope without an error".to_string(),
+                    )
+                },)?;
+                DataReaderResult::Error(error, m,)
+            },
+            ResultFormat::Directory => {
+                let entries: Vec<ResultEnvelope,> = from_value(data,)?;
+                let results = entries
+                    .into_iter()
+                    .map(|entry| {
+                        let path = entry.path.clone().unwrap_or_default();
+                        Ok((path, entry.into_result()?,),)
+                    },)
+                    .collect::<Result<_, DataReaderError,>>()?;
+                DataReaderResult::DirectoryResults(results, m,)
+            },
+            ResultFormat::Stream => {
+                return Err(DataReaderError::InternalError(
+                    "A stream envelope has no result to restore".to_string(),
+                ),);
+            },
+            ResultFormat::Record => {
+                return Err(DataReaderError::InternalError(
+                    "A record envelope holds one streamed record in `data`, not a result"
+                        .to_string(),
+                ),);
+            },
+        },)
+    }
+}
+
+fn to_value<T: Serialize + ?Sized,>(value: &T,) -> Value {
+    serde_json::to_value(value,).unwrap_or_else(|e| {
+        serde_json::json!({ "error": format!("Failed to serialize result: {}", e) })
+    },)
+}
+
+fn from_value<T: DeserializeOwned,>(data: Value,) -> Result<T, DataReaderError,> {
+    serde_json::from_value(data,).map_err(|e| {
+        DataReaderError::InternalError(format!("Invalid result envelope data: {}", e),)
+    },)
+}
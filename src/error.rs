@@ -1,5 +1,10 @@
+use std::fmt;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
+
+use crate::reader::limits::Limit;
+
 #[derive(Debug, thiserror::Error,)]
 pub enum DataReaderError {
     #[error("File not found or could not be read: {path} - {source}")]
@@ -8,11 +13,13 @@ pub enum DataReaderError {
         #[source]
         source: std::io::Error,
     },
-    #[error("Error parsing file {path}: {source}")]
+    #[error("Error parsing file {path}{}: {source}", position_suffix(.position))]
     ParseError {
-        path:   PathBuf,
+        path:     PathBuf,
         #[source]
-        source: Box<dyn std::error::Error + Send + Sync,>,
+        source:   Box<dyn std::error::Error + Send + Sync,>,
+        /// Where in the input the error was detected, when the format reports it.
+        position: Option<Box<ErrorPosition,>,>,
     },
     #[error("Internal error: {0}")]
     InternalError(String,),
@@ -20,4 +27,114 @@ pub enum DataReaderError {
     IsADirectory { path: PathBuf, },
     #[error("Unsupported file format: {0}")]
     UnsupportedFileFormat(String,),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String,),
+    #[error("Read limit exceeded for {path}: {limit}")]
+    LimitExceeded { path: PathBuf, limit: Limit, },
+    #[error("Reading {path} was cancelled")]
+    Cancelled { path: PathBuf, },
+    #[error("Too many malformed records in {path}: more than {limit} skipped. Last: {source}")]
+    RejectLimitExceeded {
+        path:   PathBuf,
+        limit:  usize,
+        #[source]
+        source: Box<DataReaderError,>,
+    },
+}
+
+impl DataReaderError {
+    /// The input position of a parse error, if one is known.
+    pub fn position(&self,) -> Option<&ErrorPosition,> {
+        match self {
+            DataReaderError::ParseError {
+                position, ..
+            } => position.as_deref(),
+            DataReaderError::RejectLimitExceeded {
+                source, ..
+            } => source.position(),
+            _ => None,
+        }
+    }
+}
+
+/// Location of a parse error in its input.
+///
+/// Every field is optional because formats report different coordinates: text formats give a
+/// line and column, CSV adds the byte offset and record index, Parquet the row group. Lines and
+/// columns are 1-based; byte offsets, record indexes and row groups are 0-based.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,)]
+pub struct ErrorPosition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line:         Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column:       Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset:  Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_index: Option<u64,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_group:    Option<usize,>,
+    /// The raw text of the offending record, when the reader has it. Not part of `Display`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw:          Option<String,>,
+}
+
+impl ErrorPosition {
+    pub fn line_column(line: u64, column: u64,) -> Self {
+        ErrorPosition {
+            line: Some(line,),
+            column: Some(column,),
+            ..Default::default()
+        }
+    }
+
+    /// Computes the line and column of `byte_offset` in `content`. The column counts characters,
+    /// not bytes. Offsets past the end are clamped to the end of `content`.
+    pub fn from_offset(content: &str, byte_offset: usize,) -> Self {
+        let mut offset = byte_offset.min(content.len(),);
+        while !content.is_char_boundary(offset,) {
+            offset -= 1;
+        }
+        let before = &content[..offset];
+        let line = before.matches('\n',).count() as u64 + 1;
+        let line_start = before.rfind('\n',).map_or(0, |i| i + 1,);
+        let column = before[line_start..].chars().count() as u64 + 1;
+        ErrorPosition {
+            line: Some(line,),
+            column: Some(column,),
+            byte_offset: Some(byte_offset as u64,),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_record_index(mut self, record_index: u64,) -> Self {
+        self.record_index = Some(record_index,);
+        self
+    }
+
+    pub fn with_raw(mut self, raw: impl Into<String,>,) -> Self {
+        self.raw = Some(raw.into(),);
+        self
+    }
+}
+
+impl fmt::Display for ErrorPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        let parts = [
+            self.line.map(|v| format!("line {}", v),),
+            self.column.map(|v| format!("column {}", v),),
+            self.byte_offset.map(|v| format!("byte {}", v),),
+            self.record_index.map(|v| format!("record {}", v),),
+            self.row_group.map(|v| format!("row group {}", v),),
+        ];
+        let parts: Vec<String,> = parts.into_iter().flatten().collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+fn position_suffix(position: &Option<Box<ErrorPosition,>,>,) -> String {
+    match position.as_ref().map(|p| p.to_string(),) {
+        Some(rendered,) if !rendered.is_empty() => format!(" at {}", rendered),
+        _ => String::new(),
+    }
 }
@@ -1,13 +1,30 @@
+use std::fmt;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Cursor, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use tracing::error;
+use arrow::datatypes::SchemaRef;
+use bytes::Bytes;
+use futures::stream::{self, StreamExt};
+use tracing::{Span, error, field, info, info_span, warn};
 use walkdir::WalkDir;
 
+use crate::columnar::{ColumnarOptions, write_batches, write_records};
 use crate::error::DataReaderError;
-use crate::nc_reader_result::{DataReaderResult, FileMetadata};
+use crate::nc_reader_result::{
+    DataReaderResult, FileErrorData, FileMetadata, PendingRead, RecordStream, TypedRecordStream,
+};
 use crate::output::{OutputFormat, OutputMode};
+use crate::reader::async_stream::{AsyncRecordStream, open_async_stream};
+use crate::reader::error_policy::apply_error_policy;
+use crate::reader::limits::{
+    BudgetedReader, ReadBudget, apply_batch_limits, apply_limits, json_depth, nc_depth, read_error,
+};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::RecordBatchStream;
+use crate::reader::registry::{FormatReader, ReadSource, registry_snapshot};
 
 #[derive(Debug, PartialEq, Clone,)]
 pub enum FileFormat {
@@ -25,60 +42,38 @@ pub enum FileFormat {
     Xml,
     Yaml,
     Zip,
+    /// A format provided by a reader registered through `register_format_reader`.
+    Other(String,),
     Unknown,
 }
 
+const MAGIC_HEADER_LEN: usize = 64;
+
 fn detect_format_from_magic_bytes(file_path: &Path,) -> Option<FileFormat,> {
-    let mut file = match File::open(file_path,) {
+    let file = match File::open(file_path,) {
         Ok(file,) => file,
         Err(_,) => return None,
     };
 
-    let mut buffer = [0; 8];
-    if file.read(&mut buffer,).is_err() {
+    let mut buffer = Vec::with_capacity(MAGIC_HEADER_LEN,);
+    if file
+        .take(MAGIC_HEADER_LEN as u64,)
+        .read_to_end(&mut buffer,)
+        .is_err()
+    {
         return None;
     }
 
-    if buffer.starts_with(b"PAR1",) {
-        return Some(FileFormat::Parquet,);
-    }
-    if buffer.starts_with(b"PK\x03\x04",) {
-        return Some(FileFormat::Zip,);
-    }
-    if buffer.starts_with(b"\x1f\x8b",) {
-        return Some(FileFormat::Gzip,);
-    }
-    if buffer.starts_with(b"%PDF",) {
-        return Some(FileFormat::Pdf,);
-    }
-    if buffer.starts_with(b"\x89PNG\r\n\x1a\n",) {
-        return Some(FileFormat::Image,);
-    }
-    if buffer.starts_with(b"\xff\xd8\xff",) {
-        return Some(FileFormat::Image,);
-    }
-
-    None
+    registry_snapshot()
+        .by_magic(&buffer,)
+        .map(|reader| reader.file_format(),)
 }
 
 pub fn get_file_format(file_path: &Path,) -> FileFormat {
-    match file_path.extension().and_then(|s| s.to_str(),) {
-        Some("xlsx",) | Some("xls",) | Some("ods",) => return FileFormat::Spreadsheet,
-        Some("csv",) => return FileFormat::Csv,
-        Some("json",) | Some("jsonl",) => return FileFormat::Json,
-        Some("md",) => return FileFormat::Markdown,
-        Some("parquet",) => return FileFormat::Parquet,
-        Some("pdf",) => return FileFormat::Pdf,
-        Some("sqlite",) | Some("db",) => return FileFormat::Sqlite,
-        Some("toml",) => return FileFormat::Toml,
-        Some("txt",) => return FileFormat::Text,
-        Some("xml",) => return FileFormat::Xml,
-        Some("yaml",) | Some("yml",) => return FileFormat::Yaml,
-        Some("zip",) => return FileFormat::Zip,
-        Some("gz",) => return FileFormat::Gzip,
-        Some("jpg",) | Some("jpeg",) | Some("png",) | Some("gif",) | Some("bmp",)
-        | Some("webp",) | Some("svg",) => return FileFormat::Image,
-        _ => {},
+    if let Some(ext,) = file_path.extension().and_then(|s| s.to_str(),)
+        && let Some(reader,) = registry_snapshot().by_extension(ext,)
+    {
+        return reader.file_format();
     }
 
     if let Some(format,) = detect_format_from_magic_bytes(file_path,) {
@@ -88,6 +83,18 @@ pub fn get_file_format(file_path: &Path,) -> FileFormat {
     FileFormat::Unknown
 }
 
+pub(crate) fn reader_for_format(
+    file_path: &Path,
+    file_format: &FileFormat,
+) -> Result<Arc<dyn FormatReader,>, DataReaderError,> {
+    registry_snapshot().by_format(file_format,).ok_or_else(|| {
+        DataReaderError::InternalError(format!(
+            "Unsupported file format for data reading: {}",
+            file_path.display()
+        ),)
+    },)
+}
+
 #[derive(Clone,)]
 pub struct FileReaderOptions {
     pub head:               Option<usize,>,
@@ -95,8 +102,31 @@ pub struct FileReaderOptions {
     pub output_mode:        OutputMode,
     pub output_format:      OutputFormat,
     pub recursive:          bool,
+    /// Extensions (`csv`, `csv.gz`) of the files kept by directory and pattern reads.
     pub filter_exts:        Option<Vec<String,>,>,
     pub output_path:        Option<PathBuf,>,
+    /// Maximum number of files read concurrently by `read_directory_content` and
+    /// `read_inputs_content`. In `OutputMode::Stream` files are read one at a time, as they are
+    /// written.
+    pub jobs:               usize,
+    /// Reader settings such as the error policy. Clones share one reject collector.
+    pub read_config:        ReadConfig,
+}
+
+impl Default for FileReaderOptions {
+    fn default() -> Self {
+        Self {
+            head:               None,
+            file_type_override: None,
+            output_mode:        OutputMode::default(),
+            output_format:      OutputFormat::default(),
+            recursive:          false,
+            filter_exts:        None,
+            output_path:        None,
+            jobs:               1,
+            read_config:        ReadConfig::default(),
+        }
+    }
 }
 
 pub fn read_file_to_data(
@@ -104,172 +134,42 @@ pub fn read_file_to_data(
     head: Option<usize,>,
     file_format: FileFormat,
 ) -> Result<DataReaderResult, DataReaderError,> {
-    let base_metadata =
-        std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
+    read_file_to_data_with_config(file_path, head, file_format, &ReadConfig::default(),)
+}
+
+/// `read_file_to_data` with the error policy, limits and reader settings of `config`.
+pub fn read_file_to_data_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    file_format: FileFormat,
+    config: &ReadConfig,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let budget = start_read(file_path, config,)?;
+    let reader = reader_for_format(file_path, &file_format,)?;
+    let result = reader.read_data(ReadSource::file(file_path,), head, config,)?;
+    budget.check()?;
+    Ok(result,)
+}
+
+/// Starts the limit budget of one file read and checks what can be checked up front:
+/// cancellation and the size of the file.
+pub(crate) fn start_read(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<ReadBudget, DataReaderError,> {
+    let budget = config.budget(file_path,);
+    budget.check()?;
+    let size = std::fs::metadata(file_path,)
+        .map_err(|e| DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
             source: e,
-        },)?;
-    let file_size = base_metadata.len();
-
-    match file_format {
-        FileFormat::Csv => crate::reader::csv_reader::read_csv_data(file_path, head,).map(|data| {
-            DataReaderResult::Csv(
-                data,
-                FileMetadata {
-                    size:       file_size,
-                    line_count: None,
-                },
-            )
-        },),
-        FileFormat::Gzip => crate::reader::gzip_reader::read_gzip_data(file_path,).map(|data| {
-            DataReaderResult::Gzip(
-                data,
-                FileMetadata {
-                    size:       file_size,
-                    line_count: None,
-                },
-            )
-        },),
-        FileFormat::Image => crate::reader::image_reader::read_image_data(file_path,).map(|data| {
-            DataReaderResult::Image(
-                data,
-                FileMetadata {
-                    size:       file_size,
-                    line_count: None,
-                },
-            )
-        },),
-        FileFormat::Json => {
-            crate::reader::json_reader::read_json_value(file_path, head,).map(|data| {
-                let line_count = data.line_count;
-                DataReaderResult::Json(
-                    data,
-                    FileMetadata {
-                        size: file_size,
-                        line_count,
-                    },
-                )
-            },)
-        },
-        FileFormat::Markdown => {
-            crate::reader::md_reader::read_md_content(file_path, head,).map(|data| {
-                let line_count = data.content.lines().count();
-                DataReaderResult::Markdown(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: Some(line_count,),
-                    },
-                )
-            },)
-        },
-        FileFormat::Parquet => crate::reader::parquet_reader::read_parquet_data(file_path, head,)
-            .map(|data| {
-                let num_rows = data.num_rows;
-                DataReaderResult::Parquet(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: Some(num_rows as usize,),
-                    },
-                )
-            },),
-        FileFormat::Pdf => crate::reader::pdf_reader::read_pdf_text(file_path, head,).map(|data| {
-            let line_count = data.line_count;
-            DataReaderResult::Pdf(
-                data,
-                FileMetadata {
-                    size:       file_size,
-                    line_count: Some(line_count,),
-                },
-            )
-        },),
-        FileFormat::Spreadsheet => {
-            crate::reader::spreadsheet_reader::read_spreadsheet_data(file_path,).map(|data| {
-                DataReaderResult::Spreadsheet(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: None,
-                    },
-                )
-            },)
-        },
-        FileFormat::Sqlite => {
-            crate::reader::sqlite_reader::read_sqlite_data(file_path,).map(|data| {
-                DataReaderResult::Sqlite(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: None,
-                    },
-                )
-            },)
-        },
-        FileFormat::Toml => {
-            crate::reader::toml_reader::read_toml_value(file_path, head,).map(|data| {
-                DataReaderResult::Toml(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: None,
-                    },
-                )
-            },)
-        },
-        FileFormat::Text => {
-            crate::reader::txt_reader::read_txt_content(file_path, head,).map(|data| {
-                let line_count = data.line_count;
-                let total_size = data.total_size;
-                DataReaderResult::Text(
-                    data,
-                    FileMetadata {
-                        size:       total_size,
-                        line_count: Some(line_count,),
-                    },
-                )
-            },)
-        },
-        FileFormat::Xml => {
-            crate::reader::xml_reader::read_xml_content(file_path, head,).map(|data| {
-                let line_count = data.content.lines().count();
-                DataReaderResult::Xml(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: Some(line_count,),
-                    },
-                )
-            },)
-        },
-        FileFormat::Yaml => {
-            crate::reader::yaml_reader::read_yaml_value(file_path, head,).map(|data| {
-                DataReaderResult::Yaml(
-                    data,
-                    FileMetadata {
-                        size:       file_size,
-                        line_count: None,
-                    },
-                )
-            },)
-        },
-        FileFormat::Zip => crate::reader::zip_reader::read_zip_data(file_path,).map(|data| {
-            DataReaderResult::Zip(
-                data,
-                FileMetadata {
-                    size:       file_size,
-                    line_count: None,
-                },
-            )
-        },),
-        FileFormat::Unknown => Err(DataReaderError::InternalError(format!(
-            "Unsupported file format for data reading: {}",
-            file_path.display()
-        ),),),
-    }
+        },)?
+        .len();
+    budget.check_bytes(size,)?;
+    Ok(budget,)
 }
 
-fn serialize_raw_content_to_string(
+pub(crate) fn serialize_raw_content_to_string(
     content: String,
     output_format: OutputFormat,
     file_type: &str,
@@ -298,10 +198,14 @@ fn serialize_raw_content_to_string(
                 ),)
             },)
         },
-        _ => Err(DataReaderError::InternalError(format!(
+        OutputFormat::Text => Err(DataReaderError::InternalError(format!(
             "Unsupported output format for {} raw content: {:?}",
             file_type, output_format
         ),),),
+        _ => {
+            let value = serde_json::json!({ "content": content });
+            crate::writer::format_value(&value, output_format,)
+        },
     }
 }
 
@@ -309,60 +213,44 @@ pub fn read_file_to_raw_content(
     file_path: &Path,
     head: Option<usize,>,
     output_format: OutputFormat,
+) -> Result<String, DataReaderError,> {
+    read_file_to_raw_content_with_config(file_path, head, output_format, &ReadConfig::default(),)
+}
+
+/// `read_file_to_raw_content` with the limits and reader settings of `config`. Raw content is
+/// never filtered, so a `config.filter` with a predicate or projection is an error.
+pub fn read_file_to_raw_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    output_format: OutputFormat,
+    config: &ReadConfig,
 ) -> Result<String, DataReaderError,> {
     let format = get_file_format(file_path,);
-    match format {
-        FileFormat::Csv => crate::reader::csv_reader::get_csv_raw_content(file_path, head,),
-        FileFormat::Json => crate::reader::json_reader::get_json_raw_content(file_path, head,),
-        FileFormat::Toml => crate::reader::toml_reader::get_toml_raw_content(file_path, head,),
-        FileFormat::Yaml => crate::reader::yaml_reader::get_yaml_raw_content(file_path, head,),
-        FileFormat::Markdown => {
-            let markdown_data = crate::reader::md_reader::read_md_content(file_path, head,)?;
-            serialize_raw_content_to_string(markdown_data.content, output_format, "Markdown",)
-        },
-        FileFormat::Pdf => {
-            let pdf_data = crate::reader::pdf_reader::read_pdf_text(file_path, head,)?;
-            serialize_raw_content_to_string(pdf_data.content, output_format, "PDF",)
-        },
-        FileFormat::Text => {
-            let text_data = crate::reader::txt_reader::read_txt_content(file_path, head,)?;
-            serialize_raw_content_to_string(text_data.content, output_format, "Text",)
-        },
-        FileFormat::Xml => {
-            let xml_data = crate::reader::xml_reader::read_xml_content(file_path, head,)?;
-            serialize_raw_content_to_string(xml_data.content, output_format, "XML",)
-        },
-        FileFormat::Parquet => {
-            let all_rows = crate::reader::parquet_reader::read_full_parquet_content(file_path,)?;
-            match output_format {
-                OutputFormat::Json => serde_json::to_string_pretty(&all_rows,).map_err(|e| {
-                    DataReaderError::InternalError(format!(
-                        "Failed to serialize Parquet raw content to JSON: {}",
-                        e
-                    ),)
-                },),
-                OutputFormat::Yaml => serde_yaml::to_string(&all_rows,).map_err(|e| {
-                    DataReaderError::InternalError(format!(
-                        "Failed to serialize Parquet raw content to YAML: {}",
-                        e
-                    ),)
-                },),
-                _ => Err(DataReaderError::InternalError(format!(
-                    "Unsupported output format for Parquet raw content: {:?}",
-                    output_format
-                ),),),
-            }
-        },
-        _ => Err(DataReaderError::InternalError(format!(
+    let reader = registry_snapshot().by_format(&format,).ok_or_else(|| {
+        DataReaderError::InternalError(format!(
             "Unsupported file format for raw content output: {}",
             file_path.display()
-        ),),),
-    }
+        ),)
+    },)?;
+    config.filter.require_empty("raw content",)?;
+    let budget = start_read(file_path, config,)?;
+    let content = reader.read_raw(ReadSource::file(file_path,), head, output_format, config,)?;
+    budget.check()?;
+    Ok(content,)
 }
 
 pub fn read_file_to_stream(
     file_path: &Path,
     file_format: FileFormat,
+) -> Result<DataReaderResult, DataReaderError,> {
+    read_file_to_stream_with_config(file_path, file_format, &ReadConfig::default(),)
+}
+
+/// `read_file_to_stream` with the error policy of `config` applied to the record stream.
+pub fn read_file_to_stream_with_config(
+    file_path: &Path,
+    file_format: FileFormat,
+    config: &ReadConfig,
 ) -> Result<DataReaderResult, DataReaderError,> {
     let base_metadata =
         std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -375,63 +263,188 @@ pub fn read_file_to_stream(
         line_count: None,
     };
 
-    match file_format {
-        FileFormat::Csv => {
-            let (_headers, stream,) = crate::reader::csv_reader::read_csv_stream(file_path,)?;
-            Ok(DataReaderResult::Stream(stream, metadata,),)
-        },
-        FileFormat::Json => {
-            let stream = crate::reader::json_reader::read_json_stream(file_path,)?;
-            Ok(DataReaderResult::Stream(stream, metadata,),)
-        },
-        FileFormat::Xml => {
-            let stream = crate::reader::xml_reader::create_xml_stream(file_path,)?;
-            Ok(DataReaderResult::Stream(stream, metadata,),)
-        },
-        FileFormat::Parquet => {
-            let stream = crate::reader::parquet_reader::read_parquet_stream(file_path,)?;
-            Ok(DataReaderResult::Stream(stream, metadata,),)
-        },
-        // For other formats, we don't have a record-based stream yet, so fall back
-        _ => read_file_to_data(file_path, None, file_format,),
+    let budget = start_read(file_path, config,)?;
+    let reader = reader_for_format(file_path, &file_format,)?;
+    match reader.read_stream(ReadSource::file(file_path,), config,)? {
+        Some(stream,) => Ok(DataReaderResult::Stream(
+            guard_stream(stream, file_path, budget, config,),
+            metadata,
+        ),),
+        // For formats without a record-based stream, fall back to the summary reader
+        None => reader.read_data(ReadSource::file(file_path,), None, config,),
     }
 }
 
+/// Applies the error policy, the limits and the filter of `config` to a record stream, in that
+/// order.
+fn guard_stream(
+    stream: RecordStream,
+    file_path: &Path,
+    budget: ReadBudget,
+    config: &ReadConfig,
+) -> RecordStream {
+    let stream =
+        apply_error_policy(stream, file_path, config.error_policy, config.rejects.clone(),);
+    config.filter.apply(apply_limits(stream, budget, json_depth,),)
+}
+
+/// Runs blocking reader work on tokio's blocking pool so async callers never stall the runtime.
+async fn run_blocking<T, F,>(work: F,) -> Result<T, DataReaderError,>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, DataReaderError,> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work,)
+        .await
+        .map_err(|e| DataReaderError::InternalError(format!("Reader task failed: {}", e),),)?
+}
+
+/// Opens a typed record stream over a file. Fails for formats that are not record based.
+pub fn read_file_to_typed_stream(
+    file_path: &Path,
+    file_format: FileFormat,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    read_file_to_typed_stream_with_config(file_path, file_format, &ReadConfig::default(),)
+}
+
+/// `read_file_to_typed_stream` with the error policy, limits, filter and reader settings of
+/// `config`.
+pub fn read_file_to_typed_stream_with_config(
+    file_path: &Path,
+    file_format: FileFormat,
+    config: &ReadConfig,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    let budget = start_read(file_path, config,)?;
+    let reader = reader_for_format(file_path, &file_format,)?;
+    let stream = reader.read_typed_stream(ReadSource::file(file_path,), config,)?.ok_or_else(|| {
+        DataReaderError::UnsupportedFileFormat(format!(
+            "Format {} does not support record streaming",
+            reader.name()
+        ),)
+    },)?;
+    let stream =
+        apply_error_policy(stream, file_path, config.error_policy, config.rejects.clone(),);
+    let stream = apply_limits(stream, budget, nc_depth,);
+    Ok(config.filter.apply_typed(stream,),)
+}
+
+/// Reads a tabular file as Arrow record batches of at most `batch_size` rows, together with
+/// the inferred Arrow schema. Fails for formats that are not tabular.
+pub fn read_file_to_batches(
+    file_path: &Path,
+    file_format: FileFormat,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    read_file_to_batches_with_config(file_path, file_format, batch_size, &ReadConfig::default(),)
+}
+
+/// `read_file_to_batches` with the limits and reader settings of `config`. Cancellation and the
+/// deadline are checked before each batch, and its rows count towards `max_records`. Batches
+/// cannot be filtered; read a record stream to apply `config.filter`.
+pub fn read_file_to_batches_with_config(
+    file_path: &Path,
+    file_format: FileFormat,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    config.filter.require_empty("record batches",)?;
+    let budget = start_read(file_path, config,)?;
+    let reader = reader_for_format(file_path, &file_format,)?;
+    let (schema, batches,) =
+        reader.read_batches(ReadSource::file(file_path,), batch_size, config,)?.ok_or_else(
+            || {
+                DataReaderError::UnsupportedFileFormat(format!(
+                    "Format {} cannot be read as record batches",
+                    reader.name()
+                ),)
+            },
+        )?;
+    // Schema inference may already have read the whole file.
+    budget.check()?;
+    Ok((schema, apply_batch_limits(batches, budget,),),)
+}
+
+/// Opens an async record stream over a file. The file is parsed on the blocking pool and records
+/// are delivered through a bounded channel. Fails for formats that are not record based.
+pub async fn read_file_to_async_stream(
+    file_path: &Path,
+    file_format: FileFormat,
+) -> Result<AsyncRecordStream, DataReaderError,> {
+    read_file_to_async_stream_with_config(file_path, file_format, &ReadConfig::default(),).await
+}
+
+/// `read_file_to_async_stream` with the error policy, limits, filter and reader settings of
+/// `config`. Cancelling `config` also stops the blocking reader.
+pub async fn read_file_to_async_stream_with_config(
+    file_path: &Path,
+    file_format: FileFormat,
+    config: &ReadConfig,
+) -> Result<AsyncRecordStream, DataReaderError,> {
+    let reader = reader_for_format(file_path, &file_format,)?;
+    let path = file_path.to_path_buf();
+    let config = config.clone();
+    open_async_stream(move || {
+        let budget = start_read(&path, &config,)?;
+        let stream = reader.read_stream(ReadSource::file(&path,), &config,)?.ok_or_else(|| {
+            DataReaderError::UnsupportedFileFormat(format!(
+                "Format {} does not support record streaming",
+                reader.name()
+            ),)
+        },)?;
+        Ok(guard_stream(stream, &path, budget, &config,),)
+    },)
+    .await
+}
+
 pub async fn read_file_content(
     file_path: &Path,
     options: FileReaderOptions,
 ) -> Result<DataReaderResult, DataReaderError,> {
-    let determined_format = if let Some(file_type_str,) = &options.file_type_override {
-        match file_type_str.to_lowercase().as_str() {
-            "csv" => FileFormat::Csv,
-            "gz" => FileFormat::Gzip,
-            "image" => FileFormat::Image,
-            "json" => FileFormat::Json,
-            "md" => FileFormat::Markdown,
-            "parquet" => FileFormat::Parquet,
-            "pdf" => FileFormat::Pdf,
-            "spreadsheet" => FileFormat::Spreadsheet,
-            "sqlite" => FileFormat::Sqlite,
-            "toml" => FileFormat::Toml,
-            "txt" => FileFormat::Text,
-            "xml" => FileFormat::Xml,
-            "yaml" => FileFormat::Yaml,
-            "zip" => FileFormat::Zip,
-            _ => {
-                return Err(DataReaderError::UnsupportedFileFormat(format!(
-                    "Unsupported file type override: {}",
-                    file_type_str
-                ),),);
-            },
-        }
-    } else {
-        get_file_format(file_path,)
-    };
+    let path = file_path.to_path_buf();
+    run_blocking(move || read_file_content_blocking(&path, options,),).await
+}
+
+/// The format of a file: the `file_type_override` if given, otherwise detected from the
+/// extension and magic bytes.
+pub(crate) fn format_for_file(
+    file_path: &Path,
+    file_type_override: Option<&str,>,
+) -> Result<FileFormat, DataReaderError,> {
+    match file_type_override {
+        Some(file_type_str,) => match registry_snapshot().by_name(file_type_str,) {
+            Some(reader,) => Ok(reader.file_format(),),
+            None => Err(DataReaderError::UnsupportedFileFormat(format!(
+                "Unsupported file type override: {}",
+                file_type_str
+            ),),),
+        },
+        None => Ok(get_file_format(file_path,),),
+    }
+}
+
+/// Synchronous body of `read_file_content`, for callers that are not running inside tokio.
+pub fn read_file_content_blocking(
+    file_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let bytes = std::fs::metadata(file_path,).map_or(0, |m| m.len(),);
+    traced_result(file_path, move || bytes, || read_file_untraced(file_path, options,),)
+}
+
+fn read_file_untraced(
+    file_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let determined_format = format_for_file(file_path, options.file_type_override.as_deref(),)?;
 
     match options.output_mode {
         OutputMode::FullRaw => {
-            let raw_content =
-                read_file_to_raw_content(file_path, options.head, options.output_format,)?;
+            let raw_content = read_file_to_raw_content_with_config(
+                file_path,
+                options.head,
+                options.output_format,
+                &options.read_config,
+            )?;
             let metadata =
                 std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
                     path:   file_path.to_path_buf(),
@@ -445,13 +458,23 @@ pub async fn read_file_content(
                 },
             ),)
         },
-        OutputMode::SchemaOnly | OutputMode::Default => {
-            read_file_to_data(file_path, options.head, determined_format,)
+        OutputMode::SchemaOnly | OutputMode::Default => read_file_to_data_with_config(
+            file_path,
+            options.head,
+            determined_format,
+            &options.read_config,
+        ),
+        OutputMode::Stream => {
+            read_file_to_stream_with_config(file_path, determined_format, &options.read_config,)
         },
-        OutputMode::Stream => read_file_to_stream(file_path, determined_format,),
         OutputMode::Analyze => match determined_format {
             FileFormat::Parquet => {
-                let data = crate::reader::parquet_reader::read_parquet_nc_for_analysis(file_path,)?;
+                options.read_config.filter.require_empty("Parquet analysis",)?;
+                start_read(file_path, &options.read_config,)?;
+                let data = crate::reader::parquet_reader::read_parquet_nc_for_analysis_with_config(
+                    file_path,
+                    &options.read_config,
+                )?;
                 let metadata =
                     std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
                         path:   file_path.to_path_buf(),
@@ -466,76 +489,450 @@ pub async fn read_file_content(
                     },
                 ),)
             },
-            _ => read_file_to_data(file_path, options.head, determined_format,),
+            _ => read_file_to_data_with_config(
+                file_path,
+                options.head,
+                determined_format,
+                &options.read_config,
+            ),
         },
     }
 }
 
-pub async fn read_directory_content(
-    directory_path: &Path,
+/// Reads in-memory data (for example stdin) that has no backing file. The format comes from the
+/// `file_type_override`, the extension of `source_path`, or the leading magic bytes. Outside
+/// `OutputMode::Stream`, the data gets the same summary as a file of that format; formats that
+/// can only be summarized from a file on disk fail.
+pub async fn read_bytes_content(
+    data: Bytes,
+    source_path: &Path,
     options: FileReaderOptions,
 ) -> Result<DataReaderResult, DataReaderError,> {
-    let mut results: Vec<(PathBuf, DataReaderResult,),> = Vec::new();
+    let path = source_path.to_path_buf();
+    run_blocking(move || read_bytes_content_blocking(data, &path, options,),).await
+}
 
-    let walker = if options.recursive {
-        WalkDir::new(directory_path,)
-    } else {
-        WalkDir::new(directory_path,).max_depth(1,)
+/// The reader for in-memory data: the `file_type_override` if given, otherwise found by the
+/// extension of `source_path` or the leading magic bytes.
+pub(crate) fn reader_for_bytes(
+    data: &[u8],
+    source_path: &Path,
+    file_type_override: Option<&str,>,
+) -> Result<Arc<dyn FormatReader,>, DataReaderError,> {
+    let registry = registry_snapshot();
+    if let Some(file_type_str,) = file_type_override {
+        return registry.by_name(file_type_str,).ok_or_else(|| {
+            DataReaderError::UnsupportedFileFormat(format!(
+                "Unsupported file type override: {}",
+                file_type_str
+            ),)
+        },);
+    }
+    source_path
+        .extension()
+        .and_then(|s| s.to_str(),)
+        .and_then(|ext| registry.by_extension(ext,),)
+        .or_else(|| registry.by_magic(&data[..data.len().min(MAGIC_HEADER_LEN,)],),)
+        .ok_or_else(|| {
+            DataReaderError::UnsupportedFileFormat(format!(
+                "Could not determine the format of {}; use --file-type",
+                source_path.display()
+            ),)
+        },)
+}
+
+/// Picks the reader of a source that can only be read once from its first bytes, which are read
+/// ahead and replayed in front of the rest of the input.
+pub(crate) fn reader_for_input(
+    mut input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    file_type_override: Option<&str,>,
+) -> Result<(Arc<dyn FormatReader,>, Box<dyn Read + Send,>,), DataReaderError,> {
+    let mut header = Vec::with_capacity(MAGIC_HEADER_LEN,);
+    (&mut input)
+        .take(MAGIC_HEADER_LEN as u64,)
+        .read_to_end(&mut header,)
+        .map_err(|e| read_error(source_path, e,),)?;
+    let reader = reader_for_bytes(&header, source_path, file_type_override,)?;
+    Ok((reader, Box::new(Cursor::new(header,).chain(input,),),),)
+}
+
+/// Synchronous body of `read_bytes_content`.
+pub fn read_bytes_content_blocking(
+    data: Bytes,
+    source_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let bytes = data.len() as u64;
+    traced_result(source_path, move || bytes, || read_bytes_untraced(data, source_path, options,),)
+}
+
+fn read_bytes_untraced(
+    data: Bytes,
+    source_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let reader = reader_for_bytes(&data, source_path, options.file_type_override.as_deref(),)?;
+
+    let budget = options.read_config.budget(source_path,);
+    budget.check()?;
+    budget.check_bytes(data.len() as u64,)?;
+
+    let config = &options.read_config;
+    let metadata = FileMetadata {
+        size:       data.len() as u64,
+        line_count: None,
     };
+    let source = ReadSource::Bytes(data, source_path.to_path_buf(),);
+    if options.output_mode != OutputMode::Stream {
+        let result = reader.read_data(source, options.head, config,)?;
+        budget.check()?;
+        return Ok(result,);
+    }
 
-    for entry in walker {
-        let entry = entry.map_err(|e| {
-            DataReaderError::InternalError(format!("Error walking directory: {}", e),)
-        },)?;
-        let path = entry.path();
+    let stream = reader.read_stream(source, config,)?.ok_or_else(|| {
+        DataReaderError::UnsupportedFileFormat(format!(
+            "Format {} does not support record streaming",
+            reader.name()
+        ),)
+    },)?;
+    let stream =
+        apply_error_policy(stream, source_path, config.error_policy, config.rejects.clone(),);
+    let stream = apply_limits(stream, budget, json_depth,);
+    let stream = config.filter.apply(stream,);
+    Ok(DataReaderResult::Stream(stream, metadata,),)
+}
 
-        if !path.is_file() {
-            continue;
-        }
+/// Reads a source that can only be read once, front to back, such as stdin. In
+/// `OutputMode::Stream`, CSV, JSON and XML records are parsed as the input arrives, in constant
+/// memory (`FormatReader::read_stream` with a `ReadSource::Reader`); Parquet reads the whole
+/// input first, and so does every other output mode, as `read_bytes_content` does.
+pub async fn read_reader_content(
+    input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let path = source_path.to_path_buf();
+    run_blocking(move || read_reader_content_blocking(input, &path, options,),).await
+}
 
-        let canonical_path = std::fs::canonicalize(path,).map_err(|e| {
-            DataReaderError::InternalError(format!(
-                "Error canonicalizing path {}: {}",
-                path.display(),
-                e
+/// Synchronous body of `read_reader_content`.
+pub fn read_reader_content_blocking(
+    mut input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    if options.output_mode != OutputMode::Stream {
+        // Reading stops as soon as the input goes past `max_bytes`.
+        let mut data = Vec::new();
+        BudgetedReader::new(&mut input, options.read_config.budget(source_path,),)
+            .read_to_end(&mut data,)
+            .map_err(|e| read_error(source_path, e,),)?;
+        return read_bytes_content_blocking(Bytes::from(data,), source_path, options,);
+    }
+    // The input is counted by the budget as it is read.
+    let budget = options.read_config.budget(source_path,);
+    let counted = budget.clone();
+    traced_result(source_path, move || counted.bytes_read(), || {
+        read_reader_untraced(input, source_path, options, budget,)
+    },)
+}
+
+fn read_reader_untraced(
+    input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: FileReaderOptions,
+    budget: ReadBudget,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let config = &options.read_config;
+    // Bytes are only counted here; `apply_limits` fails the stream once they pass `max_bytes`.
+    let input = Box::new(BudgetedReader::counting(input, budget.clone(),),);
+    let (reader, input,) =
+        reader_for_input(input, source_path, options.file_type_override.as_deref(),)?;
+    budget.check()?;
+
+    let source = ReadSource::Reader(input, source_path.to_path_buf(),);
+    let stream = reader.read_stream(source, config,)?.ok_or_else(|| {
+        DataReaderError::UnsupportedFileFormat(format!(
+            "Format {} does not support record streaming",
+            reader.name()
+        ),)
+    },)?;
+    let stream =
+        apply_error_policy(stream, source_path, config.error_policy, config.rejects.clone(),);
+    let stream = apply_limits(stream, budget, json_depth,);
+    let stream = config.filter.apply(stream,);
+    // The size of the input is not known until it has been read.
+    let metadata = FileMetadata {
+        size:       0,
+        line_count: None,
+    };
+    Ok(DataReaderResult::Stream(stream, metadata,),)
+}
+
+/// Writes the records of a file as a Parquet or Arrow IPC file (`format`) to `writer`, with the
+/// error policy and limits of `options.read_config`. Record-based formats are read once and
+/// written with `write_records`; other tabular formats (spreadsheets, SQLite) are written from
+/// their record batches. Returns the number of rows written.
+pub fn write_file_to_columnar<W: Write + Send,>(
+    file_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let bytes = std::fs::metadata(file_path,).map_or(0, |m| m.len(),);
+    traced_read(file_path, move || bytes, || {
+        write_file_columnar_untraced(file_path, options, format, columnar, writer,)
+    },)
+}
+
+fn write_file_columnar_untraced<W: Write + Send,>(
+    file_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let file_format = format_for_file(file_path, options.file_type_override.as_deref(),)?;
+    let reader = reader_for_format(file_path, &file_format,)?;
+    let config = &options.read_config;
+    let budget = start_read(file_path, config,)?;
+
+    let Some(records,) = reader.read_typed_stream(ReadSource::file(file_path,), config,)? else {
+        // Record batches bypass the filter, so filtered reads need a record stream.
+        let batches = if config.filter.is_empty() {
+            reader.read_batches(ReadSource::file(file_path,), columnar.batch_size, config,)?
+        } else {
+            None
+        };
+        let (schema, batches,) = batches.ok_or_else(|| {
+            DataReaderError::UnsupportedFileFormat(format!(
+                "Format {} cannot be written as {}",
+                reader.name(),
+                format
             ),)
         },)?;
+        return write_batches(schema, batches, format, columnar, writer,);
+    };
+    let records =
+        apply_error_policy(records, file_path, config.error_policy, config.rejects.clone(),);
+    let records = config.filter.apply_typed(apply_limits(records, budget, nc_depth,),);
+    write_records(records, file_path, format, columnar, writer,)
+}
 
-        if let Some(output_p,) = &options.output_path
-            && canonical_path == *output_p
-        {
-            continue;
+/// `write_file_to_columnar` for in-memory data (for example stdin), read as described for
+/// `read_bytes_content`.
+pub fn write_bytes_to_columnar<W: Write + Send,>(
+    data: Bytes,
+    source_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let bytes = data.len() as u64;
+    traced_read(source_path, move || bytes, || {
+        write_bytes_columnar_untraced(data, source_path, options, format, columnar, writer,)
+    },)
+}
+
+fn write_bytes_columnar_untraced<W: Write + Send,>(
+    data: Bytes,
+    source_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let reader = reader_for_bytes(&data, source_path, options.file_type_override.as_deref(),)?;
+    let config = &options.read_config;
+    let budget = config.budget(source_path,);
+    budget.check()?;
+    budget.check_bytes(data.len() as u64,)?;
+
+    let source = ReadSource::Bytes(data, source_path.to_path_buf(),);
+    let records = reader.read_typed_stream(source, config,)?.ok_or_else(|| {
+        DataReaderError::UnsupportedFileFormat(format!(
+            "Format {} does not support record streaming",
+            reader.name()
+        ),)
+    },)?;
+    let records =
+        apply_error_policy(records, source_path, config.error_policy, config.rejects.clone(),);
+    let records = config.filter.apply_typed(apply_limits(records, budget, nc_depth,),);
+    write_records(records, source_path, format, columnar, writer,)
+}
+
+/// `write_file_to_columnar` for a source that can only be read once, such as stdin. The input
+/// counts towards `max_bytes` as it is read.
+pub fn write_reader_to_columnar<W: Write + Send,>(
+    input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+) -> Result<u64, DataReaderError,> {
+    let budget = options.read_config.budget(source_path,);
+    let counted = budget.clone();
+    traced_read(source_path, move || counted.bytes_read(), || {
+        write_reader_columnar_untraced(
+            input,
+            source_path,
+            options,
+            format,
+            columnar,
+            writer,
+            budget,
+        )
+    },)
+}
+
+fn write_reader_columnar_untraced<W: Write + Send,>(
+    input: Box<dyn Read + Send,>,
+    source_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    writer: W,
+    budget: ReadBudget,
+) -> Result<u64, DataReaderError,> {
+    let config = &options.read_config;
+    budget.check()?;
+    let input = Box::new(BudgetedReader::new(input, budget.clone(),),);
+    let (reader, input,) =
+        reader_for_input(input, source_path, options.file_type_override.as_deref(),)?;
+
+    let source = ReadSource::Reader(input, source_path.to_path_buf(),);
+    let records = reader.read_typed_stream(source, config,)?.ok_or_else(|| {
+        DataReaderError::UnsupportedFileFormat(format!(
+            "Format {} does not support record streaming",
+            reader.name()
+        ),)
+    },)?;
+    let records =
+        apply_error_policy(records, source_path, config.error_policy, config.rejects.clone(),);
+    let records = config.filter.apply_typed(apply_limits(records, budget, nc_depth,),);
+    write_records(records, source_path, format, columnar, writer,)
+}
+
+/// The `read_file` span of one read, carrying the path and, once the read ends, the bytes it
+/// read. `finish` logs how long the read took: at `info` when it succeeded and at `warn` when it
+/// failed.
+struct ReadTrace {
+    span:    Span,
+    started: Instant,
+    bytes:   Box<dyn Fn() -> u64 + Send,>,
+}
+
+impl ReadTrace {
+    fn new(path: &Path, bytes: impl Fn() -> u64 + Send + 'static,) -> Self {
+        ReadTrace {
+            span:    info_span!("read_file", path = %path.display(), bytes = field::Empty),
+            started: Instant::now(),
+            bytes:   Box::new(bytes,),
         }
+    }
 
-        if path
-            .file_name()
-            .is_some_and(|name| name.to_string_lossy().starts_with('.',),)
-        {
-            continue;
+    fn finish(&self, records: Option<u64,>, error: Option<&dyn fmt::Display,>,) {
+        let _entered = self.span.enter();
+        self.span.record("bytes", (self.bytes)(),);
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        match error {
+            None => info!(elapsed_ms, records, "Read finished"),
+            Some(e,) => warn!(elapsed_ms, records, error = %e, "Read failed"),
         }
+    }
+}
 
-        let skip_file = if let Some(ext_filters,) = &options.filter_exts {
-            match path.extension().and_then(|s| s.to_str(),) {
-                Some(ext,) => !ext_filters
-                    .iter()
-                    .any(|f| f.to_lowercase() == ext.to_lowercase(),),
-                None => true,
-            }
-        } else {
-            false
-        };
+/// Runs one read inside its `read_file` span and logs it when it returns. `bytes` gives the
+/// bytes read once the read is over.
+fn traced_read<T,>(
+    path: &Path,
+    bytes: impl Fn() -> u64 + Send + 'static,
+    read: impl FnOnce() -> Result<T, DataReaderError,>,
+) -> Result<T, DataReaderError,> {
+    let trace = ReadTrace::new(path, bytes,);
+    let result = trace.span.in_scope(read,);
+    trace.finish(None, result.as_ref().err().map(|e| e as &dyn fmt::Display),);
+    result
+}
 
-        if skip_file {
-            continue;
+/// `traced_read` for reads that may return a record stream. The records of a stream are read
+/// inside the span, and the read is logged when the stream is exhausted or dropped.
+fn traced_result(
+    path: &Path,
+    bytes: impl Fn() -> u64 + Send + 'static,
+    read: impl FnOnce() -> Result<DataReaderResult, DataReaderError,>,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let trace = ReadTrace::new(path, bytes,);
+    match trace.span.in_scope(read,) {
+        Ok(DataReaderResult::Stream(stream, metadata,),) => {
+            let stream = TracedStream {
+                stream,
+                trace: Some(trace,),
+                records: 0,
+                error: None,
+            };
+            Ok(DataReaderResult::Stream(Box::new(stream,), metadata,),)
+        },
+        result => {
+            trace.finish(None, result.as_ref().err().map(|e| e as &dyn fmt::Display),);
+            result
+        },
+    }
+}
+
+/// A record stream whose read is logged when it ends: once the stream is exhausted, or when it
+/// is dropped before that. An error ends the read as failed.
+struct TracedStream {
+    stream:  RecordStream,
+    trace:   Option<ReadTrace,>,
+    records: u64,
+    error:   Option<String,>,
+}
+
+impl TracedStream {
+    fn finish(&mut self,) {
+        if let Some(trace,) = self.trace.take() {
+            let error = self.error.as_ref().map(|e| e as &dyn fmt::Display);
+            trace.finish(Some(self.records,), error,);
         }
+    }
+}
 
-        match read_file_content(path, options.clone(),).await {
-            Ok(result,) => results.push((path.to_path_buf(), result,),),
-            Err(e,) => {
-                error!("Error reading file {}: {}", path.display(), e);
-            },
+impl Iterator for TracedStream {
+    type Item = Result<serde_json::Value, DataReaderError,>;
+
+    fn next(&mut self,) -> Option<Self::Item,> {
+        let record = match &self.trace {
+            Some(trace,) => trace.span.in_scope(|| self.stream.next(),),
+            None => return None,
+        };
+        match &record {
+            Some(Ok(_,),) => self.records += 1,
+            Some(Err(e,),) => self.error = Some(e.to_string(),),
+            None => self.finish(),
         }
+        record
+    }
+}
+
+impl Drop for TracedStream {
+    fn drop(&mut self,) {
+        self.finish();
     }
+}
+
+pub async fn read_directory_content(
+    directory_path: &Path,
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let dir = directory_path.to_path_buf();
+    let walk_options = options.clone();
+    let paths = run_blocking(move || collect_directory_files(&dir, &walk_options,),).await?;
+    let results = read_paths(paths, options,).await;
     let dir_metadata =
         std::fs::metadata(directory_path,).map_err(|e| DataReaderError::FileReadError {
             path:   directory_path.to_path_buf(),
@@ -549,3 +946,209 @@ pub async fn read_directory_content(
         },
     ),)
 }
+
+/// Reads several inputs into one `DataReaderResult::DirectoryResults`, in the order given. Each
+/// input is a file, a directory (walked like `read_directory_content`) or a glob pattern such as
+/// `data/**/*.csv.gz`, expanded without a shell; see `expand_inputs`. The metadata size is the
+/// total size of the files read.
+pub async fn read_inputs_content(
+    inputs: &[PathBuf],
+    options: FileReaderOptions,
+) -> Result<DataReaderResult, DataReaderError,> {
+    let walk_inputs = inputs.to_vec();
+    let walk_options = options.clone();
+    let paths = run_blocking(move || expand_inputs(&walk_inputs, &walk_options,),).await?;
+    let size = paths.iter().map(|path| std::fs::metadata(path,).map_or(0, |m| m.len(),),).sum();
+    let results = read_paths(paths, options,).await;
+    Ok(DataReaderResult::DirectoryResults(
+        results,
+        FileMetadata {
+            size,
+            line_count: None,
+        },
+    ),)
+}
+
+/// Whether `input` is a glob pattern rather than a path: it contains `*`, `?` or `[`.
+pub fn is_glob_pattern(input: &Path,) -> bool {
+    input.to_string_lossy().contains(['*', '?', '[',],)
+}
+
+/// The files `inputs` name, without duplicates. Files are kept as given, even when missing, so
+/// their read fails in the results. Directories are walked like `read_directory_content`, and
+/// glob patterns (`*`, `?`, `[...]`, and `**` across directories) are expanded in sorted order.
+/// Files found through directories and patterns pass the hidden-file, output-path and extension
+/// filters. A pattern that matches no file is an error.
+pub fn expand_inputs(
+    inputs: &[PathBuf],
+    options: &FileReaderOptions,
+) -> Result<Vec<PathBuf,>, DataReaderError,> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if is_glob_pattern(input,) {
+            let pattern = input.to_string_lossy();
+            let match_options = glob::MatchOptions {
+                require_literal_leading_dot: true,
+                ..glob::MatchOptions::new()
+            };
+            let matches = glob::glob_with(&pattern, match_options,).map_err(|e| {
+                DataReaderError::InternalError(format!("Invalid glob pattern {}: {}", pattern, e),)
+            },)?;
+            let mut matched = false;
+            for entry in matches {
+                let path = entry.map_err(|e| DataReaderError::FileReadError {
+                    path:   e.path().to_path_buf(),
+                    source: std::io::Error::new(e.error().kind(), e.error().to_string(),),
+                },)?;
+                matched = true;
+                if path.is_dir() {
+                    paths.extend(collect_directory_files(&path, options,)?,);
+                } else if keeps_file(&path, options,)? {
+                    paths.push(path,);
+                }
+            }
+            if !matched {
+                return Err(DataReaderError::FileReadError {
+                    path:   input.clone(),
+                    source: std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        "no files match the pattern",
+                    ),
+                },);
+            }
+        } else if input.is_dir() {
+            paths.extend(collect_directory_files(input, options,)?,);
+        } else {
+            paths.push(input.clone(),);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    paths.retain(|path| seen.insert(path.clone(),),);
+    Ok(paths,)
+}
+
+/// Reads `paths`, up to `options.jobs` at once. A file that fails becomes a
+/// `DataReaderResult::Error` entry rather than failing the whole read. In `OutputMode::Stream`
+/// nothing is read here: each file becomes a `DataReaderResult::Pending` entry, which
+/// `write_streaming` opens once the files before it are written.
+async fn read_paths(
+    paths: Vec<PathBuf,>,
+    options: FileReaderOptions,
+) -> Vec<(PathBuf, DataReaderResult,),> {
+    if options.output_mode == OutputMode::Stream {
+        return paths
+            .into_iter()
+            .map(|path| {
+                let size = std::fs::metadata(&path,).map_or(0, |m| m.len(),);
+                let options = options.clone();
+                let read_path = path.clone();
+                let pending = PendingRead::new(move || {
+                    read_file_content_blocking(&read_path, options,)
+                        .unwrap_or_else(|e| error_entry(&read_path, &e,),)
+                },);
+                let metadata = FileMetadata {
+                    size,
+                    line_count: None,
+                };
+                (path, DataReaderResult::Pending(pending, metadata,),)
+            },)
+            .collect();
+    }
+    // `buffered` yields the results in the order of `paths`, so the output does not depend on
+    // which file finishes first.
+    let jobs = options.jobs.max(1,);
+    stream::iter(paths,)
+        .map(|path| {
+            let options = options.clone();
+            async move {
+                let result = match read_file_content(&path, options,).await {
+                    Ok(result,) => result,
+                    Err(e,) => error_entry(&path, &e,),
+                };
+                (path, result,)
+            }
+        },)
+        .buffered(jobs,)
+        .collect()
+        .await
+}
+
+/// The directory entry of a file that could not be read.
+fn error_entry(path: &Path, error: &DataReaderError,) -> DataReaderResult {
+    error!("Error reading file {}: {}", path.display(), error);
+    let size = std::fs::metadata(path,).map_or(0, |m| m.len(),);
+    DataReaderResult::Error(
+        FileErrorData::from(error,),
+        FileMetadata {
+            size,
+            line_count: None,
+        },
+    )
+}
+
+/// Walks `directory_path` and returns the files that pass the hidden-file, output-path and
+/// extension filters, sorted by file name within each directory.
+fn collect_directory_files(
+    directory_path: &Path,
+    options: &FileReaderOptions,
+) -> Result<Vec<PathBuf,>, DataReaderError,> {
+    let mut paths = Vec::new();
+
+    let walker = if options.recursive {
+        WalkDir::new(directory_path,)
+    } else {
+        WalkDir::new(directory_path,).max_depth(1,)
+    }
+    .sort_by_file_name();
+
+    for entry in walker {
+        let entry = entry.map_err(|e| {
+            DataReaderError::InternalError(format!("Error walking directory: {}", e),)
+        },)?;
+        let path = entry.path();
+
+        if !path.is_file() {
+            continue;
+        }
+
+        if keeps_file(path, options,)? {
+            paths.push(path.to_path_buf(),);
+        }
+    }
+    Ok(paths,)
+}
+
+/// The hidden-file, output-path and extension filters of directory and pattern reads.
+fn keeps_file(path: &Path, options: &FileReaderOptions,) -> Result<bool, DataReaderError,> {
+    let canonical_path = std::fs::canonicalize(path,).map_err(|e| {
+        DataReaderError::InternalError(format!(
+            "Error canonicalizing path {}: {}",
+            path.display(),
+            e
+        ),)
+    },)?;
+
+    if let Some(output_p,) = &options.output_path
+        && canonical_path == *output_p
+    {
+        return Ok(false,);
+    }
+
+    if path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.',),)
+    {
+        return Ok(false,);
+    }
+
+    // Matching the end of the name lets multi-part extensions such as `csv.gz` filter too.
+    let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase(),);
+    Ok(match (&options.filter_exts, name,) {
+        (Some(ext_filters,), Some(name,),) => ext_filters.iter().any(|f| {
+            let ext = f.trim_start_matches('.',).to_lowercase();
+            name.strip_suffix(ext.as_str(),).is_some_and(|stem| stem.ends_with('.',),)
+        },),
+        (Some(_,), None,) => false,
+        (None, _,) => true,
+    },)
+}
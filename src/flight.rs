@@ -0,0 +1,330 @@
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_descriptor::DescriptorType;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaAsIpc, SchemaResult, Ticket,
+};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, info};
+use walkdir::WalkDir;
+
+use crate::error::DataReaderError;
+use crate::file_reader::{FileFormat, get_file_format, read_file_to_batches_with_config};
+use crate::reader::async_stream::{DEFAULT_CHANNEL_CAPACITY, blocking_iter_to_stream};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::RecordBatchStream;
+
+type FlightStream<T,> = Pin<Box<dyn Stream<Item = Result<T, Status,>,> + Send,>,>;
+
+/// Serves the tabular files under a root directory over Arrow Flight.
+///
+/// Every readable file is one flight, described by its path relative to the root. The ticket of
+/// the flight's single endpoint is the same relative path joined with `/`. Only Parquet flights
+/// carry their schema, read from the footer; inferring the schema of other formats reads the
+/// whole file, so it is left to `GetSchema`. Every file is read with the limits and reader
+/// settings of the service's `ReadConfig`, so one request cannot exhaust the server.
+#[derive(Clone,)]
+pub struct FileFlightService {
+    root:       PathBuf,
+    batch_size: usize,
+    config:     ReadConfig,
+}
+
+impl FileFlightService {
+    pub fn new(root: &Path, batch_size: usize,) -> Result<Self, DataReaderError,> {
+        let root = std::fs::canonicalize(root,).map_err(|e| DataReaderError::FileReadError {
+            path:   root.to_path_buf(),
+            source: e,
+        },)?;
+        if !root.is_dir() {
+            return Err(DataReaderError::InternalError(format!(
+                "Flight root is not a directory: {}",
+                root.display()
+            ),),);
+        }
+        Ok(Self {
+            root,
+            batch_size: batch_size.max(1,),
+            config: ReadConfig::default(),
+        },)
+    }
+
+    /// Reads the files with the limits and reader settings of `config` instead of the defaults.
+    pub fn with_config(mut self, config: ReadConfig,) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Resolves a `/`-separated path relative to the root. Absolute paths and `..` segments are
+    /// rejected, and so are paths that symlinks lead outside the root.
+    fn resolve(&self, relative: &str,) -> Result<PathBuf, Status,> {
+        let relative_path = Path::new(relative,);
+        if relative.is_empty()
+            || relative_path
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_)),)
+        {
+            return Err(Status::invalid_argument(format!("Invalid flight path: {}", relative),),);
+        }
+        let not_found = || Status::not_found(format!("No such flight: {}", relative),);
+        let path =
+            std::fs::canonicalize(self.root.join(relative_path,),).map_err(|_| not_found(),)?;
+        if !path.starts_with(&self.root,) {
+            return Err(Status::permission_denied(format!(
+                "Flight path leads outside the root: {}",
+                relative
+            ),),);
+        }
+        if !path.is_file() {
+            return Err(not_found(),);
+        }
+        Ok(path,)
+    }
+
+    fn resolve_descriptor(&self, descriptor: &FlightDescriptor,) -> Result<PathBuf, Status,> {
+        if descriptor.r#type() != DescriptorType::Path {
+            return Err(Status::invalid_argument("Only path descriptors are supported",),);
+        }
+        self.resolve(&descriptor.path.join("/",),)
+    }
+
+    fn resolve_ticket(&self, ticket: &Ticket,) -> Result<PathBuf, Status,> {
+        let relative = std::str::from_utf8(&ticket.ticket,)
+            .map_err(|_| Status::invalid_argument("Ticket is not valid UTF-8",),)?;
+        self.resolve(relative,)
+    }
+
+    fn relative_segments(&self, path: &Path,) -> Vec<String,> {
+        path.strip_prefix(&self.root,)
+            .unwrap_or(path,)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned(),)
+            .collect()
+    }
+
+    /// Builds the `FlightInfo` of one file, with its schema if it is a Parquet file. Blocking:
+    /// reads the Parquet footer.
+    fn flight_info(&self, path: &Path,) -> Result<FlightInfo, Status,> {
+        let segments = self.relative_segments(path,);
+        let total_bytes = std::fs::metadata(path,).map_or(-1, |m| m.len() as i64,);
+
+        let mut info = FlightInfo::new();
+        if get_file_format(path,) == FileFormat::Parquet {
+            let (schema, _batches,) = open_batches(path, self.batch_size, &self.config,)?;
+            info = info.try_with_schema(&schema,).map_err(|e| Status::internal(e.to_string(),),)?;
+        }
+        let info = info
+            .with_descriptor(FlightDescriptor::new_path(segments.clone(),),)
+            .with_endpoint(FlightEndpoint::new().with_ticket(Ticket::new(segments.join("/",),),),)
+            .with_total_bytes(total_bytes,);
+        Ok(info,)
+    }
+
+    /// Lists the flight infos of all non-hidden tabular files under the root whose relative path
+    /// starts with `prefix`. Symlinks are not followed. Blocking.
+    fn list(&self, prefix: &str,) -> Vec<FlightInfo,> {
+        WalkDir::new(&self.root,)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.',)
+            },)
+            .filter_map(Result::ok,)
+            .filter(|entry| entry.file_type().is_file() && is_tabular(entry.path(),),)
+            .filter(|entry| self.relative_segments(entry.path(),).join("/",).starts_with(prefix,),)
+            .filter_map(|entry| match self.flight_info(entry.path(),) {
+                Ok(info,) => Some(info,),
+                Err(status,) => {
+                    debug!("Skipping {}: {}", entry.path().display(), status.message());
+                    None
+                },
+            },)
+            .collect()
+    }
+}
+
+/// Whether the file's format can be read as record batches. Formats added through
+/// `register_format_reader` are listed, and fail on `DoGet` if they are not tabular.
+fn is_tabular(path: &Path,) -> bool {
+    matches!(
+        get_file_format(path,),
+        FileFormat::Csv
+            | FileFormat::Json
+            | FileFormat::Xml
+            | FileFormat::Parquet
+            | FileFormat::Spreadsheet
+            | FileFormat::Sqlite
+            | FileFormat::Other(_,)
+    )
+}
+
+fn open_batches(
+    path: &Path,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), Status,> {
+    read_file_to_batches_with_config(path, get_file_format(path,), batch_size, config,)
+        .map_err(to_status,)
+}
+
+fn to_status(error: DataReaderError,) -> Status {
+    match error {
+        DataReaderError::FileReadError { .. } | DataReaderError::IsADirectory { .. } => {
+            Status::not_found(error.to_string(),)
+        },
+        DataReaderError::UnsupportedFileFormat(_,) => {
+            Status::failed_precondition(error.to_string(),)
+        },
+        DataReaderError::LimitExceeded { .. } => Status::resource_exhausted(error.to_string(),),
+        DataReaderError::Cancelled { .. } => Status::cancelled(error.to_string(),),
+        _ => Status::internal(error.to_string(),),
+    }
+}
+
+async fn run_blocking<T, F,>(work: F,) -> Result<T, Status,>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Status,> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work,)
+        .await
+        .map_err(|e| Status::internal(format!("Reader task failed: {}", e),),)?
+}
+
+#[tonic::async_trait]
+impl FlightService for FileFlightService {
+    type DoActionStream = FlightStream<arrow_flight::Result,>;
+    type DoExchangeStream = FlightStream<FlightData,>;
+    type DoGetStream = FlightStream<FlightData,>;
+    type DoPutStream = FlightStream<PutResult,>;
+    type HandshakeStream = FlightStream<HandshakeResponse,>;
+    type ListActionsStream = FlightStream<ActionType,>;
+    type ListFlightsStream = FlightStream<FlightInfo,>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest,>,>,
+    ) -> Result<Response<Self::HandshakeStream,>, Status,> {
+        Err(Status::unimplemented("Handshake is not supported",),)
+    }
+
+    /// Lists one flight per file. A UTF-8 criteria expression restricts the listing to paths
+    /// starting with it.
+    async fn list_flights(
+        &self,
+        request: Request<Criteria,>,
+    ) -> Result<Response<Self::ListFlightsStream,>, Status,> {
+        let prefix = String::from_utf8(request.into_inner().expression.to_vec(),)
+            .map_err(|_| Status::invalid_argument("Criteria expression is not valid UTF-8",),)?;
+        let service = self.clone();
+        let infos = run_blocking(move || Ok(service.list(&prefix,),),).await?;
+        Ok(Response::new(Box::pin(stream::iter(infos.into_iter().map(Ok,),),),),)
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor,>,
+    ) -> Result<Response<FlightInfo,>, Status,> {
+        let path = self.resolve_descriptor(&request.into_inner(),)?;
+        let service = self.clone();
+        let info = run_blocking(move || service.flight_info(&path,),).await?;
+        Ok(Response::new(info,),)
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor,>,
+    ) -> Result<Response<PollInfo,>, Status,> {
+        Err(Status::unimplemented("PollFlightInfo is not supported",),)
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor,>,
+    ) -> Result<Response<SchemaResult,>, Status,> {
+        let path = self.resolve_descriptor(&request.into_inner(),)?;
+        let batch_size = self.batch_size;
+        let config = self.config.clone();
+        let (schema, _batches,) =
+            run_blocking(move || open_batches(&path, batch_size, &config,),).await?;
+        let result: SchemaResult = SchemaAsIpc::new(&schema, &IpcWriteOptions::default(),)
+            .try_into()
+            .map_err(|e: arrow::error::ArrowError| Status::internal(e.to_string(),),)?;
+        Ok(Response::new(result,),)
+    }
+
+    /// Streams the file named by the ticket as Arrow batches. Decoding runs on the blocking pool
+    /// and is throttled by the client through a bounded channel.
+    async fn do_get(
+        &self,
+        request: Request<Ticket,>,
+    ) -> Result<Response<Self::DoGetStream,>, Status,> {
+        let path = self.resolve_ticket(&request.into_inner(),)?;
+        info!("Serving flight {}", path.display());
+        let batch_size = self.batch_size;
+        let config = self.config.clone();
+        let (schema, batches,) =
+            run_blocking(move || open_batches(&path, batch_size, &config,),).await?;
+
+        let batches = blocking_iter_to_stream(batches, DEFAULT_CHANNEL_CAPACITY,)
+            .map(|batch| batch.map_err(|e| FlightError::ExternalError(Box::new(e,),),),);
+        let flight_data = FlightDataEncoderBuilder::new()
+            .with_schema(schema,)
+            .build(batches,)
+            .map_err(Status::from,);
+        Ok(Response::new(Box::pin(flight_data,),),)
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData,>,>,
+    ) -> Result<Response<Self::DoPutStream,>, Status,> {
+        Err(Status::unimplemented("The file flight server is read-only",),)
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action,>,
+    ) -> Result<Response<Self::DoActionStream,>, Status,> {
+        Err(Status::unimplemented("No actions are supported",),)
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty,>,
+    ) -> Result<Response<Self::ListActionsStream,>, Status,> {
+        Ok(Response::new(Box::pin(stream::empty(),),),)
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData,>,>,
+    ) -> Result<Response<Self::DoExchangeStream,>, Status,> {
+        Err(Status::unimplemented("DoExchange is not supported",),)
+    }
+}
+
+/// Serves the files under `root` over Arrow Flight on `addr` until the process is stopped. Each
+/// file is read with the limits and reader settings of `config`.
+pub async fn serve_flight(
+    root: &Path,
+    addr: SocketAddr,
+    batch_size: usize,
+    config: ReadConfig,
+) -> Result<(), DataReaderError,> {
+    let service = FileFlightService::new(root, batch_size,)?.with_config(config,);
+    info!("Serving {} over Arrow Flight on {}", service.root.display(), addr);
+    tonic::transport::Server::builder()
+        .add_service(FlightServiceServer::new(service,),)
+        .serve(addr,)
+        .await
+        .map_err(|e| DataReaderError::InternalError(format!("Flight server error: {}", e),),)
+}
@@ -1,5 +1,14 @@
+pub mod columnar;
+pub mod config;
+pub mod convert;
+pub mod envelope;
 pub mod error;
 pub mod file_reader;
+pub mod flight;
+pub mod logging;
 pub mod nc_reader_result;
 pub mod output;
 pub mod reader;
+pub mod record;
+pub mod sink;
+pub mod writer;
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::error::DataReaderError;
+
+/// How diagnostics are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum,)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per event, with the fields of the enclosing spans.
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Where and how much the CLI logs. Diagnostics always go to stderr, and additionally to
+/// `file` when one is given; data output never goes through the logger.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct LogOptions {
+    /// Used unless `RUST_LOG` is set.
+    pub level:  LevelFilter,
+    pub format: LogFormat,
+    /// Appended to, never truncated.
+    pub file:   Option<PathBuf,>,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            level:  LevelFilter::WARN,
+            format: LogFormat::default(),
+            file:   None,
+        }
+    }
+}
+
+impl LogOptions {
+    /// The level for `-v`/`-q` counts: warnings by default, each `-v` one level more verbose
+    /// (`info`, `debug`, `trace`), each `-q` one level less (`error`, then nothing).
+    pub fn level_for(verbose: u8, quiet: u8,) -> LevelFilter {
+        match 2 + i16::from(verbose,) - i16::from(quiet,) {
+            ..=0 => LevelFilter::OFF,
+            1 => LevelFilter::ERROR,
+            2 => LevelFilter::WARN,
+            3 => LevelFilter::INFO,
+            4 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Installs the global tracing subscriber. Keep the returned guard alive until exit: dropping it
+/// flushes the log file.
+pub fn init_logging(options: &LogOptions,) -> Result<Option<WorkerGuard,>, DataReaderError,> {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::default().add_directive(options.level.into(),),);
+
+    let mut layers = vec![fmt_layer(options.format, std::io::stderr, true,)];
+    let guard = match &options.file {
+        Some(path,) => {
+            let file = OpenOptions::new().create(true,).append(true,).open(path,).map_err(|e| {
+                DataReaderError::InternalError(format!(
+                    "Failed to open log file {}: {}",
+                    path.display(),
+                    e
+                ),)
+            },)?;
+            let (writer, guard,) = tracing_appender::non_blocking(file,);
+            layers.push(fmt_layer(options.format, writer, false,),);
+            Some(guard,)
+        },
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(layers,)
+        .with(filter,)
+        .try_init()
+        .map_err(|e| DataReaderError::InternalError(format!("Failed to start logging: {}", e),),)?;
+    Ok(guard,)
+}
+
+fn fmt_layer<W,>(
+    format: LogFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Layer<Registry,> + Send + Sync,>
+where
+    W: for<'w> MakeWriter<'w,> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer,);
+    match format {
+        LogFormat::Text => layer.with_ansi(ansi,).boxed(),
+        LogFormat::Json => layer.json().with_current_span(true,).with_span_list(true,).boxed(),
+    }
+}
@@ -1,13 +1,25 @@
 use std::fs;
 use std::io::Write; // New import for writeln!
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::{ArgGroup, CommandFactory, Parser};
-use nc_reader::file_reader::FileReaderOptions;
+use clap::{ArgAction, ArgGroup, Args, CommandFactory, Parser, Subcommand};
+use nc_reader::columnar::{ColumnarCompression, ColumnarOptions};
+use nc_reader::config::{ConfigSources, LoadedSettings, Settings};
+use nc_reader::convert::{ConvertOptions, SchemaOverride};
+use nc_reader::file_reader::{FileReaderOptions, is_glob_pattern};
+use nc_reader::logging::{LogFormat, LogOptions, init_logging};
+use nc_reader::nc_reader_result::DataReaderResult;
 use nc_reader::output::{OutputFormat, OutputMode};
+use nc_reader::reader::coercion::ValueType;
+use nc_reader::reader::csv_dialect::parse_dialect_char;
+use nc_reader::reader::error_policy::{ErrorPolicy, RejectCollector};
+use nc_reader::reader::filter::{Predicate, Projection};
+use nc_reader::reader::record_batch::DEFAULT_BATCH_SIZE;
+use nc_reader::reader::type_inference::{ColumnHint, NumberLocale};
+use nc_reader::sink::{OutputSink, SinkCompression, SinkOptions};
 use tracing::{info, warn};
-use tracing_subscriber::prelude::*;
-use tracing_subscriber::{EnvFilter, fmt};
 
 #[derive(Parser, Debug,)]
 #[command(
@@ -25,10 +37,40 @@ use tracing_subscriber::{EnvFilter, fmt};
 
     Usage:
         nc_reader --file-path <FILE_PATH> [--format <FORMAT>] [--schema] [--head <LINES>] [--all] \
-                  [--file-type <TYPE>] [--output-path <PATH>] [--analyze]
+                  [--file-type <TYPE>] [--output-path <PATH>] [--analyze] [--stream] \
+                  [--error-policy <POLICY>] [--rejects-path <PATH>] [--max-bytes <BYTES>] \
+                  [--max-decompressed-bytes <BYTES>] [--max-records <N>] [--max-depth <N>] \
+                  [--timeout <SECONDS>] [--compression <CODEC>] [--row-group-size <ROWS>] \
+                  [--where <EXPR>] [--select <FIELDS>] [--output-compression <CODEC>] \
+                  [--atomic|--no-atomic] [--csv-delimiter <CHAR>] [--csv-quote <CHAR>] \
+                  [--no-header|--header] [--locale <LOCALE>] [--date-format <FORMAT>]... \
+                  [--column-format <COLUMN=HINT>]... [--sample-first <ROWS>] \
+                  [--sample-reservoir <ROWS>] [--null-value <TOKEN>]... [--true-value <WORD>]... \
+                  [--false-value <WORD>]... [--preserve-strings|--no-preserve-strings] \
+                  [--type <FIELD=TYPE>]...
         nc_reader --directory-path <DIRECTORY_PATH> [--format <FORMAT>] [--schema] [--head \
-                  <LINES>] [--all] [--file-type <TYPE>] [--recursive] [--filter-ext <EXT>] \
-                  [--output-path <PATH>] [--analyze]
+                  <LINES>] [--all] [--file-type <TYPE>] [--recursive|--no-recursive] \
+                  [--filter-ext <EXT>,...] [--output-path <PATH>] [--analyze] [--stream] \
+                  [--jobs <N>]
+        nc_reader --file-path <PATH|PATTERN>... [--directory-path <DIRECTORY_PATH>...] \
+                  [same options as --directory-path]
+        nc_reader convert <INPUT> <OUTPUT> [--format <FORMAT>] [--file-type <TYPE>] \
+                  [--schema <PATH>] [--encoding <LABEL>] [--compression <CODEC>] \
+                  [--output-compression <CODEC>] [--row-group-size <ROWS>] \
+                  [--error-policy <POLICY>] [--rejects-path <PATH>] \
+                  [--where <EXPR>] [--select <FIELDS>] [--atomic|--no-atomic] \
+                  [--csv-delimiter <CHAR>] [--csv-quote <CHAR>] [--no-header|--header] \
+                  [--locale <LOCALE>] [--date-format <FORMAT>]... \
+                  [--column-format <COLUMN=HINT>]... [--null-value <TOKEN>]... \
+                  [--true-value <WORD>]... [--false-value <WORD>]... \
+                  [--preserve-strings|--no-preserve-strings] [--type <FIELD=TYPE>]... \
+                  [--max-bytes <BYTES>] [--max-decompressed-bytes <BYTES>] [--max-records <N>] \
+                  [--max-depth <N>] [--timeout <SECONDS>]
+        nc_reader serve-flight [--root <DIRECTORY_PATH>] [--addr <HOST:PORT>]
+        nc_reader config show [--profile <NAME>]
+
+    Every command also takes --profile <NAME> to apply a profile from the configuration files,
+    -v/-q to log more or less, --log-file <PATH> and --log-format <text|json>.
 
     Examples:
         # Read a CSV file and output its schema in JSON format
@@ -44,31 +86,91 @@ use tracing_subscriber::{EnvFilter, fmt};
         nc_reader --directory-path my_nc_dir --recursive --filter-ext csv --format yaml \
                   --output-path output.yaml
 
+        # Read every gzipped CSV file under data, plus one JSON file, as one set of results
+        nc_reader --file-path 'data/**/*.csv.gz' extra.json --format json
+
+        # Read the CSV and JSON files of two directories
+        nc_reader --directory-path exports archive --filter-ext csv,json
+
+        # Read a directory of Parquet files, eight at a time
+        nc_reader --directory-path warehouse --recursive --filter-ext parquet --jobs 8
+
+        # Persist a JSONL file as Parquet with zstd compression (the format follows the extension)
+        nc_reader --file-path events.jsonl --output-path events.parquet --compression zstd
+
+        # Convert a JSON file's records into flattened CSV rows
+        nc_reader --file-path events.json --format csv --output-path events.csv
+
+        # Convert a Latin-1 CSV file to gzipped NDJSON, forcing zip codes to stay strings
+        nc_reader convert customers.csv customers.ndjson.gz --encoding latin1 --schema types.json
+
         # Read a file, explicitly treating it as a JSON file regardless of extension
         nc_reader --file-path my_data.txt --file-type json
 
+        # Summarize a CSV file, skipping malformed rows and listing them in rejects.jsonl
+        nc_reader --file-path data.csv --error-policy skip --rejects-path rejects.jsonl
+
+        # Read an untrusted upload with at most 100 MB of input and 30 seconds of work
+        nc_reader --file-path upload.json --max-bytes 100000000 --max-depth 64 --timeout 30
+
+        # Stream a large JSONL file as NDJSON, one record per line, in constant memory
+        nc_reader --file-path events.jsonl --stream --format json > events.ndjson
+
+        # Stream a JSONL file into a zstd-compressed NDJSON file, renamed into place when done
+        nc_reader --file-path events.jsonl --stream --output-path events.ndjson.zst --atomic
+
+        # Stream the active adult users' ids and nested names, without piping through jq
+        nc_reader --file-path users.jsonl --stream --where 'active and age >= 18' \
+                  --select 'id, profile.name as name'
+
+        # Read a semicolon-separated export without a header row (detected when not given)
+        nc_reader --file-path export.csv --csv-delimiter ';' --no-header --format json
+
+        # Read German amounts and day-first dates in an otherwise guessed export
+        nc_reader --file-path umsatz.csv --column-format betrag=de --column-format datum=%d.%m.%Y
+
+        # Read CSV piped from another program
+        cat data.csv | nc_reader --file-path - --file-type csv --format json
+
         # Analyze a Parquet file for column statistics
         nc_reader --file-path data.parquet --analyze --format json
+
+        # Serve every tabular file under ./data to Arrow Flight clients (pyarrow, DuckDB)
+        nc_reader serve-flight --root data --addr 0.0.0.0:50051
+
+        # Read with the limits of the `untrusted` profile from nc_reader.yml or cli.yml
+        nc_reader --profile untrusted --file-path upload.json
+
+        # Log each file read with its size and timing as JSON lines
+        nc_reader --directory-path data -v --log-format json --log-file reads.jsonl
+
+        # Show the settings the configuration files and NC_READER_* variables produce
+        nc_reader config show
         
     "
 )]
 #[clap(group(
     ArgGroup::new("input_source")
         .required(true)
+        .multiple(true)
         .args(&["file_path", "directory_path"]),
 ))]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// Path to the file to read
-    #[arg(long, group = "input_source")]
-    file_path: Option<PathBuf,>,
+    #[command(subcommand)]
+    command: Option<Command,>,
 
-    /// Path to the directory to read
-    #[arg(long, group = "input_source")]
-    directory_path: Option<PathBuf,>,
+    /// Files to read, "-" for stdin, or glob patterns such as 'data/**/*.csv.gz'; repeatable
+    #[arg(long, group = "input_source", value_name = "PATH", num_args = 1..)]
+    file_path: Vec<PathBuf,>,
 
-    /// Output format
-    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
-    format: OutputFormat,
+    /// Directories to read; repeatable
+    #[arg(long, group = "input_source", value_name = "PATH", num_args = 1..)]
+    directory_path: Vec<PathBuf,>,
+
+    /// Output format [default: text]; inferred from the --output-path extension when not given
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat,>,
 
     /// Display only schema or structural information
     #[arg(long)]
@@ -79,69 +181,515 @@ struct Cli {
     head: Option<usize,>,
 
     /// Output full raw content (disables analytical summary)
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["schema", "analyze", "stream"])]
     all: bool,
 
-    /// Explicitly set the file type (e.g., csv, json, parquet, etc.)
-    #[arg(long, value_name = "TYPE")]
-    file_type: Option<String,>,
+    /// Output column statistics (null counts, distinct counts) where the format supports them
+    #[arg(long, conflicts_with_all = ["schema", "stream"])]
+    analyze: bool,
+
+    /// Write each record as soon as it is read: NDJSON for json and text, YAML documents for yaml
+    #[arg(long, conflicts_with = "schema")]
+    stream: bool,
 
     /// Recursively read files in subdirectories
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_recursive")]
     recursive: bool,
 
-    /// Filter files by extension when reading a directory (e.g., "csv", "json")
-    #[arg(long, value_name = "EXT")]
-    filter_ext: Option<String,>,
+    /// Read only the top level of directories, overriding a configuration file
+    #[arg(long, overrides_with = "recursive")]
+    no_recursive: bool,
+
+    /// Read only files with these extensions from directories and patterns (e.g. "csv,json.gz")
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    filter_ext: Vec<String,>,
 
     /// Path to write the output to instead of stdout
     #[arg(long, value_name = "PATH")]
     output_path: Option<PathBuf,>,
+
+    /// Number of files to read concurrently when reading a directory [default: 1]
+    #[arg(long, value_name = "N")]
+    jobs: Option<usize,>,
+
+    /// Rows a CSV summary keeps from the start of the file [default: 1000]
+    #[arg(long, value_name = "ROWS")]
+    sample_first: Option<usize,>,
+
+    /// Rows a CSV summary picks at random from the rest of the file [default: 1000]
+    #[arg(long, value_name = "ROWS")]
+    sample_reservoir: Option<usize,>,
+
+    #[command(flatten)]
+    read: ReadArgs,
+
+    /// Apply this profile from the configuration files
+    #[arg(long, global = true, value_name = "NAME", env = "NC_READER_PROFILE")]
+    profile: Option<String,>,
+
+    /// Log more: -v for info, -vv for debug, -vvv for trace (default: warnings)
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Log less: -q for errors only, -qq for nothing
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    quiet: u8,
+
+    /// Also append diagnostics to this file (default: stderr only)
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf,>,
+
+    /// Diagnostics as human-readable text or one JSON object per line
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    log_format: Option<LogFormat,>,
+}
+
+impl Cli {
+    /// The settings given as flags, the top layer over the configuration files.
+    fn settings(&self,) -> Settings {
+        Settings {
+            format: self.format.map(|format| format.to_string(),),
+            head: self.head,
+            recursive: flag(self.recursive, self.no_recursive,),
+            filter_ext: (!self.filter_ext.is_empty()).then(|| self.filter_ext.join(",",),),
+            jobs: self.jobs,
+            sample_first: self.sample_first,
+            sample_reservoir: self.sample_reservoir,
+            log_file: self.log_file.clone(),
+            log_format: self.log_format.map(|format| format.to_string(),),
+            ..self.read.settings()
+        }
+    }
+}
+
+/// Flags shared by reads and `convert`: how the input is read and how the output file is
+/// written.
+#[derive(Args, Debug,)]
+struct ReadArgs {
+    /// Explicitly set the input file type (e.g., csv, json, parquet, etc.)
+    #[arg(long, value_name = "TYPE")]
+    file_type: Option<String,>,
+
+    /// What to do with malformed records: fail-fast (default), skip, or skip-with-limit:N
+    #[arg(long, value_name = "POLICY")]
+    error_policy: Option<ErrorPolicy,>,
+
+    /// Write skipped records to this file, one JSON object per line
+    #[arg(long, value_name = "PATH")]
+    rejects_path: Option<PathBuf,>,
+
+    /// Keep only records matching this expression, e.g. 'age >= 18 and name =~ "^A"'
+    #[arg(long = "where", value_name = "EXPR")]
+    where_clause: Option<Predicate,>,
+
+    /// Keep only these fields, e.g. 'id, user.name as name, tags[0]'
+    #[arg(long, value_name = "FIELDS")]
+    select: Option<Projection,>,
+
+    /// CSV field delimiter, e.g. ";" or "tab" (default: detected from the start of the file)
+    #[arg(long, value_name = "CHAR", value_parser = parse_dialect_char)]
+    csv_delimiter: Option<u8,>,
+
+    /// CSV quote character (default: detected from the start of the file)
+    #[arg(long, value_name = "CHAR", value_parser = parse_dialect_char)]
+    csv_quote: Option<u8,>,
+
+    /// Read the first CSV row as data; columns are named column_1, column_2, ...
+    #[arg(long, overrides_with = "header")]
+    no_header: bool,
+
+    /// Read the first CSV row as column names, overriding a configuration file
+    #[arg(long, overrides_with = "no_header")]
+    header: bool,
+
+    /// Number separators of CSV fields: en (1,234.56), de (1.234,56), fr or ch [default: guessed]
+    #[arg(long, value_name = "LOCALE")]
+    locale: Option<NumberLocale,>,
+
+    /// Date format tried before the built-in ones, e.g. '%d %b %Y'; repeatable
+    #[arg(long, value_name = "FORMAT")]
+    date_format: Vec<String,>,
+
+    /// Locale or date format of one CSV column, e.g. price=de or day=%d.%m.%Y; repeatable
+    #[arg(long, value_name = "COLUMN=HINT", value_parser = parse_column_format)]
+    column_format: Vec<(String, ColumnHint,),>,
+
+    /// Text read as null in CSV, XML and spreadsheet values, e.g. NA or '\N'; repeatable
+    #[arg(long, value_name = "TOKEN", allow_hyphen_values = true)]
+    null_value: Vec<String,>,
+
+    /// Word read as true, e.g. Y or 1; repeatable, replaces the default true and yes
+    #[arg(long, value_name = "WORD")]
+    true_value: Vec<String,>,
+
+    /// Word read as false, e.g. N or 0; repeatable, replaces the default false and no
+    #[arg(long, value_name = "WORD")]
+    false_value: Vec<String,>,
+
+    /// Keep CSV and XML values as text instead of reading numbers and booleans
+    #[arg(long, overrides_with = "no_preserve_strings")]
+    preserve_strings: bool,
+
+    /// Read numbers and booleans in CSV and XML values, overriding a configuration file
+    #[arg(long, overrides_with = "preserve_strings")]
+    no_preserve_strings: bool,
+
+    /// Read a field as string, integer, float, decimal or boolean, e.g. zip=string or @id=string
+    /// (XML attribute); repeatable
+    #[arg(long = "type", value_name = "FIELD=TYPE", value_parser = parse_field_type)]
+    field_type: Vec<(String, ValueType,),>,
+
+    /// Compression codec for parquet output (default snappy); arrow-ipc output is uncompressed.
+    /// For the text formats of convert, gzip or zstd compresses the whole output
+    #[arg(long, value_enum, value_name = "CODEC")]
+    compression: Option<ColumnarCompression,>,
+
+    /// Maximum rows per Parquet row group
+    #[arg(long, value_name = "ROWS")]
+    row_group_size: Option<usize,>,
+
+    /// Compress the whole output (default: from a .gz or .zst output path, else none)
+    #[arg(long, value_enum, value_name = "CODEC")]
+    output_compression: Option<SinkCompression,>,
+
+    /// Write the output file through a temporary file renamed into place when complete
+    #[arg(long, overrides_with = "no_atomic")]
+    atomic: bool,
+
+    /// Write the output file in place, overriding a configuration file
+    #[arg(long, overrides_with = "atomic")]
+    no_atomic: bool,
+
+    /// Fail files (or stdin input) larger than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    max_bytes: Option<u64,>,
+
+    /// Fail GZIP files that decompress to more than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    max_decompressed_bytes: Option<u64,>,
+
+    /// Fail files with more than this many records
+    #[arg(long, value_name = "N")]
+    max_records: Option<u64,>,
+
+    /// Fail records nested deeper than this many levels
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize,>,
+
+    /// Fail files that take longer than this many seconds to read
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64,>,
+}
+
+impl ReadArgs {
+    /// The settings given as flags, the top layer over the configuration files.
+    fn settings(&self,) -> Settings {
+        Settings {
+            file_type: self.file_type.clone(),
+            error_policy: self.error_policy.map(|policy| policy.to_string(),),
+            rejects_path: self.rejects_path.clone(),
+            compression: self.compression.map(|codec| codec.to_string(),),
+            row_group_size: self.row_group_size,
+            output_compression: self.output_compression.map(|codec| codec.to_string(),),
+            atomic: flag(self.atomic, self.no_atomic,),
+            max_bytes: self.max_bytes,
+            max_decompressed_bytes: self.max_decompressed_bytes,
+            max_records: self.max_records,
+            max_depth: self.max_depth,
+            timeout: self.timeout,
+            where_clause: self.where_clause.as_ref().map(ToString::to_string,),
+            select: self.select.as_ref().map(ToString::to_string,),
+            csv_delimiter: self.csv_delimiter.map(|c| char::from(c,).to_string(),),
+            csv_quote: self.csv_quote.map(|c| char::from(c,).to_string(),),
+            no_header: flag(self.no_header, self.header,),
+            locale: self.locale.map(|locale| locale.to_string(),),
+            date_formats: (!self.date_format.is_empty()).then(|| self.date_format.clone(),),
+            column_formats: (!self.column_format.is_empty()).then(|| {
+                self.column_format
+                    .iter()
+                    .map(|(column, hint,)| (column.clone(), hint.to_string(),),)
+                    .collect()
+            },),
+            null_values: (!self.null_value.is_empty()).then(|| self.null_value.clone(),),
+            true_values: (!self.true_value.is_empty()).then(|| self.true_value.clone(),),
+            false_values: (!self.false_value.is_empty()).then(|| self.false_value.clone(),),
+            preserve_strings: flag(self.preserve_strings, self.no_preserve_strings,),
+            types: (!self.field_type.is_empty()).then(|| {
+                self.field_type
+                    .iter()
+                    .map(|(field, value_type,)| (field.clone(), value_type.to_string(),),)
+                    .collect()
+            },),
+            ..Settings::default()
+        }
+    }
+}
+
+#[derive(Subcommand, Debug,)]
+enum Command {
+    /// Inspect the layered configuration
+    #[command(subcommand)]
+    Config(ConfigCommand,),
+    /// Convert one input to another format, streaming records from the reader to the writer
+    Convert(ConvertArgs,),
+    /// Serve the files under a directory as Arrow Flight flights
+    ServeFlight(ServeFlightArgs,),
+}
+
+#[derive(Subcommand, Debug,)]
+enum ConfigCommand {
+    /// Print the settings from the configuration files and environment, and where they came from
+    Show,
+}
+
+#[derive(Args, Debug,)]
+struct ConvertArgs {
+    /// Input file, or "-" to read from stdin
+    input: PathBuf,
+
+    /// Output file, or "-" to write to stdout
+    output: PathBuf,
+
+    /// Output format; inferred from the output extension (before any .gz or .zst) when not given
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat,>,
+
+    /// JSON file mapping column names to the types of --type: string, integer, float, decimal
+    /// or boolean
+    #[arg(long, value_name = "PATH")]
+    schema: Option<PathBuf,>,
+
+    /// Character encoding of text input (e.g., utf-16le, latin1, shift_jis); detected if not given
+    #[arg(long, value_name = "LABEL")]
+    encoding: Option<String,>,
+
+    #[command(flatten)]
+    read: ReadArgs,
 }
 
-// Helper function to write output
-fn write_output(
-    formatted_output: &str,
+impl ConvertArgs {
+    /// The settings given as flags, the top layer over the configuration files.
+    fn settings(&self,) -> Settings {
+        Settings {
+            format: self.format.map(|format| format.to_string(),),
+            ..self.read.settings()
+        }
+    }
+}
+
+#[derive(Args, Debug,)]
+struct ServeFlightArgs {
+    /// Root directory whose files are exposed as flights
+    #[arg(long, value_name = "DIRECTORY_PATH", default_value = ".")]
+    root: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, value_name = "HOST:PORT", default_value = "127.0.0.1:50051")]
+    addr: SocketAddr,
+
+    /// Maximum number of rows per Arrow batch [default: 8192]
+    #[arg(long, value_name = "ROWS")]
+    batch_size: Option<usize,>,
+}
+
+/// The setting of a flag and its `--no-` form: `None` when neither is given, so the
+/// configuration files decide.
+fn flag(set: bool, unset: bool,) -> Option<bool,> {
+    match (set, unset,) {
+        (true, _,) => Some(true,),
+        (false, true,) => Some(false,),
+        (false, false,) => None,
+    }
+}
+
+/// Parses a `--column-format` value: `COLUMN=HINT`.
+fn parse_column_format(value: &str,) -> Result<(String, ColumnHint,), String,> {
+    let (column, hint,) = value
+        .split_once('=',)
+        .ok_or_else(|| format!("expected COLUMN=HINT, got {:?}", value),)?;
+    Ok((column.to_string(), hint.parse()?,),)
+}
+
+fn parse_field_type(value: &str,) -> Result<(String, ValueType,), String,> {
+    let (field, value_type,) = value
+        .split_once('=',)
+        .ok_or_else(|| format!("expected FIELD=TYPE, got {:?}", value),)?;
+    Ok((field.to_string(), value_type.parse()?,),)
+}
+
+/// Writes a read result to `output_path` or stdout. In `OutputMode::Stream` records are
+/// written as they are read; otherwise the whole result is formatted first.
+fn write_result(
+    result: DataReaderResult,
+    output_mode: OutputMode,
+    output_format: OutputFormat,
     output_path: Option<&Path,>,
+    sink_options: SinkOptions,
 ) -> Result<(), Box<dyn std::error::Error,>,> {
-    if let Some(path,) = output_path {
-        let mut file = std::fs::File::create(path,).map_err(|e| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Failed to create output file {}: {}", path.display(), e),
-            ),) as Box<dyn std::error::Error,>
-        },)?;
-        writeln!(file, "{}", formatted_output).map_err(|e| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Failed to write to output file {}: {}", path.display(), e),
-            ),) as Box<dyn std::error::Error,>
-        },)?;
+    let mut sink = OutputSink::open(output_path, sink_options,)?;
+    if output_mode == OutputMode::Stream {
+        result.write_streaming(output_format, &mut sink,)?;
+    } else {
+        let formatted_output = result.to_string_formatted(output_format,);
+        if let Err(e,) = writeln!(sink, "{}", formatted_output)
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(Box::new(e,),);
+        }
+    }
+    sink.finish()?;
+    Ok((),)
+}
+
+/// Writes one input (a file, or stdin for "-") as a Parquet or Arrow IPC file to `output_path`
+/// or stdout.
+fn write_columnar(
+    file_path: &Path,
+    options: &FileReaderOptions,
+    format: OutputFormat,
+    columnar: &ColumnarOptions,
+    output_path: Option<&Path,>,
+    sink_options: SinkOptions,
+) -> Result<(), Box<dyn std::error::Error,>,> {
+    let mut sink = OutputSink::open(output_path, sink_options,)?;
+    let rows = if file_path.as_os_str() == "-" {
+        nc_reader::file_reader::write_reader_to_columnar(
+            Box::new(std::io::stdin(),),
+            file_path,
+            options,
+            format,
+            columnar,
+            &mut sink,
+        )?
+    } else {
+        nc_reader::file_reader::write_file_to_columnar(
+            file_path, options, format, columnar, &mut sink,
+        )?
+    };
+    sink.finish()?;
+    info!("Wrote {} rows as {}", rows, format);
+    Ok((),)
+}
+
+/// Runs `nc_reader convert` and prints its summary to stderr, keeping stdout for the output.
+fn convert(args: ConvertArgs, config: Settings,) -> Result<(), Box<dyn std::error::Error,>,> {
+    let settings = config.merge(args.settings(),);
+    let format_path = SinkCompression::strip_extension(&args.output,);
+    let format = match args.format.or_else(|| OutputFormat::from_extension(&format_path,),) {
+        Some(format,) => format,
+        None => settings.output_format()?.ok_or_else(|| {
+            format!("Cannot infer the output format of {}; use --format", args.output.display())
+        },)?,
+    };
+    let encoding = match args.encoding.as_deref() {
+        Some(label,) => Some(
+            encoding_rs::Encoding::for_label(label.as_bytes(),)
+                .ok_or_else(|| format!("Unknown encoding: {}", label),)?,
+        ),
+        None => None,
+    };
+    let mut columnar = settings.columnar_options()?;
+    let mut sink_options = settings.sink_options()?;
+    // Text formats are compressed as a whole by the sink; `--compression` names its codec.
+    if !format.is_columnar()
+        && let Some(codec,) = columnar.compression.take()
+        && sink_options.compression.is_none()
+    {
+        sink_options.compression = Some(sink_compression(codec,)?,);
+    }
+    let options = ConvertOptions {
+        file_type_override: settings.file_type.clone(),
+        format,
+        read_config: settings.read_config()?,
+        encoding,
+        schema: args.schema.as_deref().map(SchemaOverride::from_file,).transpose()?,
+        columnar,
+    };
+
+    let mut sink = OutputSink::open(Some(&args.output,), sink_options,)?;
+    open_rejects(&options.read_config.rejects, settings.rejects_path.as_deref(),)?;
+
+    let summary = if args.input.as_os_str() == "-" {
+        let stdin = Box::new(std::io::stdin(),);
+        nc_reader::convert::convert_reader(stdin, &args.input, &options, &mut sink,)?
     } else {
-        info!("{}", formatted_output);
+        nc_reader::convert::convert_file(&args.input, &options, &mut sink,)?
+    };
+    sink.finish()?;
+    eprintln!("{}", summary);
+    write_rejects(&options.read_config.rejects, settings.rejects_path.as_deref(),)
+}
+
+/// The sink compression that `--compression` names for text output.
+fn sink_compression(codec: ColumnarCompression,) -> Result<SinkCompression, String,> {
+    match codec {
+        ColumnarCompression::None => Ok(SinkCompression::None,),
+        ColumnarCompression::Gzip => Ok(SinkCompression::Gzip,),
+        ColumnarCompression::Zstd => Ok(SinkCompression::Zstd,),
+        other => Err(format!("Text output cannot be compressed with {}; use gzip or zstd", other),),
+    }
+}
+
+/// Prints the merged configuration, after the files it came from.
+fn show_config(loaded: &LoadedSettings,) -> Result<(), Box<dyn std::error::Error,>,> {
+    let mut out = std::io::stdout().lock();
+    if loaded.sources.is_empty() {
+        writeln!(out, "# Sources: none (built-in defaults)")?;
+    } else {
+        writeln!(out, "# Sources: {}", loaded.sources.join(", "))?;
+    }
+    write!(out, "{}", serde_yaml::to_string(&loaded.settings,)?)?;
+    Ok((),)
+}
+
+/// Creates `rejects_path`, if given, and makes `rejects` write each skipped record to it as a
+/// JSON line as soon as it is skipped.
+fn open_rejects(
+    rejects: &RejectCollector,
+    rejects_path: Option<&Path,>,
+) -> Result<(), Box<dyn std::error::Error,>,> {
+    if let Some(path,) = rejects_path {
+        let file = fs::File::create(path,).map_err(|e| rejects_file_error(path, e,),)?;
+        rejects.stream_to(Box::new(std::io::BufWriter::new(file,),),);
     }
     Ok((),)
 }
 
+/// Flushes the rejects file, if any, and warns when any record was skipped.
+fn write_rejects(
+    rejects: &RejectCollector,
+    rejects_path: Option<&Path,>,
+) -> Result<(), Box<dyn std::error::Error,>,> {
+    let skipped = rejects.report().skipped;
+    if skipped > 0 {
+        warn!("Skipped {} malformed record(s)", skipped);
+    }
+    if let Some(path,) = rejects_path {
+        rejects.flush().map_err(|e| rejects_file_error(path, e,),)?;
+    }
+    Ok((),)
+}
+
+fn rejects_file_error(path: &Path, e: std::io::Error,) -> Box<dyn std::error::Error,> {
+    Box::new(std::io::Error::new(
+        e.kind(),
+        format!("Failed to write rejects file {}: {}", path.display(), e),
+    ),)
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error,>,> {
-    // Initialize tracing
-    let file_appender = tracing_appender::rolling::never(".", "reader.log",);
-    let (non_blocking, _guard,) = tracing_appender::non_blocking(file_appender,);
-
-    tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info",),),)
-        .with(fmt::layer().with_writer(std::io::stderr,),)
-        .with(fmt::layer().with_writer(non_blocking,).with_ansi(false,),)
-        .init();
-
-    let path = std::env::current_dir().map_err(|e| {
-        Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            format!("Error getting current directory: {}", e),
-        ),) as Box<dyn std::error::Error,>
-    },)?;
-    info!("The current directory is {}", path.display());
+async fn main() -> ExitCode {
+    // Errors are printed with `Display` so parse errors show their position in the input.
+    match run().await {
+        Ok((),) => ExitCode::SUCCESS,
+        Err(e,) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error,>,> {
     if std::env::args().len() == 1 {
         // If no arguments are provided, print the help message
         Cli::command().print_help().map_err(|e| {
@@ -154,20 +702,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error,>,> {
     }
 
     let cli = Cli::parse(); // Removed mut
+    let loaded = ConfigSources::from_environment().load(cli.profile.as_deref(),)?;
+    let flags = cli.settings();
+    let level = LogOptions::level_for(cli.verbose, cli.quiet,);
+    let log_options = loaded.settings.clone().merge(flags.clone(),).log_options(level,)?;
+    // Flushes the log file when `run` returns.
+    let _log_guard = init_logging(&log_options,)?;
+    info!("Configuration read from {:?}", loaded.sources);
+    for warning in &loaded.warnings {
+        warn!("{}", warning);
+    }
+
+    match cli.command {
+        Some(Command::Config(ConfigCommand::Show,),) => return show_config(&loaded,),
+        Some(Command::Convert(args,),) => return convert(args, loaded.settings,),
+        Some(Command::ServeFlight(args,),) => {
+            // Limits and reader settings apply to every file served.
+            let settings = loaded.settings.merge(flags,);
+            let batch_size =
+                args.batch_size.or(settings.batch_size,).unwrap_or(DEFAULT_BATCH_SIZE,);
+            let config = settings.read_config()?;
+            return nc_reader::flight::serve_flight(&args.root, args.addr, batch_size, config,)
+                .await
+                .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,);
+        },
+        None => {},
+    }
+
+    // Command-line flags override the configuration files and environment.
+    let settings = loaded.settings.merge(flags,);
 
-    // Infer output format from output_path extension if --format is not explicitly set
-    let determined_output_format = if cli.output_path.is_some() && cli.format == OutputFormat::Text
+    // Infer output format from output_path extension (ignoring a trailing .gz or .zst) if
+    // --format is not explicitly set
+    let determined_output_format = match cli
+        .output_path
+        .as_deref()
+        .and_then(|path| OutputFormat::from_extension(&SinkCompression::strip_extension(path,),),)
     {
-        cli.output_path
-            .as_ref()
-            .and_then(|path| OutputFormat::from_extension(path,),)
-            .unwrap_or(OutputFormat::Text,) // Fallback to Text if extension doesn't match
-    } else {
-        cli.format
+        Some(format,) if cli.format.is_none() => format,
+        _ => settings.output_format()?.unwrap_or_default(),
     };
 
     let output_mode = if cli.all {
         OutputMode::FullRaw
+    } else if cli.stream {
+        OutputMode::Stream
+    } else if cli.analyze {
+        OutputMode::Analyze
     } else if cli.schema {
         OutputMode::SchemaOnly
     } else {
@@ -182,16 +763,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error,>,> {
     },);
 
     let options = FileReaderOptions {
-        head: cli.head,
-        file_type_override: cli.file_type,
         output_mode,
         output_format: determined_output_format, // Use determined format
-        recursive: cli.recursive,
-        filter_exts: cli.filter_ext.map(|e| vec![e],),
         output_path: canonicalized_output_path.clone(), // Clone here to pass to options
+        ..settings.reader_options()?
+    };
+    let rejects = options.read_config.rejects.clone();
+    let rejects_path = settings.rejects_path.clone();
+    open_rejects(&rejects, rejects_path.as_deref(),)?;
+    let sink_options = settings.sink_options()?;
+
+    // One plain --file-path keeps its own result shape; anything else is read as a list of
+    // inputs into directory results.
+    let single_file = match (cli.file_path.as_slice(), cli.directory_path.is_empty(),) {
+        ([path,], true,) if !is_glob_pattern(path,) => Some(path.clone(),),
+        _ => None,
     };
 
-    let result = if let Some(file_path_arg,) = cli.file_path {
+    if determined_output_format.is_columnar() {
+        let columnar = settings.columnar_options()?;
+        let Some(file_path_arg,) = single_file else {
+            return Err(Box::<dyn std::error::Error,>::from(format!(
+                "{} output needs a single --file-path input, not a pattern or several inputs",
+                determined_output_format
+            ),),);
+        };
+        write_columnar(
+            &file_path_arg,
+            &options,
+            determined_output_format,
+            &columnar,
+            canonicalized_output_path.as_deref(),
+            sink_options,
+        )?;
+        return write_rejects(&rejects, rejects_path.as_deref(),);
+    }
+
+    let result = if let Some(file_path_arg,) = single_file {
+        if file_path_arg.as_os_str() == "-" {
+            // With --stream, CSV, JSON and XML records are written as stdin delivers them.
+            let result = nc_reader::file_reader::read_reader_content(
+                Box::new(std::io::stdin(),),
+                &file_path_arg,
+                options,
+            )
+            .await
+            .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,)?;
+            write_result(
+                result,
+                output_mode,
+                determined_output_format,
+                canonicalized_output_path.as_deref(),
+                sink_options,
+            )?;
+            return write_rejects(&rejects, rejects_path.as_deref(),);
+        }
+
         let absolute_path = std::fs::canonicalize(&file_path_arg,).map_err(|e| {
             Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -220,20 +847,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error,>,> {
                 .await
                 .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,)?
         }
-    } else if let Some(directory_path,) = cli.directory_path {
-        let absolute_path = std::fs::canonicalize(&directory_path,)
+    } else if let ([], [directory_path,],) =
+        (cli.file_path.as_slice(), cli.directory_path.as_slice(),)
+    {
+        let absolute_path = std::fs::canonicalize(directory_path,)
             .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,)?;
         nc_reader::file_reader::read_directory_content(&absolute_path, options,)
             .await
             .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,)?
     } else {
-        return Err(Box::<dyn std::error::Error,>::from(
-            "Either FILE_PATH or --directory-path must be provided.",
-        ),);
+        if cli.file_path.iter().any(|path| path.as_os_str() == "-",) {
+            return Err(Box::<dyn std::error::Error,>::from(
+                "Standard input (-) cannot be read together with other inputs",
+            ),);
+        }
+        let inputs: Vec<PathBuf,> =
+            cli.file_path.iter().chain(&cli.directory_path,).cloned().collect();
+        nc_reader::file_reader::read_inputs_content(&inputs, options,)
+            .await
+            .map_err(|e| Box::new(e,) as Box<dyn std::error::Error,>,)?
     };
 
-    let formatted_output = result.to_string_formatted(determined_output_format,); // Use determined format
-
-    write_output(&formatted_output, canonicalized_output_path.as_deref(),)?;
-    Ok((),)
+    write_result(
+        result,
+        output_mode,
+        determined_output_format,
+        canonicalized_output_path.as_deref(),
+        sink_options,
+    )?;
+    write_rejects(&rejects, rejects_path.as_deref(),)
 }
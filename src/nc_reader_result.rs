@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::fmt;
-use std::path::PathBuf; // New import for PathBuf
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use crate::error::DataReaderError;
+use crate::envelope::ResultEnvelope;
+use crate::error::{DataReaderError, ErrorPosition};
 use crate::output::OutputFormat;
+use crate::record::NcValue;
 use crate::reader::csv_reader::CsvData;
 use crate::reader::gzip_reader::GzipData;
 use crate::reader::image_reader::ImageData;
@@ -20,18 +23,68 @@ use crate::reader::txt_reader::TextData;
 use crate::reader::xml_reader::XmlData;
 use crate::reader::yaml_reader::YamlData;
 use crate::reader::zip_reader::ZipData;
+use crate::writer::{RecordWriter, format_value, write_record_stream};
 
 pub type RecordStream =
     Box<dyn Iterator<Item = Result<serde_json::Value, DataReaderError,>,> + Send,>;
 
-#[derive(Debug, Serialize, Deserialize,)]
+/// A record stream that keeps source types (decimals, timestamps, binary) intact.
+pub type TypedRecordStream = Box<dyn Iterator<Item = Result<NcValue, DataReaderError,>,> + Send,>;
+
+/// Renders each record of a typed stream with `NcValue::to_json`.
+pub(crate) fn typed_to_json_stream(stream: TypedRecordStream,) -> RecordStream {
+    Box::new(stream.map(|record| record.map(|value| value.to_json(),),),)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize,)]
 pub struct FileMetadata {
     pub size:       u64,
     pub line_count: Option<usize,>,
 }
 
-#[derive(Serialize, Deserialize,)]
-#[serde(untagged)] // Use untagged enum for flexible deserialization
+/// Summary produced by a format reader registered from outside this crate.
+#[derive(Debug, Serialize, Deserialize,)]
+pub struct CustomData {
+    pub format: String,
+    pub value:  serde_json::Value,
+}
+
+/// A file that could not be read while reading a directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize,)]
+pub struct FileErrorData {
+    pub error:    String,
+    /// Where the file failed to parse, when the reader reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<ErrorPosition,>,
+}
+
+/// A file read that has not started. In `OutputMode::Stream` the entries of a directory result
+/// are pending reads, which `write_streaming` opens one at a time: a file is opened, and its time
+/// limit starts, only once the files before it have been written.
+pub struct PendingRead(Box<dyn FnOnce() -> DataReaderResult + Send,>,);
+
+impl PendingRead {
+    pub fn new(read: impl FnOnce() -> DataReaderResult + Send + 'static,) -> Self {
+        PendingRead(Box::new(read,),)
+    }
+
+    /// Reads the file. A read that fails gives a `DataReaderResult::Error`.
+    pub fn open(self,) -> DataReaderResult {
+        (self.0)()
+    }
+}
+
+impl From<&DataReaderError,> for FileErrorData {
+    fn from(error: &DataReaderError,) -> Self {
+        FileErrorData {
+            error:    error.to_string(),
+            position: error.position().cloned(),
+        }
+    }
+}
+
+/// The result of a read. It serializes as its [`ResultEnvelope`], tagged with `format` and
+/// `version`, and deserializes from one.
 pub enum DataReaderResult {
     Csv(CsvData, FileMetadata,),
     Gzip(GzipData, FileMetadata,),
@@ -49,11 +102,25 @@ pub enum DataReaderResult {
     Xml(XmlData, FileMetadata,),
     Yaml(YamlData, FileMetadata,),
     Zip(ZipData, FileMetadata,),
+    Custom(CustomData, FileMetadata,),
+    Error(FileErrorData, FileMetadata,),
     RawContent(String, FileMetadata,), // New variant for raw content
-    #[serde(skip_serializing)] // Skip serialization of this variant directly
     DirectoryResults(Vec<(PathBuf, DataReaderResult,),>, FileMetadata,), // New variant
-    #[serde(skip)]
     Stream(RecordStream, FileMetadata,),
+    Pending(PendingRead, FileMetadata,),
+}
+
+impl Serialize for DataReaderResult {
+    fn serialize<S: serde::Serializer,>(&self, serializer: S,) -> Result<S::Ok, S::Error,> {
+        self.to_envelope().serialize(serializer,)
+    }
+}
+
+impl<'de,> Deserialize<'de,> for DataReaderResult {
+    fn deserialize<D: serde::Deserializer<'de,>,>(deserializer: D,) -> Result<Self, D::Error,> {
+        let envelope = ResultEnvelope::deserialize(deserializer,)?;
+        envelope.into_result().map_err(serde::de::Error::custom,)
+    }
 }
 
 impl fmt::Debug for DataReaderResult {
@@ -86,6 +153,10 @@ impl fmt::Debug for DataReaderResult {
             DataReaderResult::Xml(d, m,) => f.debug_tuple("Xml",).field(d,).field(m,).finish(),
             DataReaderResult::Yaml(d, m,) => f.debug_tuple("Yaml",).field(d,).field(m,).finish(),
             DataReaderResult::Zip(d, m,) => f.debug_tuple("Zip",).field(d,).field(m,).finish(),
+            DataReaderResult::Custom(d, m,) => {
+                f.debug_tuple("Custom",).field(d,).field(m,).finish()
+            },
+            DataReaderResult::Error(d, m,) => f.debug_tuple("Error",).field(d,).field(m,).finish(),
             DataReaderResult::RawContent(d, m,) => {
                 f.debug_tuple("RawContent",).field(d,).field(m,).finish()
             },
@@ -99,67 +170,49 @@ impl fmt::Debug for DataReaderResult {
                 .field(&"<RecordStream>",)
                 .field(m,)
                 .finish(),
+            DataReaderResult::Pending(_, m,) => f
+                .debug_tuple("Pending",)
+                .field(&"<PendingRead>",)
+                .field(m,)
+                .finish(),
         }
     }
 }
 
 impl DataReaderResult {
-    // This method will now take an OutputFormat to determine serialization
+    /// The result rendered in `format`. JSON and YAML are the [`ResultEnvelope`]; see
+    /// `to_json_value` and `tabular_rows` for the others.
     pub fn to_string_formatted(&self, format: OutputFormat,) -> String {
         match format {
-            OutputFormat::Json => match self {
-                DataReaderResult::DirectoryResults(results, _metadata,) => {
-                    let serialized_results: Vec<serde_json::Value> = results.iter().map(|(path, nc_result)| {
-                            let result_value = match nc_result {
-                                DataReaderResult::Json(json_data, _meta) => serde_json::to_value(&json_data.value).unwrap_or_else(|_| serde_json::json!({"error": "Failed to serialize inner json value"})),
-                                _ => serde_json::to_value(nc_result).unwrap_or_else(|_| serde_json::json!({"error": "Failed to serialize inner result"})),
-                            };
-                            serde_json::json!({
-                                "path": path.to_string_lossy(),
-                                "result": result_value,
-                            })
-                        }).collect();
-                    serde_json::to_string_pretty(&serialized_results,).unwrap_or_else(|e| {
-                        format!("Error serializing directory results to JSON: {}", e)
-                    },)
-                },
-                _ => serde_json::to_string_pretty(self,)
-                    .unwrap_or_else(|e| format!("Error serializing to JSON: {}", e),),
+            OutputFormat::Json => serde_json::to_string_pretty(&self.to_envelope(),)
+                .unwrap_or_else(|e| format!("Error serializing to JSON: {}", e),),
+            OutputFormat::Yaml => serde_yaml::to_string(&self.to_envelope(),)
+                .unwrap_or_else(|e| format!("Error serializing to YAML: {}", e),),
+            OutputFormat::Ndjson => {
+                // A directory result is one file envelope per line, as streamed output writes it.
+                let value = match self {
+                    DataReaderResult::DirectoryResults(..,) => self.to_envelope().data,
+                    _ => self.to_json_value(),
+                };
+                format_value(&value, format,)
+                    .unwrap_or_else(|e| format!("Error serializing to {}: {}", format, e),)
             },
-            OutputFormat::Yaml => match self {
-                DataReaderResult::DirectoryResults(results, _metadata,) => {
-                    let serialized_results: Vec<serde_yaml::Value,> = results
-                        .iter()
-                        .map(|(path, nc_result,)| {
-                            let result_value =
-                                serde_yaml::to_value(nc_result,).unwrap_or_else(|_| {
-                                    serde_yaml::Value::String(
-                                        "Failed to serialize inner result".to_string(),
-                                    )
-                                },);
-                            let mut map = serde_yaml::Mapping::new();
-                            map.insert(
-                                serde_yaml::Value::String("path".to_string(),),
-                                serde_yaml::Value::String(path.to_string_lossy().into_owned(),),
-                            );
-                            map.insert(
-                                serde_yaml::Value::String("result".to_string(),),
-                                result_value,
-                            );
-                            serde_yaml::Value::Mapping(map,)
-                        },)
-                        .collect();
-                    serde_yaml::to_string(&serialized_results,).unwrap_or_else(|e| {
-                        format!("Error serializing directory results to YAML: {}", e)
-                    },)
-                },
-                _ => serde_yaml::to_string(self,)
-                    .unwrap_or_else(|e| format!("Error serializing to YAML: {}", e),),
+            OutputFormat::Xml
+            | OutputFormat::Toml
+            | OutputFormat::Parquet
+            | OutputFormat::ArrowIpc => {
+                format_value(&self.to_json_value(), format,)
+                    .unwrap_or_else(|e| format!("Error serializing to {}: {}", format, e),)
+            },
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                format_value(&serde_json::Value::Array(self.tabular_rows(),), format,)
+                    .unwrap_or_else(|e| format!("Error serializing to {}: {}", format, e),)
             },
             OutputFormat::Text => {
                 match self {
                     DataReaderResult::RawContent(s, _metadata,) => s.clone(),
                     DataReaderResult::Text(text_data, _metadata,) => text_data.content.clone(), /* Handle TextData specifically */
+                    DataReaderResult::Error(e, _metadata,) => format!("Error: {}", e.error),
                     DataReaderResult::DirectoryResults(results, _metadata,) => {
                         // For text output, iterate and print each result with its path
                         results
@@ -273,6 +326,9 @@ impl DataReaderResult {
                     DataReaderResult::Stream(_, _metadata,) => {
                         "Stream data (cannot be displayed)".to_string()
                     },
+                    DataReaderResult::Pending(_, _metadata,) => {
+                        "Pending read (cannot be displayed)".to_string()
+                    },
                     _ => format!("{:?}", self),
                 } // This closes the match self block
             }, // This closes the OutputFormat::Text arm
@@ -280,6 +336,143 @@ impl DataReaderResult {
     }
 }
 
+impl DataReaderResult {
+    /// The versioned envelope that JSON and YAML output are written as.
+    pub fn to_envelope(&self,) -> ResultEnvelope {
+        ResultEnvelope::from_result(self, None,)
+    }
+
+    /// The result as one JSON value: its envelope, so a directory result is a `directory`
+    /// envelope holding one envelope per file. Streams and pending reads become a `stream`
+    /// envelope carrying an error.
+    pub fn to_json_value(&self,) -> serde_json::Value {
+        serde_json::to_value(self.to_envelope(),).unwrap_or_else(|e| {
+            serde_json::json!({ "error": format!("Failed to serialize result: {}", e) })
+        },)
+    }
+
+    /// The rows of a tabular result, for CSV and TSV output: the records of CSV and JSON
+    /// data, the sample rows of Parquet data, the sheets of a spreadsheet and the tables of a
+    /// SQLite database. Other results are a single row. Directory rows get a `path` column.
+    pub fn tabular_rows(&self,) -> Vec<serde_json::Value,> {
+        match self {
+            DataReaderResult::Csv(data, _metadata,) => data.nc_rows.clone(),
+            DataReaderResult::Json(data, _metadata,) => match &data.value {
+                serde_json::Value::Array(items,) => items.clone(),
+                other => vec![other.clone()],
+            },
+            DataReaderResult::Parquet(data, _metadata,) => data
+                .sample_rows
+                .iter()
+                .flatten()
+                .map(|row| serde_json::json!(row.0),)
+                .collect(),
+            DataReaderResult::Spreadsheet(data, _metadata,) => {
+                data.sheets.iter().map(|sheet| serde_json::json!(sheet),).collect()
+            },
+            DataReaderResult::Sqlite(data, _metadata,) => {
+                data.tables.iter().map(|table| serde_json::json!(table),).collect()
+            },
+            DataReaderResult::DirectoryResults(results, _metadata,) => results
+                .iter()
+                .flat_map(|(path, result,)| {
+                    let path = serde_json::Value::String(path.to_string_lossy().into_owned(),);
+                    result.tabular_rows().into_iter().map(move |row| {
+                        let mut with_path = serde_json::Map::new();
+                        with_path.insert("path".to_string(), path.clone(),);
+                        match row {
+                            serde_json::Value::Object(fields,) => with_path.extend(fields,),
+                            other => {
+                                with_path.insert("value".to_string(), other,);
+                            },
+                        }
+                        serde_json::Value::Object(with_path,)
+                    },)
+                },)
+                .collect(),
+            other => vec![other.to_json_value()],
+        }
+    }
+}
+
+impl DataReaderResult {
+    /// Writes the result incrementally. Record streams are written record by record with a
+    /// [`RecordWriter`]. Every document of a directory result is a [`ResultEnvelope`] with its
+    /// `path`: a `record` envelope per record, an `error` envelope for a file that fails (before
+    /// moving on to the next file) and the file's own envelope for any other result. Pending
+    /// reads are opened one at a time, when the writer gets to them. Any other result is written
+    /// whole, as `to_string_formatted` renders it.
+    pub fn write_streaming<W: Write + ?Sized,>(
+        self,
+        format: OutputFormat,
+        writer: &mut W,
+    ) -> Result<(), DataReaderError,> {
+        match self {
+            DataReaderResult::Stream(stream, _metadata,) => {
+                write_record_stream(stream, format, writer,).map(|_| (),)
+            },
+            DataReaderResult::Pending(pending, _metadata,) => {
+                pending.open().write_streaming(format, writer,)
+            },
+            DataReaderResult::DirectoryResults(results, _metadata,) => {
+                let mut records = RecordWriter::new(writer, format,);
+                for (path, result,) in results {
+                    if !write_directory_entry(&path, result, &mut records,)? {
+                        return Ok((),);
+                    }
+                }
+                records.finish().map(|_| (),)
+            },
+            other => {
+                let formatted = other.to_string_formatted(format,);
+                writeln!(writer, "{}", formatted).map_err(|e| {
+                    DataReaderError::InternalError(format!("Failed to write output: {}", e),)
+                },)
+            },
+        }
+    }
+}
+
+/// Writes one file of a directory result, reading it first if it is pending. Returns `false` if
+/// the pipe was closed.
+fn write_directory_entry<W: Write + ?Sized,>(
+    path: &Path,
+    result: DataReaderResult,
+    records: &mut RecordWriter<'_, W,>,
+) -> Result<bool, DataReaderError,> {
+    match result {
+        DataReaderResult::Pending(pending, _metadata,) => {
+            write_directory_entry(path, pending.open(), records,)
+        },
+        DataReaderResult::Stream(stream, metadata,) => {
+            write_directory_stream(path, stream, &metadata, records,)
+        },
+        other => records.write(&ResultEnvelope::from_result(&other, Some(path,),),),
+    }
+}
+
+/// Writes the records of one file of a directory result. Returns `false` if the pipe was closed.
+fn write_directory_stream<W: Write + ?Sized,>(
+    path: &Path,
+    stream: RecordStream,
+    metadata: &FileMetadata,
+    records: &mut RecordWriter<'_, W,>,
+) -> Result<bool, DataReaderError,> {
+    for record in stream {
+        let entry = match record {
+            Ok(record,) => ResultEnvelope::record(path, metadata, record,),
+            Err(e,) => {
+                let error = DataReaderResult::Error(FileErrorData::from(&e,), metadata.clone(),);
+                return records.write(&ResultEnvelope::from_result(&error, Some(path,),),);
+            },
+        };
+        if !records.write(&entry,)? {
+            return Ok(false,);
+        }
+    }
+    Ok(true,)
+}
+
 // Implement Display trait for DataReaderResult to allow direct printing
 impl fmt::Display for DataReaderResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
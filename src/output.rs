@@ -10,6 +10,18 @@ pub enum OutputFormat {
     Text,
     Json,
     Yaml,
+    /// Comma-separated rows with nested values flattened into `parent.child` columns.
+    Csv,
+    /// Tab-separated rows, flattened like `Csv`.
+    Tsv,
+    /// One compact JSON value per line.
+    Ndjson,
+    Xml,
+    Toml,
+    /// A Parquet file; binary, so only written through `crate::columnar`.
+    Parquet,
+    /// An Arrow IPC (Feather v2) file; binary, so only written through `crate::columnar`.
+    ArrowIpc,
 }
 
 impl fmt::Display for OutputFormat {
@@ -18,6 +30,13 @@ impl fmt::Display for OutputFormat {
             OutputFormat::Text => write!(f, "text"),
             OutputFormat::Json => write!(f, "json"),
             OutputFormat::Yaml => write!(f, "yaml"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Tsv => write!(f, "tsv"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
+            OutputFormat::Xml => write!(f, "xml"),
+            OutputFormat::Toml => write!(f, "toml"),
+            OutputFormat::Parquet => write!(f, "parquet"),
+            OutputFormat::ArrowIpc => write!(f, "arrow-ipc"),
         }
     }
 }
@@ -30,12 +49,25 @@ impl OutputFormat {
                 match ext_str.to_lowercase().as_str() {
                     "json" => Some(OutputFormat::Json,),
                     "yaml" | "yml" => Some(OutputFormat::Yaml,),
+                    "csv" => Some(OutputFormat::Csv,),
+                    "tsv" => Some(OutputFormat::Tsv,),
+                    "ndjson" | "jsonl" => Some(OutputFormat::Ndjson,),
+                    "xml" => Some(OutputFormat::Xml,),
+                    "toml" => Some(OutputFormat::Toml,),
+                    "parquet" => Some(OutputFormat::Parquet,),
+                    "arrow" | "feather" | "ipc" => Some(OutputFormat::ArrowIpc,),
                     "txt" => Some(OutputFormat::Text,), // Explicitly map .txt to Text
                     _ => None,                          /* No matching output format for other
                                                           * extensions */
                 }
             },)
     }
+
+    /// Whether the format is a binary columnar file, written from record batches rather than
+    /// rendered as text.
+    pub fn is_columnar(self,) -> bool {
+        matches!(self, OutputFormat::Parquet | OutputFormat::ArrowIpc)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default,)]
@@ -47,3 +79,4 @@ pub enum OutputMode {
     Analyze, // New variant for analysis-ready data
     Stream,  // New variant for streaming records
 }
+
@@ -0,0 +1,55 @@
+use std::pin::Pin;
+
+use futures::stream::{self, Stream};
+use tokio::sync::mpsc;
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::RecordStream;
+
+pub type AsyncRecordStream =
+    Pin<Box<dyn Stream<Item = Result<serde_json::Value, DataReaderError,>,> + Send,>,>;
+
+/// Number of records buffered between the blocking reader and the async consumer.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Drives a blocking `RecordStream` on tokio's blocking pool and forwards its records through a
+/// bounded channel, so a slow consumer applies backpressure instead of buffering the whole file.
+/// Dropping the returned stream stops the reader at its next record.
+pub fn into_async_stream(records: RecordStream, capacity: usize,) -> AsyncRecordStream {
+    blocking_iter_to_stream(records, capacity,)
+}
+
+/// Generic form of `into_async_stream` for any blocking iterator, such as a record batch stream.
+pub fn blocking_iter_to_stream<I,>(
+    iter: I,
+    capacity: usize,
+) -> Pin<Box<dyn Stream<Item = I::Item,> + Send,>,>
+where
+    I: Iterator + Send + 'static,
+    I::Item: Send + 'static,
+{
+    let (tx, rx,) = mpsc::channel(capacity.max(1,),);
+    tokio::task::spawn_blocking(move || {
+        for item in iter {
+            if tx.blocking_send(item,).is_err() {
+                // The receiver was dropped; stop reading.
+                break;
+            }
+        }
+    },);
+    Box::pin(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx,),)
+    },),)
+}
+
+/// Runs a blocking stream constructor (file open, charset sniffing, header parsing) on the
+/// blocking pool and wraps the resulting stream with `into_async_stream`.
+pub async fn open_async_stream<F,>(open: F,) -> Result<AsyncRecordStream, DataReaderError,>
+where
+    F: FnOnce() -> Result<RecordStream, DataReaderError,> + Send + 'static,
+{
+    let records = tokio::task::spawn_blocking(open,)
+        .await
+        .map_err(|e| DataReaderError::InternalError(format!("Reader task failed: {}", e),),)??;
+    Ok(into_async_stream(records, DEFAULT_CHANNEL_CAPACITY,),)
+}
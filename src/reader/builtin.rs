@@ -0,0 +1,889 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+
+use crate::error::DataReaderError;
+use crate::file_reader::{FileFormat, serialize_raw_content_to_string};
+use crate::nc_reader_result::{DataReaderResult, FileMetadata, RecordStream, TypedRecordStream};
+use crate::output::OutputFormat;
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::RecordBatchStream;
+use crate::reader::registry::{FormatReader, ReadSource};
+
+fn file_metadata(
+    file_path: &Path,
+    line_count: Option<usize,>,
+) -> Result<FileMetadata, DataReaderError,> {
+    let metadata = std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    },)?;
+    Ok(FileMetadata {
+        size: metadata.len(),
+        line_count,
+    },)
+}
+
+/// Fails if `config` filters records: only the CSV and JSON summaries are built from a record
+/// stream the filter can apply to, and a summary that ignored it would look filtered.
+fn unfiltered_summary(
+    reader: &dyn FormatReader,
+    config: &ReadConfig,
+) -> Result<(), DataReaderError,> {
+    config.filter.require_empty(&format!("a {} summary", reader.name()),)
+}
+
+pub fn builtin_readers() -> Vec<Arc<dyn FormatReader,>,> {
+    vec![
+        Arc::new(CsvFormat,),
+        Arc::new(GzipFormat,),
+        Arc::new(ImageFormat,),
+        Arc::new(JsonFormat,),
+        Arc::new(MarkdownFormat,),
+        Arc::new(ParquetFormat,),
+        Arc::new(PdfFormat,),
+        Arc::new(SpreadsheetFormat,),
+        Arc::new(SqliteFormat,),
+        Arc::new(TomlFormat,),
+        Arc::new(TextFormat,),
+        Arc::new(XmlFormat,),
+        Arc::new(YamlFormat,),
+        Arc::new(ZipFormat,),
+    ]
+}
+
+pub struct CsvFormat;
+
+impl FormatReader for CsvFormat {
+    fn name(&self,) -> &str {
+        "csv"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["csv", "tsv", "psv"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Csv
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        match source {
+            ReadSource::File(path,) => {
+                let data =
+                    crate::reader::csv_reader::read_csv_data_with_config(&path, head, config,)?;
+                Ok(DataReaderResult::Csv(data, file_metadata(&path, None,)?,),)
+            },
+            source => {
+                let path = source.path().to_path_buf();
+                let data = source.into_bytes()?;
+                let size = data.len() as u64;
+                let data = crate::reader::csv_reader::read_csv_data_from_bytes(
+                    data, &path, head, config,
+                )?;
+                Ok(DataReaderResult::Csv(
+                    data,
+                    FileMetadata {
+                        size,
+                        line_count: None,
+                    },
+                ),)
+            },
+        }
+    }
+
+    fn read_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<RecordStream,>, DataReaderError,> {
+        let path = source.path().to_path_buf();
+        let (_headers, stream,) =
+            crate::reader::csv_reader::read_csv_stream_from_reader_with_config(
+                source.into_reader()?,
+                &path,
+                config,
+            )?;
+        Ok(Some(stream,),)
+    }
+
+    fn read_typed_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<TypedRecordStream,>, DataReaderError,> {
+        let path = source.path().to_path_buf();
+        let (_headers, stream,) =
+            crate::reader::csv_reader::read_csv_typed_stream_from_reader_with_config(
+                source.into_reader()?,
+                &path,
+                config,
+            )?;
+        Ok(Some(stream,),)
+    }
+
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        crate::reader::csv_reader::read_csv_batches_with_config(path, batch_size, config,)
+            .map(Some,)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        _output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        crate::reader::csv_reader::get_csv_raw_content_with_config(path, head, config,)
+    }
+}
+
+pub struct GzipFormat;
+
+impl FormatReader for GzipFormat {
+    fn name(&self,) -> &str {
+        "gz"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["gz"]
+    }
+
+    fn matches_magic(&self, header: &[u8],) -> bool {
+        header.starts_with(b"\x1f\x8b",)
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Gzip
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::gzip_reader::read_gzip_data_with_config(path, config,)?;
+        Ok(DataReaderResult::Gzip(data, file_metadata(path, None,)?,),)
+    }
+}
+
+pub struct ImageFormat;
+
+impl FormatReader for ImageFormat {
+    fn name(&self,) -> &str {
+        "image"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["jpg", "jpeg", "png", "gif", "bmp", "webp", "svg"]
+    }
+
+    fn matches_magic(&self, header: &[u8],) -> bool {
+        header.starts_with(b"\x89PNG\r\n\x1a\n",) || header.starts_with(b"\xff\xd8\xff",)
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Image
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::image_reader::read_image_data(path,)?;
+        Ok(DataReaderResult::Image(data, file_metadata(path, None,)?,),)
+    }
+}
+
+pub struct JsonFormat;
+
+impl FormatReader for JsonFormat {
+    fn name(&self,) -> &str {
+        "json"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["json", "jsonl"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Json
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        match source {
+            ReadSource::File(path,) => {
+                let data =
+                    crate::reader::json_reader::read_json_value_with_config(&path, head, config,)?;
+                let line_count = data.line_count;
+                Ok(DataReaderResult::Json(data, file_metadata(&path, line_count,)?,),)
+            },
+            source => {
+                let path = source.path().to_path_buf();
+                let data = source.into_bytes()?;
+                let size = data.len() as u64;
+                let data = crate::reader::json_reader::read_json_value_from_bytes(
+                    data, &path, head, config,
+                )?;
+                let line_count = data.line_count;
+                Ok(DataReaderResult::Json(data, FileMetadata { size, line_count, },),)
+            },
+        }
+    }
+
+    fn read_stream(
+        &self,
+        source: ReadSource,
+        _config: &ReadConfig,
+    ) -> Result<Option<RecordStream,>, DataReaderError,> {
+        let path = source.path().to_path_buf();
+        let is_jsonl = path.extension().is_some_and(|ext| ext == "jsonl",);
+        crate::reader::json_reader::read_json_stream_from_reader(
+            source.into_reader()?,
+            is_jsonl,
+            &path,
+        )
+        .map(Some,)
+    }
+
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        _config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        crate::reader::json_reader::read_json_batches(path, batch_size,).map(Some,)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        _output_format: OutputFormat,
+        _config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        crate::reader::json_reader::get_json_raw_content(source.require_file(self.name(),)?, head,)
+    }
+}
+
+pub struct MarkdownFormat;
+
+impl FormatReader for MarkdownFormat {
+    fn name(&self,) -> &str {
+        "md"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["md"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Markdown
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::md_reader::read_md_content_with_config(path, head, config,)?;
+        let line_count = data.content.lines().count();
+        Ok(DataReaderResult::Markdown(data, file_metadata(path, Some(line_count,),)?,),)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        let markdown_data =
+            crate::reader::md_reader::read_md_content_with_config(path, head, config,)?;
+        serialize_raw_content_to_string(markdown_data.content, output_format, "Markdown",)
+    }
+}
+
+pub struct ParquetFormat;
+
+impl FormatReader for ParquetFormat {
+    fn name(&self,) -> &str {
+        "parquet"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["parquet"]
+    }
+
+    fn matches_magic(&self, header: &[u8],) -> bool {
+        header.starts_with(b"PAR1",)
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Parquet
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        match source {
+            ReadSource::File(path,) => {
+                let data = crate::reader::parquet_reader::read_parquet_data_with_config(
+                    &path, head, config,
+                )?;
+                let num_rows = data.num_rows;
+                Ok(DataReaderResult::Parquet(
+                    data,
+                    file_metadata(&path, Some(num_rows as usize,),)?,
+                ),)
+            },
+            source => {
+                let path = source.path().to_path_buf();
+                let data = source.into_bytes()?;
+                let size = data.len() as u64;
+                let data = crate::reader::parquet_reader::read_parquet_data_from_bytes(
+                    data, &path, head, config,
+                )?;
+                let line_count = Some(data.num_rows as usize,);
+                Ok(DataReaderResult::Parquet(data, FileMetadata { size, line_count, },),)
+            },
+        }
+    }
+
+    fn read_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<RecordStream,>, DataReaderError,> {
+        Ok(self.read_typed_stream(source, config,)?.map(|stream| {
+            Box::new(stream.map(|record| record.map(|value| value.to_json(),),),) as RecordStream
+        },),)
+    }
+
+    /// Only the columns `config.filter` needs are decoded. Sources other than files are read
+    /// into memory first, as the footer comes last.
+    fn read_typed_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<TypedRecordStream,>, DataReaderError,> {
+        let columns = config.filter.required_columns();
+        let stream = match source {
+            ReadSource::File(path,) => {
+                crate::reader::parquet_reader::read_parquet_typed_stream_projected(
+                    &path,
+                    columns.as_deref(),
+                )?
+            },
+            source => {
+                let path = source.path().to_path_buf();
+                crate::reader::parquet_reader::read_parquet_typed_stream_from_bytes(
+                    source.into_bytes()?,
+                    &path,
+                )?
+            },
+        };
+        Ok(Some(stream,),)
+    }
+
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        _config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        crate::reader::parquet_reader::read_parquet_batches(path, batch_size,).map(Some,)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        let all_rows =
+            crate::reader::parquet_reader::read_full_parquet_content_with_config(path, config,)?;
+        match output_format {
+            OutputFormat::Json => serde_json::to_string_pretty(&all_rows,).map_err(|e| {
+                DataReaderError::InternalError(format!(
+                    "Failed to serialize Parquet raw content to JSON: {}",
+                    e
+                ),)
+            },),
+            OutputFormat::Yaml => serde_yaml::to_string(&all_rows,).map_err(|e| {
+                DataReaderError::InternalError(format!(
+                    "Failed to serialize Parquet raw content to YAML: {}",
+                    e
+                ),)
+            },),
+            OutputFormat::Text => Err(DataReaderError::InternalError(format!(
+                "Unsupported output format for Parquet raw content: {:?}",
+                output_format
+            ),),),
+            _ => crate::writer::format_value(&serde_json::json!(all_rows), output_format,),
+        }
+    }
+}
+
+pub struct PdfFormat;
+
+impl FormatReader for PdfFormat {
+    fn name(&self,) -> &str {
+        "pdf"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["pdf"]
+    }
+
+    fn matches_magic(&self, header: &[u8],) -> bool {
+        header.starts_with(b"%PDF",)
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Pdf
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::pdf_reader::read_pdf_text_with_config(path, head, config,)?;
+        let line_count = data.line_count;
+        Ok(DataReaderResult::Pdf(data, file_metadata(path, Some(line_count,),)?,),)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        let pdf_data = crate::reader::pdf_reader::read_pdf_text_with_config(path, head, config,)?;
+        serialize_raw_content_to_string(pdf_data.content, output_format, "PDF",)
+    }
+}
+
+pub struct SpreadsheetFormat;
+
+impl FormatReader for SpreadsheetFormat {
+    fn name(&self,) -> &str {
+        "spreadsheet"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["xlsx", "xls", "ods"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Spreadsheet
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data =
+            crate::reader::spreadsheet_reader::read_spreadsheet_data_with_config(path, config,)?;
+        Ok(DataReaderResult::Spreadsheet(data, file_metadata(path, None,)?,),)
+    }
+
+    /// Reads the first sheet; use `read_spreadsheet_batches_with_config` to pick another one.
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        crate::reader::spreadsheet_reader::read_spreadsheet_batches_with_config(
+            source.require_file(self.name(),)?,
+            None,
+            batch_size,
+            config,
+        )
+        .map(Some,)
+    }
+}
+
+pub struct SqliteFormat;
+
+impl FormatReader for SqliteFormat {
+    fn name(&self,) -> &str {
+        "sqlite"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["sqlite", "db"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Sqlite
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::sqlite_reader::read_sqlite_data_with_config(path, config,)?;
+        Ok(DataReaderResult::Sqlite(data, file_metadata(path, None,)?,),)
+    }
+
+    /// Reads the first table; use `read_sqlite_batches_with_config` to pick another one.
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        match crate::reader::sqlite_reader::first_sqlite_table(path,)? {
+            Some(table,) => crate::reader::sqlite_reader::read_sqlite_batches_with_config(
+                path,
+                &table,
+                batch_size,
+                config,
+            )
+            .map(Some,),
+            None => Ok(None,),
+        }
+    }
+}
+
+pub struct TomlFormat;
+
+impl FormatReader for TomlFormat {
+    fn name(&self,) -> &str {
+        "toml"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["toml"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Toml
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::toml_reader::read_toml_value_with_config(path, head, config,)?;
+        Ok(DataReaderResult::Toml(data, file_metadata(path, None,)?,),)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        _output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        crate::reader::toml_reader::get_toml_raw_content_with_config(
+            source.require_file(self.name(),)?,
+            head,
+            config,
+        )
+    }
+}
+
+pub struct TextFormat;
+
+impl FormatReader for TextFormat {
+    fn name(&self,) -> &str {
+        "txt"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["txt"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Text
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::txt_reader::read_txt_content_with_config(path, head, config,)?;
+        let line_count = data.line_count;
+        let total_size = data.total_size;
+        Ok(DataReaderResult::Text(
+            data,
+            FileMetadata {
+                size:       total_size,
+                line_count: Some(line_count,),
+            },
+        ),)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        let text_data =
+            crate::reader::txt_reader::read_txt_content_with_config(path, head, config,)?;
+        serialize_raw_content_to_string(text_data.content, output_format, "Text",)
+    }
+}
+
+pub struct XmlFormat;
+
+impl FormatReader for XmlFormat {
+    fn name(&self,) -> &str {
+        "xml"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["xml"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Xml
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        match source {
+            ReadSource::File(path,) => {
+                let data =
+                    crate::reader::xml_reader::read_xml_content_with_config(&path, head, config,)?;
+                let line_count = data.content.lines().count();
+                Ok(DataReaderResult::Xml(data, file_metadata(&path, Some(line_count,),)?,),)
+            },
+            source => {
+                let path = source.path().to_path_buf();
+                let data = source.into_bytes()?;
+                let size = data.len() as u64;
+                let data = crate::reader::xml_reader::read_xml_content_from_bytes(
+                    data, &path, head, config,
+                )?;
+                let line_count = Some(data.content.lines().count(),);
+                Ok(DataReaderResult::Xml(data, FileMetadata { size, line_count, },),)
+            },
+        }
+    }
+
+    fn read_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<RecordStream,>, DataReaderError,> {
+        let path = source.path().to_path_buf();
+        crate::reader::xml_reader::create_xml_stream_from_reader_with_config(
+            source.into_reader()?,
+            &path,
+            config,
+        )
+        .map(Some,)
+    }
+
+    fn read_typed_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<TypedRecordStream,>, DataReaderError,> {
+        let path = source.path().to_path_buf();
+        crate::reader::xml_reader::create_xml_typed_stream_from_reader_with_config(
+            source.into_reader()?,
+            &path,
+            config,
+        )
+        .map(Some,)
+    }
+
+    fn read_batches(
+        &self,
+        source: ReadSource,
+        batch_size: usize,
+        config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        crate::reader::xml_reader::read_xml_batches_with_config(path, batch_size, config,)
+            .map(Some,)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        let path = source.require_file(self.name(),)?;
+        let xml_data =
+            crate::reader::xml_reader::read_xml_content_with_config(path, head, config,)?;
+        serialize_raw_content_to_string(xml_data.content, output_format, "XML",)
+    }
+}
+
+pub struct YamlFormat;
+
+impl FormatReader for YamlFormat {
+    fn name(&self,) -> &str {
+        "yaml"
+    }
+
+    fn is_text(&self,) -> bool {
+        true
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Yaml
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::yaml_reader::read_yaml_value_with_config(path, head, config,)?;
+        Ok(DataReaderResult::Yaml(data, file_metadata(path, None,)?,),)
+    }
+
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        _output_format: OutputFormat,
+        config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        crate::reader::yaml_reader::get_yaml_raw_content_with_config(
+            source.require_file(self.name(),)?,
+            head,
+            config,
+        )
+    }
+}
+
+pub struct ZipFormat;
+
+impl FormatReader for ZipFormat {
+    fn name(&self,) -> &str {
+        "zip"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["zip"]
+    }
+
+    fn matches_magic(&self, header: &[u8],) -> bool {
+        header.starts_with(b"PK\x03\x04",)
+    }
+
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Zip
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        unfiltered_summary(self, config,)?;
+        let path = source.require_file(self.name(),)?;
+        let data = crate::reader::zip_reader::read_zip_data_with_config(path, config,)?;
+        Ok(DataReaderResult::Zip(data, file_metadata(path, None,)?,),)
+    }
+}
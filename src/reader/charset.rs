@@ -1,7 +1,10 @@
-use std::io::Read;
+use std::io::{Chain, Cursor, Read};
 
 use chardetng::EncodingDetector;
 use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytes;
+
+const SNIFF_LEN: usize = 4096;
 
 pub fn detect_encoding_from_buffer(buffer: &[u8],) -> &'static Encoding {
     let mut detector = EncodingDetector::new();
@@ -36,3 +39,38 @@ pub fn get_decoded_reader(
         .encoding(Some(encoding,),)
         .build(file_to_reset,),)
 }
+
+/// Like `get_decoded_reader`, but for sources that cannot be rewound (pipes, sockets, archive
+/// entries). The sniffed prefix is replayed in front of the remaining input.
+pub fn get_decoded_reader_from<R: Read,>(
+    mut reader: R,
+) -> std::io::Result<DecodeReaderBytes<Chain<Cursor<Vec<u8,>,>, R,>, Vec<u8,>,>,> {
+    let mut sniffed = Vec::with_capacity(SNIFF_LEN,);
+    (&mut reader).take(SNIFF_LEN as u64,).read_to_end(&mut sniffed,)?;
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(&sniffed, sniffed.len() < SNIFF_LEN,);
+    let encoding = detector.guess(None, true,);
+
+    Ok(encoding_rs_io::DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding,),)
+        .build(Cursor::new(sniffed,).chain(reader,),),)
+}
+
+/// Transcodes `reader` from `encoding` to UTF-8 as it is read.
+pub fn decode_reader<R: Read,>(
+    reader: R,
+    encoding: &'static Encoding,
+) -> DecodeReaderBytes<R, Vec<u8,>,> {
+    encoding_rs_io::DecodeReaderBytesBuilder::new().encoding(Some(encoding,),).build(reader,)
+}
+
+/// The first `n` lines of in-memory data, decoded with the detected encoding. Only the first
+/// 64 KiB are decoded, so very long lines may be cut short.
+pub fn head_lines(data: &[u8], n: usize,) -> Vec<String,> {
+    decode_to_string(&data[..data.len().min(64 * 1024,)],)
+        .lines()
+        .take(n,)
+        .map(|l| l.to_string(),)
+        .collect()
+}
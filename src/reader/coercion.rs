@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::record::NcValue;
+
+/// Words read as `true` unless configured otherwise.
+pub const DEFAULT_TRUE_VALUES: &[&str] = &["true", "yes"];
+/// Words read as `false` unless configured otherwise.
+pub const DEFAULT_FALSE_VALUES: &[&str] = &["false", "no"];
+
+/// The type a field is read as when it is declared (`--type id=string`) instead of inferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+pub enum ValueType {
+    /// The text as written, leading zeros and all.
+    String,
+    /// An integer of any size: `Integer`, or `BigInteger` beyond `i64`.
+    Integer,
+    Float,
+    /// An exact decimal, never rounded through `f64`.
+    Decimal,
+    /// One of the boolean words.
+    Boolean,
+}
+
+impl ValueType {
+    /// Reads `text` as this type, or says why it is not one.
+    pub fn parse(self, coercion: &ValueCoercion, text: &str,) -> Result<NcValue, String,> {
+        let value = match self {
+            ValueType::String => Some(NcValue::String(text.to_string(),),),
+            ValueType::Integer => NcValue::parse_number(text,)
+                .filter(|n| matches!(n, NcValue::Integer(_,) | NcValue::BigInteger(_,)),),
+            ValueType::Float => {
+                text.parse::<f64>().ok().filter(|f| f.is_finite(),).map(NcValue::Float,)
+            },
+            ValueType::Decimal => NcValue::parse_decimal(text,),
+            ValueType::Boolean => coercion.parse_bool(text,).map(NcValue::Boolean,),
+        };
+        value.ok_or_else(|| format!("{:?} is not {}", text, self.description()),)
+    }
+
+    fn description(self,) -> &'static str {
+        match self {
+            ValueType::String => "a string",
+            ValueType::Integer => "an integer",
+            ValueType::Float => "a float",
+            ValueType::Decimal => "a decimal",
+            ValueType::Boolean => "a boolean",
+        }
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        match s.to_ascii_lowercase().as_str() {
+            "string" | "str" | "utf8" => Ok(ValueType::String,),
+            "integer" | "int" | "int64" => Ok(ValueType::Integer,),
+            "float" | "double" | "float64" => Ok(ValueType::Float,),
+            "decimal" => Ok(ValueType::Decimal,),
+            "boolean" | "bool" => Ok(ValueType::Boolean,),
+            other => Err(format!(
+                "unknown type {:?} (expected string, integer, float, decimal or boolean)",
+                other
+            ),),
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        let name = match self {
+            ValueType::String => "string",
+            ValueType::Integer => "integer",
+            ValueType::Float => "float",
+            ValueType::Decimal => "decimal",
+            ValueType::Boolean => "boolean",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How the readers that parse text (CSV fields, XML attributes and text, spreadsheet text
+/// cells) turn it into values: which text is null, which words are booleans, and which fields
+/// keep their text or have a declared type.
+///
+/// Null values match exactly (`NA`, `NULL`, `\N`, `-`); boolean words match in any ASCII case.
+/// Empty CSV fields and empty XML elements are null whatever the null values are. Numbers are
+/// read without losing precision; see [`NcValue::parse_number`].
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct ValueCoercion {
+    pub null_values:      Vec<String,>,
+    pub true_values:      Vec<String,>,
+    pub false_values:     Vec<String,>,
+    /// Keep the text of every field without a declared type instead of reading numbers and
+    /// booleans; null values are still null.
+    pub preserve_strings: bool,
+    /// Declared types, by field name as it appears in records (`zip`, or `@id` for an XML
+    /// attribute).
+    pub types:            HashMap<String, ValueType,>,
+}
+
+impl Default for ValueCoercion {
+    fn default() -> Self {
+        Self {
+            null_values:      Vec::new(),
+            true_values:      DEFAULT_TRUE_VALUES.iter().map(|v| v.to_string(),).collect(),
+            false_values:     DEFAULT_FALSE_VALUES.iter().map(|v| v.to_string(),).collect(),
+            preserve_strings: false,
+            types:            HashMap::new(),
+        }
+    }
+}
+
+impl ValueCoercion {
+    pub fn with_null_values(mut self, values: Vec<String,>,) -> Self {
+        self.null_values = values;
+        self
+    }
+
+    /// Replaces the boolean vocabulary, e.g. `Y`/`N` or `1`/`0`. Fails when a word would be
+    /// both `true` and `false`.
+    pub fn with_booleans(
+        mut self,
+        true_values: Vec<String,>,
+        false_values: Vec<String,>,
+    ) -> Result<Self, String,> {
+        if let Some(word,) =
+            true_values.iter().find(|t| false_values.iter().any(|f| f.eq_ignore_ascii_case(t,),),)
+        {
+            return Err(format!("{:?} cannot be both a true and a false value", word),);
+        }
+        self.true_values = true_values;
+        self.false_values = false_values;
+        Ok(self,)
+    }
+
+    pub fn with_preserve_strings(mut self, preserve_strings: bool,) -> Self {
+        self.preserve_strings = preserve_strings;
+        self
+    }
+
+    pub fn with_type(mut self, field: &str, value_type: ValueType,) -> Self {
+        self.types.insert(field.to_string(), value_type,);
+        self
+    }
+
+    pub fn is_null(&self, text: &str,) -> bool {
+        self.null_values.iter().any(|v| v == text,)
+    }
+
+    pub fn parse_bool(&self, text: &str,) -> Option<bool,> {
+        if self.true_values.iter().any(|v| v.eq_ignore_ascii_case(text,),) {
+            Some(true,)
+        } else if self.false_values.iter().any(|v| v.eq_ignore_ascii_case(text,),) {
+            Some(false,)
+        } else {
+            None
+        }
+    }
+
+    /// The type `field` is read as without inference: its declared type, or `String` when
+    /// strings are preserved.
+    pub fn declared_type(&self, field: &str,) -> Option<ValueType,> {
+        self.types
+            .get(field,)
+            .copied()
+            .or(self.preserve_strings.then_some(ValueType::String,),)
+    }
+
+    /// `Null` or a boolean when `text` is a null value or (unless strings are preserved) a
+    /// boolean word, `None` otherwise. Readers call this before parsing numbers, so `1`/`0`
+    /// flags win over integers.
+    pub fn coerce(&self, text: &str,) -> Option<Value,> {
+        if self.is_null(text,) {
+            Some(Value::Null,)
+        } else if self.preserve_strings {
+            None
+        } else {
+            self.parse_bool(text,).map(Value::Bool,)
+        }
+    }
+
+    /// The value of a text scalar of `field` (an XML attribute or element): null, its declared
+    /// type, a boolean, a number, or the text itself. Fails when the text is not of the
+    /// declared type.
+    pub fn parse_value(&self, field: &str, text: &str,) -> Result<NcValue, String,> {
+        if self.is_null(text,) {
+            return Ok(NcValue::Null,);
+        }
+        if let Some(value_type,) = self.declared_type(field,) {
+            return value_type.parse(self, text,);
+        }
+        if let Some(b,) = self.parse_bool(text,) {
+            return Ok(NcValue::Boolean(b,),);
+        }
+        Ok(NcValue::parse_number(text,).unwrap_or_else(|| NcValue::String(text.to_string(),),),)
+    }
+}
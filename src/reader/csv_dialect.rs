@@ -0,0 +1,256 @@
+use std::io::{self, Chain, Cursor, Read};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes of decoded text the dialect is detected from.
+pub const SNIFF_SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Delimiters tried by [`CsvDialect::sniff`], in order of preference on a tie.
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// Quote characters tried by [`CsvDialect::sniff`], in order of preference on a tie.
+const QUOTES: [u8; 2] = [b'"', b'\''];
+const COMMENT: u8 = b'#';
+const ESCAPE: u8 = b'\\';
+
+/// Dialect settings given explicitly (`--csv-delimiter`, `--csv-quote`, `--no-header`).
+/// `None` leaves a setting to detection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq,)]
+pub struct CsvOptions {
+    pub delimiter:  Option<u8,>,
+    pub quote:      Option<u8,>,
+    pub has_header: Option<bool,>,
+}
+
+/// Parses a delimiter or quote setting: one ASCII character, or `tab` / `\t`.
+pub fn parse_dialect_char(value: &str,) -> Result<u8, String,> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t',),
+        _ => match value.as_bytes() {
+            [c] if c.is_ascii() && *c != b'\n' && *c != b'\r' => Ok(*c,),
+            _ => Err(format!("expected a single ASCII character or `tab`, got {:?}", value),),
+        },
+    }
+}
+
+/// The layout of a CSV file: detected from its first [`SNIFF_SAMPLE_BYTES`], with any
+/// [`CsvOptions`] taking precedence over what was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,)]
+pub struct CsvDialect {
+    pub delimiter:  char,
+    pub quote:      char,
+    /// Escapes a quote inside a quoted field. `None` when quotes are only escaped by doubling.
+    pub escape:     Option<char,>,
+    /// Whether the first row names the columns; without one they are named `column_1`, ...
+    pub has_header: bool,
+    /// Lines starting with this character are skipped, wherever they are in the file.
+    pub comment:    Option<char,>,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter:  ',',
+            quote:      '"',
+            escape:     None,
+            has_header: true,
+            comment:    None,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Detects the dialect of `sample`, the start of `file_path`. `complete` is false when the
+    /// file goes on past the sample, so its last line may be cut off.
+    ///
+    /// The delimiter and quote are the pair that splits the most rows into the same number of
+    /// fields (more than one). A `.tsv` or `.psv` extension decides between equally good
+    /// delimiters. `#` starts comment lines when the first line does, or when skipping the
+    /// lines that start with it splits the other rows more evenly. A header is assumed unless
+    /// the first row has numbers where the columns below it do.
+    pub fn sniff(
+        sample: &str,
+        complete: bool,
+        file_path: &Path,
+        options: &CsvOptions,
+    ) -> CsvDialect {
+        let mut lines = sample.lines().filter(|line| !line.trim().is_empty(),);
+        let comments = match lines.next().map(|line| line.as_bytes()[0] == COMMENT,) {
+            Some(true,) => vec![Some(COMMENT,)],
+            _ if lines.any(|line| line.as_bytes()[0] == COMMENT,) => vec![None, Some(COMMENT,)],
+            _ => vec![None],
+        };
+
+        let preferred = preferred_delimiter(file_path,);
+        let delimiters = match options.delimiter {
+            Some(delimiter,) => vec![delimiter],
+            None => {
+                let mut delimiters = DELIMITERS.to_vec();
+                delimiters.sort_by_key(|d| Some(*d,) != preferred,);
+                delimiters
+            },
+        };
+        let quotes = options.quote.map_or_else(|| QUOTES.to_vec(), |quote| vec![quote],);
+
+        let mut best = (delimiters[0], quotes[0], comments[0],);
+        let mut best_score = None;
+        for &comment in &comments {
+            for &quote in &quotes {
+                for &delimiter in &delimiters {
+                    let counts = field_counts(sample, complete, delimiter, quote, comment,);
+                    let score = (consistency(&counts,), quote_starts(sample, delimiter, quote,),);
+                    if best_score.is_none_or(|best| score > best,) {
+                        best = (delimiter, quote, comment,);
+                        best_score = Some(score,);
+                    }
+                }
+            }
+        }
+        let (delimiter, quote, comment,) = best;
+
+        let escaped = sample.as_bytes().windows(3,).any(|w| {
+            w[0] == ESCAPE && w[1] == quote && !matches!(w[2], b'\n' | b'\r') && w[2] != delimiter
+        },);
+
+        let mut dialect = CsvDialect {
+            delimiter:  char::from(delimiter,),
+            quote:      char::from(quote,),
+            escape:     escaped.then_some(char::from(ESCAPE,),),
+            has_header: true,
+            comment:    comment.map(char::from,),
+        };
+        dialect.has_header = options.has_header.unwrap_or_else(|| {
+            let mut rows = dialect
+                .reader_builder()
+                .has_headers(false,)
+                .flexible(true,)
+                .from_reader(sample.as_bytes(),)
+                .into_records()
+                .map_while(Result::ok,)
+                .collect::<Vec<_,>>();
+            if !complete {
+                rows.pop();
+            }
+            looks_like_header(&rows,)
+        },);
+        dialect
+    }
+
+    /// A `csv::ReaderBuilder` set up for this dialect.
+    pub fn reader_builder(&self,) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .delimiter(self.delimiter as u8,)
+            .quote(self.quote as u8,)
+            .escape(self.escape.map(|c| c as u8,),)
+            .comment(self.comment.map(|c| c as u8,),)
+            .has_headers(self.has_header,);
+        builder
+    }
+}
+
+/// Reads the first [`SNIFF_SAMPLE_BYTES`] of `reader` (decoded text) to detect the dialect, and
+/// returns a reader that yields them again followed by the rest.
+pub fn sniff_reader<R: Read,>(
+    mut reader: R,
+    file_path: &Path,
+    options: &CsvOptions,
+) -> io::Result<(CsvDialect, Chain<Cursor<Vec<u8,>,>, R,>,),> {
+    let mut sample = Vec::with_capacity(SNIFF_SAMPLE_BYTES,);
+    (&mut reader).take(SNIFF_SAMPLE_BYTES as u64,).read_to_end(&mut sample,)?;
+    let complete = sample.len() < SNIFF_SAMPLE_BYTES;
+    let dialect =
+        CsvDialect::sniff(&String::from_utf8_lossy(&sample,), complete, file_path, options,);
+    Ok((dialect, Cursor::new(sample,).chain(reader,),),)
+}
+
+/// Column names for a file without a header row.
+pub fn generated_headers(count: usize,) -> Vec<String,> {
+    (1..=count).map(|i| format!("column_{}", i),).collect()
+}
+
+fn preferred_delimiter(file_path: &Path,) -> Option<u8,> {
+    match file_path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "tsv" => Some(b'\t',),
+        "psv" => Some(b'|',),
+        "csv" => Some(b',',),
+        _ => None,
+    }
+}
+
+/// The number of fields of each row of the sample, leaving out a last row that may be cut off.
+fn field_counts(
+    sample: &str,
+    complete: bool,
+    delimiter: u8,
+    quote: u8,
+    comment: Option<u8,>,
+) -> Vec<usize,> {
+    let mut counts = csv::ReaderBuilder::new()
+        .delimiter(delimiter,)
+        .quote(quote,)
+        .comment(comment,)
+        .has_headers(false,)
+        .flexible(true,)
+        .from_reader(sample.as_bytes(),)
+        .into_records()
+        .map_while(Result::ok,)
+        .map(|record| record.len(),)
+        .collect::<Vec<_,>>();
+    if !complete {
+        counts.pop();
+    }
+    counts
+}
+
+/// How well a delimiter splits the rows: the share of rows (per mille) with the most common
+/// field count, then that count. Zero when it never splits a row.
+fn consistency(counts: &[usize],) -> (usize, usize,) {
+    let mut frequencies = std::collections::HashMap::new();
+    for &count in counts {
+        *frequencies.entry(count,).or_insert(0_usize,) += 1;
+    }
+    match frequencies.into_iter().max_by_key(|&(count, frequency,)| (frequency, count,),) {
+        Some((count, frequency,),) if count > 1 => (frequency * 1000 / counts.len(), count,),
+        _ => (0, 0,),
+    }
+}
+
+/// How often `quote` opens a field, which tells quote characters apart when both split the
+/// rows equally well.
+fn quote_starts(sample: &str, delimiter: u8, quote: u8,) -> usize {
+    let bytes = sample.as_bytes();
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(i, &b,)| {
+            b == quote
+                && (i == 0 || matches!(bytes[i - 1], b'\n' | b'\r') || bytes[i - 1] == delimiter)
+        },)
+        .count()
+}
+
+/// Whether the first row reads as a header: each column whose values below are all numbers
+/// votes for a header when its first value is not a number, and against one when it is.
+fn looks_like_header(rows: &[csv::StringRecord],) -> bool {
+    let Some((first, rest,),) = rows.split_first() else {
+        return true;
+    };
+    if rest.is_empty() {
+        return true;
+    }
+    let is_number = |value: &str| value.trim().parse::<f64>().is_ok();
+    let mut votes = 0_i32;
+    for (i, name,) in first.iter().enumerate() {
+        let values = rest
+            .iter()
+            .filter_map(|row| row.get(i,),)
+            .filter(|value| !value.is_empty(),)
+            .collect::<Vec<&str,>>();
+        if values.is_empty() || !values.iter().all(|value| is_number(value,),) {
+            continue;
+        }
+        votes += if is_number(name,) { -1 } else { 1 };
+    }
+    votes >= 0
+}
@@ -1,90 +1,371 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::{self, BufRead}; // Added File and BufRead
+use std::io::{self, BufRead, Read}; // Added File and BufRead
 use std::path::Path;
 
+use arrow::datatypes::SchemaRef;
+use bytes::{Buf, Bytes};
 use nc_schema::{DataType, merge_nc_types};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
-use crate::error::DataReaderError;
-use crate::nc_reader_result::RecordStream;
+use crate::error::{DataReaderError, ErrorPosition};
+use crate::nc_reader_result::{RecordStream, TypedRecordStream};
+use crate::reader::async_stream::{AsyncRecordStream, DEFAULT_CHANNEL_CAPACITY, into_async_stream};
+use crate::reader::csv_dialect::{CsvDialect, CsvOptions, generated_headers, sniff_reader};
+use crate::reader::error_policy::{RejectReport, apply_error_policy};
+use crate::reader::limits::{apply_limits, nc_depth};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::{
+    RecordBatchStream, arrow_schema_from_nc_types, json_records_to_batches,
+};
+use crate::reader::sample::RowSampler;
+use crate::reader::type_inference::FormatTracker;
+use crate::record::NcValue;
 
 #[derive(Debug, Serialize, Deserialize,)]
 pub struct CsvData {
-    pub file_size:       u64,
-    pub num_rows:        u64,
-    pub column_headers:  Vec<String,>,
-    pub nc_rows:         Vec<serde_json::Value,>,
-    pub total_size:      u64,
-    pub first_lines:     Option<Vec<String,>,>,
-    pub inferred_schema: Option<HashMap<String, DataType,>,>,
+    pub file_size:        u64,
+    pub num_rows:         u64,
+    pub column_headers:   Vec<String,>,
+    /// Every row, or a sample of them when `sampled` is set.
+    pub nc_rows:          Vec<serde_json::Value,>,
+    /// `nc_rows` holds only some of the `num_rows` rows: the first ones, then a random
+    /// selection of the rest, in file order.
+    #[serde(default)]
+    pub sampled:          bool,
+    pub total_size:       u64,
+    pub first_lines:      Option<Vec<String,>,>,
+    pub inferred_schema:  Option<HashMap<String, DataType,>,>,
+    /// The format of each date or timestamp column (`%d.%m.%Y`), by column name. These columns
+    /// are text in `inferred_schema`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inferred_formats: Option<HashMap<String, String,>,>,
+    /// The delimiter, quoting and header detected for the file, after any overrides.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect:          Option<CsvDialect,>,
+    /// Rows skipped under a lenient error policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejects:          Option<RejectReport,>,
 }
 
 pub fn read_csv_stream(
     file_path: &Path,
 ) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    read_csv_stream_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_csv_stream` with the dialect overrides and type inference of `config`.
+pub fn read_csv_stream_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    let (_dialect, headers, stream,) = open_csv_file(file_path, config,)?;
+    Ok((headers, stream,),)
+}
+
+/// Streams the rows of a CSV file as `NcValue` objects, keeping exact decimals and integers
+/// beyond `i64` that `read_csv_stream` renders as strings.
+pub fn read_csv_typed_stream(
+    file_path: &Path,
+) -> Result<(Vec<String,>, TypedRecordStream,), DataReaderError,> {
+    read_csv_typed_stream_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_csv_typed_stream` with the dialect overrides and type inference of `config`.
+pub fn read_csv_typed_stream_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(Vec<String,>, TypedRecordStream,), DataReaderError,> {
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
+    let (_dialect, headers, stream,) = open_csv_typed_stream(file, file_path, config,)?;
+    Ok((headers, stream,),)
+}
 
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-        DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        }
+/// `read_csv_typed_stream_with_config` for any reader. `file_path` is only used to label errors
+/// and to prefer the delimiter its extension names.
+pub fn read_csv_typed_stream_from_reader_with_config<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(Vec<String,>, TypedRecordStream,), DataReaderError,> {
+    let (_dialect, headers, stream,) = open_csv_typed_stream(reader, file_path, config,)?;
+    Ok((headers, stream,),)
+}
+
+/// Async variant of `read_csv_stream`; parsing runs on tokio's blocking pool.
+pub async fn read_csv_stream_async(
+    file_path: &Path,
+) -> Result<(Vec<String,>, AsyncRecordStream,), DataReaderError,> {
+    let path = file_path.to_path_buf();
+    let (headers, records,) = tokio::task::spawn_blocking(move || read_csv_stream(&path,),)
+        .await
+        .map_err(|e| DataReaderError::InternalError(format!("Reader task failed: {}", e),),)??;
+    Ok((headers, into_async_stream(records, DEFAULT_CHANNEL_CAPACITY,),),)
+}
+
+/// Streams CSV records from in-memory bytes. Use `Bytes::copy_from_slice` for borrowed slices.
+pub fn read_csv_stream_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    read_csv_stream_from_bytes_with_config(data, file_path, &ReadConfig::default(),)
+}
+
+/// `read_csv_stream_from_bytes` with the dialect overrides and type inference of `config`.
+pub fn read_csv_stream_from_bytes_with_config(
+    data: Bytes,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    let (_dialect, headers, stream,) = open_csv_stream(data.reader(), file_path, config,)?;
+    Ok((headers, stream,),)
+}
+
+/// Streams CSV records from any reader. `file_path` is only used to label errors and to prefer
+/// the delimiter its extension names (`.tsv`, `.psv`).
+pub fn read_csv_stream_from_reader<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    read_csv_stream_from_reader_with_config(reader, file_path, &ReadConfig::default(),)
+}
+
+/// `read_csv_stream_from_reader` with the dialect overrides and type inference of `config`.
+pub fn read_csv_stream_from_reader_with_config<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(Vec<String,>, RecordStream,), DataReaderError,> {
+    let (_dialect, headers, stream,) = open_csv_stream(reader, file_path, config,)?;
+    Ok((headers, stream,),)
+}
+
+fn open_csv_file(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(CsvDialect, Vec<String,>, RecordStream,), DataReaderError,> {
+    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
     },)?;
-    let mut rdr = csv::Reader::from_reader(decoder,);
+    open_csv_stream(file, file_path, config,)
+}
 
-    let headers = rdr
-        .headers()
-        .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
-        },)?
-        .iter()
-        .map(|s| s.to_string(),)
-        .collect::<Vec<String,>>();
+/// Decodes `reader` and detects its dialect from the start of the text. The returned reader
+/// replays the sample the dialect was detected from.
+fn sniff_csv<R: Read + Send,>(
+    reader: R,
+    file_path: &Path,
+    options: &CsvOptions,
+) -> Result<(CsvDialect, impl Read + Send + use<R,>,), DataReaderError,> {
+    let read_error = |e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    };
+    let decoder = crate::reader::charset::get_decoded_reader_from(reader,).map_err(read_error,)?;
+    sniff_reader(decoder, file_path, options,).map_err(read_error,)
+}
+
+/// The column names: the header row, or `column_1`, ... named after the first row when the
+/// dialect has no header.
+fn csv_headers<R: Read,>(
+    rdr: &mut csv::Reader<R,>,
+    dialect: &CsvDialect,
+    file_path: &Path,
+) -> Result<Vec<String,>, DataReaderError,> {
+    let first = rdr.headers().map_err(|e| csv_parse_error(file_path, e,),)?;
+    Ok(if dialect.has_header {
+        first.iter().map(|s| s.to_string(),).collect()
+    } else {
+        generated_headers(first.len(),)
+    },)
+}
 
+fn open_csv_stream<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(CsvDialect, Vec<String,>, RecordStream,), DataReaderError,> {
+    let (dialect, headers, rows,) = open_csv_rows(reader, file_path, config,)?;
     let headers_clone = headers.clone();
-    let path_clone = file_path.to_path_buf();
+    let stream = rows.map(move |row| {
+        let row_map = headers_clone
+            .iter()
+            .cloned()
+            .zip(row?.into_iter().map(|value| value.to_json(),),)
+            .collect::<serde_json::Map<_, _,>>();
+        Ok(serde_json::Value::Object(row_map,),)
+    },);
+    Ok((dialect, headers, Box::new(stream,),),)
+}
 
-    let stream = rdr.into_records().map(move |result| {
-        let record = result.map_err(|e| DataReaderError::ParseError {
-            path:   path_clone.clone(),
-            source: Box::new(e,),
-        },)?;
+fn open_csv_typed_stream<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<(CsvDialect, Vec<String,>, TypedRecordStream,), DataReaderError,> {
+    let (dialect, headers, rows,) = open_csv_rows(reader, file_path, config,)?;
+    let headers_clone = headers.clone();
+    let stream = rows.map(move |row| {
+        let fields = headers_clone.iter().cloned().zip(row?,).collect::<BTreeMap<_, _,>>();
+        Ok(NcValue::Object(fields,),)
+    },);
+    Ok((dialect, headers, Box::new(stream,),),)
+}
 
-        let mut row_map = serde_json::Map::new();
-        for (i, header,) in headers_clone.iter().enumerate() {
-            let field_val = if let Some(field,) = record.get(i,) {
-                if field.is_empty() {
-                    serde_json::Value::Null
-                } else if let Ok(i_val,) = field.parse::<i64>() {
-                    serde_json::Value::Number(i_val.into(),)
-                } else if let Ok(f_val,) = field.parse::<f64>() {
-                    serde_json::Value::Number(
-                        serde_json::Number::from_f64(f_val,)
-                            .unwrap_or(serde_json::Number::from(0,),),
-                    )
-                } else if let Ok(b_val,) = field.parse::<bool>() {
-                    serde_json::Value::Bool(b_val,)
-                } else {
-                    serde_json::Value::String(field.to_string(),)
-                }
-            } else {
-                serde_json::Value::Null
-            };
-            row_map.insert(header.clone(), field_val,);
+/// The values of one row, in header order.
+type CsvRow = Result<Vec<NcValue,>, DataReaderError,>;
+
+/// Parses each row into one value per header.
+fn open_csv_rows<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<
+    (CsvDialect, Vec<String,>, impl Iterator<Item = CsvRow,> + Send + use<R,>,),
+    DataReaderError,
+> {
+    let (dialect, text,) = sniff_csv(reader, file_path, &config.csv,)?;
+    // Rows with the wrong number of fields are reported below rather than by the csv crate, so
+    // the error can carry the row's text.
+    let mut rdr = dialect.reader_builder().flexible(true,).from_reader(text,);
+    let headers = csv_headers(&mut rdr, &dialect, file_path,)?;
+
+    let headers_clone = headers.clone();
+    let path_clone = file_path.to_path_buf();
+    let delimiter = dialect.delimiter;
+    let inference = config.inference.clone();
+    let coercion = config.coercion.clone();
+
+    let rows = rdr.into_records().map(move |result| {
+        let record = result.map_err(|e| csv_parse_error(&path_clone, e,),)?;
+        if record.len() != headers_clone.len() {
+            return Err(csv_length_error(&path_clone, &record, headers_clone.len(), delimiter,),);
         }
-        Ok(serde_json::Value::Object(row_map,),)
+
+        headers_clone
+            .iter()
+            .zip(record.iter(),)
+            .map(|(header, field,)| {
+                inference.parse_field(&coercion, header, field,).map_err(|e| {
+                    let message = format!("column {}: {}", header, e);
+                    csv_record_error(&path_clone, &record, delimiter, message,)
+                },)
+            },)
+            .collect()
+    },);
+
+    Ok((dialect, headers, rows,),)
+}
+
+/// Wraps a CSV error, keeping the line, byte offset and record index the parser reports. The
+/// record index counts the header row.
+fn csv_parse_error(file_path: &Path, e: csv::Error,) -> DataReaderError {
+    let position = e.position().map(|pos| {
+        Box::new(ErrorPosition {
+            line: Some(pos.line(),),
+            byte_offset: Some(pos.byte(),),
+            record_index: Some(pos.record(),),
+            ..Default::default()
+        },)
     },);
+    DataReaderError::ParseError {
+        path: file_path.to_path_buf(),
+        source: Box::new(e,),
+        position,
+    }
+}
 
-    Ok((headers, Box::new(stream,),),)
+fn csv_length_error(
+    file_path: &Path,
+    record: &csv::StringRecord,
+    expected: usize,
+    delimiter: char,
+) -> DataReaderError {
+    let message =
+        format!("found record with {} fields, but the header has {}", record.len(), expected);
+    csv_record_error(file_path, record, delimiter, message,)
+}
+
+/// An error about one record, carrying its position and text.
+fn csv_record_error(
+    file_path: &Path,
+    record: &csv::StringRecord,
+    delimiter: char,
+    message: String,
+) -> DataReaderError {
+    let raw = record.iter().collect::<Vec<&str,>>().join(&delimiter.to_string(),);
+    let position = match record.position() {
+        Some(pos,) => ErrorPosition {
+            line: Some(pos.line(),),
+            byte_offset: Some(pos.byte(),),
+            record_index: Some(pos.record(),),
+            ..Default::default()
+        },
+        None => ErrorPosition::default(),
+    };
+    DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(io::Error::new(io::ErrorKind::InvalidData, message,),),
+        position: Some(Box::new(position.with_raw(raw,),),),
+    }
+}
+
+/// Reads the file as Arrow record batches. A first pass infers the column types the same way
+/// `read_csv_data` does; a second pass decodes the rows into batches of `batch_size`.
+pub fn read_csv_batches(
+    file_path: &Path,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    read_csv_batches_with_config(file_path, batch_size, &ReadConfig::default(),)
+}
+
+/// `read_csv_batches` with the dialect overrides and type inference of `config`. Decimal and
+/// big-integer columns are numeric, like their type in `inferred_schema`.
+pub fn read_csv_batches_with_config(
+    file_path: &Path,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    let (headers, stream,) = read_csv_typed_stream_with_config(file_path, config,)?;
+    let mut types: HashMap<String, DataType,> = HashMap::new();
+    for record in stream {
+        if let NcValue::Object(fields,) = record? {
+            for (header, value,) in &fields {
+                let current_type = value.data_type();
+                types
+                    .entry(header.clone(),)
+                    .and_modify(|t| *t = merge_nc_types(t.clone(), current_type.clone(),),)
+                    .or_insert(current_type,);
+            }
+        }
+    }
+    let schema = arrow_schema_from_nc_types(&headers, &types,);
+
+    let (_, _, records,) = open_csv_file(file_path, config,)?;
+    let batches = json_records_to_batches(schema.clone(), records, batch_size, file_path,)?;
+    Ok((schema, batches,),)
 }
 
 pub fn read_csv_data(file_path: &Path, head: Option<usize,>,) -> Result<CsvData, DataReaderError,> {
+    read_csv_data_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_csv_data` with an explicit `ReadConfig`. Malformed rows are handled according to
+/// `config.error_policy`; skipped rows are listed in `CsvData::rejects`. Reading stops with
+/// `LimitExceeded` once `config.limits` is exceeded.
+///
+/// Rows are streamed: every row is counted and typed, but only the sample of `config.sample`
+/// is kept in `CsvData::nc_rows`, so memory stays bounded however large the file is.
+pub fn read_csv_data_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<CsvData, DataReaderError,> {
     let num_lines_to_extract = head.unwrap_or(0,); // Default to 0 if None
 
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -124,88 +405,110 @@ pub fn read_csv_data(file_path: &Path, head: Option<usize,>,) -> Result<CsvData,
         None
     };
 
-    let (headers, stream,) = read_csv_stream(file_path,)?;
+    let (dialect, headers, stream,) = open_csv_typed_stream(file, file_path, config,)?;
+    summarize_csv(file_path, file_size, first_lines, dialect, headers, stream, config,)
+}
 
-    let mut records: Vec<serde_json::Value,> = Vec::new();
+/// `read_csv_data_with_config` for CSV held in memory (stdin, archive entries). `file_path` is
+/// only used to label errors and rejects and to prefer the delimiter its extension names.
+pub fn read_csv_data_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<CsvData, DataReaderError,> {
+    let size = data.len() as u64;
+    let first_lines = head
+        .filter(|n| *n > 0,)
+        .map(|n| crate::reader::charset::head_lines(&data, n,),);
+    let (dialect, headers, stream,) = open_csv_typed_stream(data.reader(), file_path, config,)?;
+    summarize_csv(file_path, size, first_lines, dialect, headers, stream, config,)
+}
+
+/// Counts, types and samples the rows of `stream` into a `CsvData`. Each column's type is the
+/// type of the values the rows were parsed into.
+fn summarize_csv(
+    file_path: &Path,
+    file_size: u64,
+    first_lines: Option<Vec<String,>,>,
+    dialect: CsvDialect,
+    headers: Vec<String,>,
+    stream: TypedRecordStream,
+    config: &ReadConfig,
+) -> Result<CsvData, DataReaderError,> {
+    let headers = config.filter.output_columns().unwrap_or(headers,);
+    let stream =
+        apply_error_policy(stream, file_path, config.error_policy, config.rejects.clone(),);
+    let stream = apply_limits(stream, config.budget(file_path,), nc_depth,);
+    let stream = config.filter.apply_typed(stream,);
+
+    let mut sample = RowSampler::new(config.sample,);
     let mut schema_map: HashMap<String, DataType,> = HashMap::new();
+    let mut formats = FormatTracker::default();
 
     for result in stream {
         let row = result?;
 
-        if let serde_json::Value::Object(ref obj,) = row {
-            for (header, value,) in obj {
-                let current_type = match value {
-                    serde_json::Value::Null => DataType::Null,
-                    serde_json::Value::Bool(_,) => DataType::Boolean,
-                    serde_json::Value::Number(n,) => {
-                        if n.is_i64() {
-                            DataType::Integer
-                        } else {
-                            DataType::Float
-                        }
-                    },
-                    serde_json::Value::String(_,) => DataType::String,
-                    _ => DataType::Unknown,
-                };
+        if let NcValue::Object(ref fields,) = row {
+            for (header, value,) in fields {
+                let current_type = value.data_type();
                 schema_map
                     .entry(header.clone(),)
                     .and_modify(|t| *t = merge_nc_types(t.clone(), current_type.clone(),),)
                     .or_insert(current_type,);
+                formats.observe(&config.inference, header, &value.to_json(),);
             }
         }
 
-        records.push(row,);
+        sample.push(row,);
     }
 
-    let num_rows = records.len() as u64;
+    let num_rows = sample.seen();
+    let (records, complete,) = sample.finish();
+    let records = records.iter().map(NcValue::to_json,).collect();
+    let formats = formats.formats();
 
     Ok(CsvData {
         file_size,
         num_rows,
         column_headers: headers,
         nc_rows: records,
+        sampled: !complete,
         total_size: file_size, // Now using actual file_size
         first_lines,
         inferred_schema: Some(schema_map,),
+        inferred_formats: (!formats.is_empty()).then_some(formats,),
+        dialect: Some(dialect,),
+        rejects: config.reject_report(file_path,),
     },)
 }
 
 pub fn get_csv_raw_content(
+    file_path: &Path,
+    head: Option<usize,>,
+) -> Result<String, DataReaderError,> {
+    get_csv_raw_content_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `get_csv_raw_content` with the dialect overrides of `config`.
+pub fn get_csv_raw_content_with_config(
     file_path: &Path,
     _head: Option<usize,>,
+    config: &ReadConfig,
 ) -> Result<String, DataReaderError,> {
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
 
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-        DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        }
-    },)?;
-    let mut rdr = csv::ReaderBuilder::new()
-        .has_headers(true,) // Assuming CSVs always have headers for this mode
-        .from_reader(decoder,);
-
-    let headers = rdr
-        .headers()
-        .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
-        },)?
-        .iter()
-        .map(|s| s.to_string(),)
-        .collect::<Vec<String,>>();
+    let (dialect, text,) = sniff_csv(file, file_path, &config.csv,)?;
+    let mut rdr = dialect.reader_builder().from_reader(text,);
+    let headers = csv_headers(&mut rdr, &dialect, file_path,)?;
 
     let mut records: Vec<serde_json::Value,> = Vec::new();
 
     for result in rdr.into_records() {
-        let record = result.map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
-        },)?;
+        let record = result.map_err(|e| csv_parse_error(file_path, e,),)?;
         let mut row_map = serde_json::Map::new();
         for (i, header,) in headers.iter().enumerate() {
             if let Some(value,) = record.get(i,) {
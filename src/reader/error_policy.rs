@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DataReaderError, ErrorPosition};
+
+/// What a record stream does when a record fails to parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq,)]
+pub enum ErrorPolicy {
+    /// Stop at the first malformed record and return its error.
+    #[default]
+    FailFast,
+    /// Skip every malformed record.
+    Skip,
+    /// Skip up to this many malformed records per file, then fail.
+    SkipWithLimit(usize,),
+}
+
+impl FromStr for ErrorPolicy {
+    type Err = String;
+
+    /// Parses `fail-fast`, `skip` or `skip-with-limit:N`.
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        match s {
+            "fail-fast" => Ok(ErrorPolicy::FailFast,),
+            "skip" => Ok(ErrorPolicy::Skip,),
+            _ => match s.strip_prefix("skip-with-limit:",) {
+                Some(limit,) => limit
+                    .parse()
+                    .map(ErrorPolicy::SkipWithLimit,)
+                    .map_err(|_| format!("Invalid reject limit: {}", limit),),
+                None => Err(format!(
+                    "Unknown error policy: {} (expected fail-fast, skip or skip-with-limit:N)",
+                    s
+                ),),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            ErrorPolicy::FailFast => write!(f, "fail-fast"),
+            ErrorPolicy::Skip => write!(f, "skip"),
+            ErrorPolicy::SkipWithLimit(limit,) => write!(f, "skip-with-limit:{}", limit),
+        }
+    }
+}
+
+/// A record that was skipped under a lenient [`ErrorPolicy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize,)]
+pub struct RejectedRecord {
+    pub path:     PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<ErrorPosition,>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw:      Option<String,>,
+    pub error:    String,
+}
+
+/// The records skipped while reading, in the order they were met. `skipped` counts every
+/// reject; `records` keeps only the first [`KEPT_REJECTS`] of them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize,)]
+pub struct RejectReport {
+    pub skipped: usize,
+    pub records: Vec<RejectedRecord,>,
+}
+
+/// How many rejected records a [`RejectCollector`] keeps in memory. Later ones are only counted,
+/// and written to its sink if it has one.
+pub const KEPT_REJECTS: usize = 1000;
+
+#[derive(Default,)]
+struct RejectState {
+    report:     RejectReport,
+    per_file:   HashMap<PathBuf, usize,>,
+    sink:       Option<Box<dyn Write + Send,>,>,
+    sink_error: Option<io::Error,>,
+}
+
+impl fmt::Debug for RejectState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        f.debug_struct("RejectState",)
+            .field("report", &self.report,)
+            .field("per_file", &self.per_file,)
+            .field("sink", &self.sink.is_some(),)
+            .finish()
+    }
+}
+
+/// A shared, cloneable sink for rejected records. Every clone appends to the same report, so
+/// one collector can gather the rejects of all files read with a `ReadConfig`. Rejects are
+/// written to the sink set with `stream_to` as they happen; only a count and the first
+/// [`KEPT_REJECTS`] records are kept in memory.
+#[derive(Debug, Clone, Default,)]
+pub struct RejectCollector {
+    state: Arc<Mutex<RejectState,>,>,
+}
+
+impl RejectCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self,) -> MutexGuard<'_, RejectState,> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner(),)
+    }
+
+    /// Writes every reject pushed from now on to `sink` as a JSON line, for all clones of this
+    /// collector.
+    pub fn stream_to(&self, sink: Box<dyn Write + Send,>,) {
+        self.lock().sink = Some(sink,);
+    }
+
+    pub fn push(&self, record: RejectedRecord,) {
+        let mut state = self.lock();
+        let state = &mut *state;
+        state.report.skipped += 1;
+        *state.per_file.entry(record.path.clone(),).or_default() += 1;
+        if let Some(sink,) = &mut state.sink
+            && state.sink_error.is_none()
+        {
+            let written = serde_json::to_writer(&mut *sink, &record,)
+                .map_err(io::Error::from,)
+                .and_then(|()| sink.write_all(b"\n",),);
+            state.sink_error = written.err();
+        }
+        if state.report.records.len() < KEPT_REJECTS {
+            state.report.records.push(record,);
+        }
+    }
+
+    /// Flushes the sink, returning the first error met while writing to it.
+    pub fn flush(&self,) -> io::Result<(),> {
+        let mut state = self.lock();
+        if let Some(error,) = state.sink_error.take() {
+            return Err(error,);
+        }
+        match &mut state.sink {
+            Some(sink,) => sink.flush(),
+            None => Ok((),),
+        }
+    }
+
+    /// A snapshot of the rejects collected so far.
+    pub fn report(&self,) -> RejectReport {
+        self.lock().report.clone()
+    }
+
+    /// A snapshot of the rejects of one file.
+    pub fn report_for(&self, path: &Path,) -> RejectReport {
+        let state = self.lock();
+        RejectReport {
+            skipped: state.per_file.get(path,).copied().unwrap_or(0,),
+            records: state.report.records.iter().filter(|r| r.path == path,).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self,) -> bool {
+        self.lock().report.skipped == 0
+    }
+}
+
+/// Applies `policy` to a record stream. Under `FailFast` the stream is returned unchanged;
+/// otherwise parse errors are recorded in `rejects` and skipped. Other errors (I/O failures,
+/// for instance) always end the stream, since the following records cannot be trusted.
+pub fn apply_error_policy<T: Send + 'static,>(
+    stream: Box<dyn Iterator<Item = Result<T, DataReaderError,>,> + Send,>,
+    file_path: &Path,
+    policy: ErrorPolicy,
+    rejects: RejectCollector,
+) -> Box<dyn Iterator<Item = Result<T, DataReaderError,>,> + Send,> {
+    if policy == ErrorPolicy::FailFast {
+        return stream;
+    }
+
+    let path = file_path.to_path_buf();
+    let mut stream = stream;
+    let mut skipped = 0usize;
+    let mut failed = false;
+    Box::new(std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        loop {
+            let error = match stream.next()? {
+                Ok(record,) => return Some(Ok(record,),),
+                Err(error @ DataReaderError::ParseError { .. },) => error,
+                Err(error,) => {
+                    failed = true;
+                    return Some(Err(error,),);
+                },
+            };
+
+            if let ErrorPolicy::SkipWithLimit(limit,) = policy
+                && skipped >= limit
+            {
+                failed = true;
+                return Some(Err(DataReaderError::RejectLimitExceeded {
+                    path: path.clone(),
+                    limit,
+                    source: Box::new(error,),
+                },),);
+            }
+            skipped += 1;
+            let position = error.position().cloned();
+            rejects.push(RejectedRecord {
+                path:     path.clone(),
+                raw:      position.as_ref().and_then(|p| p.raw.clone(),),
+                position: position.map(|p| ErrorPosition {
+                    raw: None,
+                    ..p
+                },),
+                error:    error.to_string(),
+            },);
+        }
+    },),)
+}
+
+/// Ends a stream after its first error. Used by readers that cannot resynchronize after a
+/// syntax error, so a lenient policy does not keep re-reading the same broken input.
+pub(crate) fn fuse_after_error<T,>(
+    stream: impl Iterator<Item = Result<T, DataReaderError,>,>,
+) -> impl Iterator<Item = Result<T, DataReaderError,>,> {
+    let mut failed = false;
+    stream.take_while(move |result| {
+        let keep = !failed;
+        failed |= result.is_err();
+        keep
+    },)
+}
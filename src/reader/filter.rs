@@ -0,0 +1,759 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::{RecordStream, TypedRecordStream};
+use crate::record::NcValue;
+
+/// One step of a `FieldPath`: an object key or an array index.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub enum PathSegment {
+    Key(String,),
+    Index(usize,),
+}
+
+/// A path to a nested field such as `a.b[0]`. Keys that are not plain identifiers (spaces,
+/// dots, keywords) are written in backticks: `` `first name` ``.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct FieldPath {
+    segments: Vec<PathSegment,>,
+    source:   String,
+}
+
+impl FieldPath {
+    pub fn segments(&self,) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// The value at this path, or `None` if any step is missing.
+    pub fn get<'a,>(&self, record: &'a Value,) -> Option<&'a Value,> {
+        self.segments.iter().try_fold(record, |value, segment| match segment {
+            PathSegment::Key(key,) => value.get(key.as_str(),),
+            PathSegment::Index(index,) => value.get(*index,),
+        },)
+    }
+
+    /// `get` over a typed record.
+    pub fn get_nc<'a,>(&self, record: &'a NcValue,) -> Option<&'a NcValue,> {
+        self.segments.iter().try_fold(record, |value, segment| match (segment, value,) {
+            (PathSegment::Key(key,), NcValue::Object(fields,),) => fields.get(key,),
+            (PathSegment::Index(index,), NcValue::Array(items,),) => items.get(*index,),
+            _ => None,
+        },)
+    }
+
+    /// The top-level key this path starts with.
+    fn root(&self,) -> Option<&str,> {
+        match self.segments.first() {
+            Some(PathSegment::Key(key,),) => Some(key,),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for FieldPath {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        let mut parser = Parser::new(s,)?;
+        let path = parser.path()?;
+        parser.expect_end()?;
+        Ok(path,)
+    }
+}
+
+static NULL: Value = Value::Null;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone,)]
+enum Operand {
+    Field(FieldPath,),
+    Literal(Value,),
+}
+
+impl Operand {
+    fn eval<'a,>(&'a self, record: &'a Value,) -> &'a Value {
+        match self {
+            Operand::Field(path,) => path.get(record,).unwrap_or(&NULL,),
+            Operand::Literal(value,) => value,
+        }
+    }
+
+    fn field(&self,) -> Option<&FieldPath,> {
+        match self {
+            Operand::Field(path,) => Some(path,),
+            Operand::Literal(_,) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone,)]
+enum Expr {
+    Or(Box<Expr,>, Box<Expr,>,),
+    And(Box<Expr,>, Box<Expr,>,),
+    Not(Box<Expr,>,),
+    Compare(Operand, CompareOp, Operand,),
+    IsNull(Operand,),
+    Matches(Operand, Regex,),
+    /// A bare operand: true only for the boolean `true`.
+    Truthy(Operand,),
+}
+
+impl Expr {
+    fn eval(&self, record: &Value,) -> bool {
+        match self {
+            Expr::Or(left, right,) => left.eval(record,) || right.eval(record,),
+            Expr::And(left, right,) => left.eval(record,) && right.eval(record,),
+            Expr::Not(inner,) => !inner.eval(record,),
+            Expr::Compare(left, op, right,) => {
+                compare(left.eval(record,), *op, right.eval(record,),)
+            },
+            Expr::IsNull(operand,) => operand.eval(record,).is_null(),
+            Expr::Matches(operand, regex,) => {
+                operand.eval(record,).as_str().is_some_and(|s| regex.is_match(s,),)
+            },
+            Expr::Truthy(operand,) => operand.eval(record,) == &Value::Bool(true,),
+        }
+    }
+
+    fn fields<'a,>(&'a self, fields: &mut Vec<&'a FieldPath,>,) {
+        match self {
+            Expr::Or(left, right,) | Expr::And(left, right,) => {
+                left.fields(fields,);
+                right.fields(fields,);
+            },
+            Expr::Not(inner,) => inner.fields(fields,),
+            Expr::Compare(left, _, right,) => {
+                fields.extend(left.field(),);
+                fields.extend(right.field(),);
+            },
+            Expr::IsNull(value,) | Expr::Matches(value, _,) | Expr::Truthy(value,) => {
+                fields.extend(value.field(),)
+            },
+        }
+    }
+}
+
+/// Equality treats missing fields as null and compares numbers by value, so `1 == 1.0`.
+/// Ordering is only defined between two numbers or two strings; anything else is false.
+/// Numbers compare exactly, including integers beyond the 53 bits of an `f64`, and a string
+/// holding a plain decimal number (how decimals and big integers are rendered) compares as that
+/// number against a number.
+fn compare(left: &Value, op: CompareOp, right: &Value,) -> bool {
+    let ordering = match (left, right,) {
+        (Value::String(a,), Value::String(b,),) => Some(a.cmp(b,),),
+        (Value::Number(_,), Value::Number(_,) | Value::String(_,),)
+        | (Value::String(_,), Value::Number(_,),) => compare_numbers(left, right,),
+        _ => None,
+    };
+    let equal = ordering.map_or_else(|| left == right, |o| o == Ordering::Equal,);
+    match op {
+        CompareOp::Eq => equal,
+        CompareOp::Ne => !equal,
+        CompareOp::Lt => ordering == Some(Ordering::Less,),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater,),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// Orders two numbers exactly, as `unscaled * 10^-scale`. Floats are taken as the shortest
+/// decimal that reads back as them, which is how they are written. Falls back to `f64` when a
+/// value has more digits than an `i128` holds.
+fn compare_numbers(left: &Value, right: &Value,) -> Option<Ordering,> {
+    if let Some((a, a_scale,),) = exact_number(left,)
+        && let Some((b, b_scale,),) = exact_number(right,)
+    {
+        let scale = a_scale.max(b_scale,);
+        let align = |unscaled: i128, from: i32| {
+            10i128.checked_pow((scale - from) as u32,).and_then(|f| unscaled.checked_mul(f,),)
+        };
+        if let (Some(a,), Some(b,),) = (align(a, a_scale,), align(b, b_scale,),) {
+            return Some(a.cmp(&b,),);
+        }
+    }
+    let float = |value: &Value| match value {
+        Value::Number(n,) => n.as_f64(),
+        Value::String(s,) => s.parse::<f64>().ok().filter(|f| f.is_finite(),),
+        _ => None,
+    };
+    float(left,)?.partial_cmp(&float(right,)?,)
+}
+
+/// The exact value of a number, or of a string holding a plain decimal number, as `unscaled`
+/// and `scale`.
+fn exact_number(value: &Value,) -> Option<(i128, i32,),> {
+    let text = match value {
+        Value::Number(n,) => {
+            if let Some(i,) = n.as_i64() {
+                return Some((i as i128, 0,),);
+            }
+            if let Some(u,) = n.as_u64() {
+                return Some((u as i128, 0,),);
+            }
+            n.to_string()
+        },
+        Value::String(s,) => s.clone(),
+        _ => return None,
+    };
+    match NcValue::parse_decimal(&text,)? {
+        NcValue::Decimal {
+            unscaled,
+            scale,
+        } => Some((unscaled, scale as i32,),),
+        _ => None,
+    }
+}
+
+/// A `--where` expression, for example
+/// `status == "active" and (age >= 18 or guardian is not null) and email =~ "@example\.com$"`.
+///
+/// Supported: `==` (or `=`), `!=`, `<`, `<=`, `>`, `>=`; `and`/`&&`, `or`/`||`, `not`/`!`;
+/// `is null`, `is not null`; `=~` (or `matches`) with a regex; string, number, `true`, `false`
+/// and `null` literals; and field paths such as `a.b[0]`.
+#[derive(Debug, Clone,)]
+pub struct Predicate {
+    expr:   Expr,
+    source: String,
+}
+
+impl Predicate {
+    pub fn matches(&self, record: &Value,) -> bool {
+        self.expr.eval(record,)
+    }
+
+    /// The field paths the expression reads.
+    pub fn fields(&self,) -> Vec<&FieldPath,> {
+        let mut fields = Vec::new();
+        self.expr.fields(&mut fields,);
+        fields
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        let mut parser = Parser::new(s,)?;
+        let expr = parser.or_expr()?;
+        parser.expect_end()?;
+        Ok(Predicate {
+            expr,
+            source: s.trim().to_string(),
+        },)
+    }
+}
+
+/// A `--select` list: comma-separated field paths, each optionally renamed with `as`, e.g.
+/// `id, user.name as name, tags[0]`. Output keys default to the path as written.
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct Projection {
+    fields: Vec<(FieldPath, String,),>,
+    source: String,
+}
+
+impl Projection {
+    /// Builds the projected record. Fields missing from `record` are null, so every output
+    /// record has the same keys.
+    pub fn project(&self, record: &Value,) -> Value {
+        let mut projected = Map::new();
+        for (path, name,) in &self.fields {
+            projected.insert(name.clone(), path.get(record,).cloned().unwrap_or(Value::Null,),);
+        }
+        Value::Object(projected,)
+    }
+
+    /// `project` over a typed record, keeping the types of the selected values.
+    pub fn project_nc(&self, record: &NcValue,) -> NcValue {
+        let mut projected = BTreeMap::new();
+        for (path, name,) in &self.fields {
+            let value = path.get_nc(record,).cloned().unwrap_or(NcValue::Null,);
+            projected.insert(name.clone(), value,);
+        }
+        NcValue::Object(projected,)
+    }
+
+    /// The output keys, in order.
+    pub fn names(&self,) -> Vec<String,> {
+        self.fields.iter().map(|(_, name,)| name.clone(),).collect()
+    }
+
+    pub fn paths(&self,) -> impl Iterator<Item = &FieldPath,> {
+        self.fields.iter().map(|(path, _,)| path,)
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        let mut parser = Parser::new(s,)?;
+        let mut fields = Vec::new();
+        loop {
+            let path = parser.path()?;
+            let name = if parser.eat(&Token::Keyword("as",),) {
+                let alias = match parser.peek() {
+                    Some(Token::Path(alias,),) => match alias.segments() {
+                        [PathSegment::Key(key,),] => Some(key.clone(),),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let alias = alias.ok_or_else(|| parser.error("expected a name after 'as'",),)?;
+                parser.position += 1;
+                alias
+            } else {
+                path.source.clone()
+            };
+            fields.push((path, name,),);
+            if !parser.eat(&Token::Comma,) {
+                break;
+            }
+        }
+        parser.expect_end()?;
+        Ok(Projection {
+            fields,
+            source: s.trim().to_string(),
+        },)
+    }
+}
+
+/// The `--where` and `--select` of a read. Applied to record streams after the error policy,
+/// so schema inference, summaries and output only see the records (and fields) that pass.
+#[derive(Debug, Clone, Default,)]
+pub struct RecordFilter {
+    pub predicate:  Option<Predicate,>,
+    pub projection: Option<Projection,>,
+}
+
+impl RecordFilter {
+    pub fn is_empty(&self,) -> bool {
+        self.predicate.is_none() && self.projection.is_none()
+    }
+
+    /// Keeps the records matching `predicate` and projects them through `projection`. Errors
+    /// are passed through unfiltered.
+    pub fn apply(&self, stream: RecordStream,) -> RecordStream {
+        if self.is_empty() {
+            return stream;
+        }
+        let filter = self.clone();
+        Box::new(stream.filter_map(move |record| match record {
+            Ok(record,) => {
+                if filter.predicate.as_ref().is_some_and(|p| !p.matches(&record,),) {
+                    return None;
+                }
+                Some(Ok(match &filter.projection {
+                    Some(projection,) => projection.project(&record,),
+                    None => record,
+                },),)
+            },
+            Err(e,) => Some(Err(e,),),
+        },),)
+    }
+
+    /// `apply` over a typed record stream. The predicate sees each record as it is rendered in
+    /// JSON.
+    pub fn apply_typed(&self, stream: TypedRecordStream,) -> TypedRecordStream {
+        if self.is_empty() {
+            return stream;
+        }
+        let filter = self.clone();
+        Box::new(stream.filter_map(move |record| match record {
+            Ok(record,) => {
+                if filter.predicate.as_ref().is_some_and(|p| !p.matches(&record.to_json(),),) {
+                    return None;
+                }
+                Some(Ok(match &filter.projection {
+                    Some(projection,) => projection.project_nc(&record,),
+                    None => record,
+                },),)
+            },
+            Err(e,) => Some(Err(e,),),
+        },),)
+    }
+
+    /// Fails unless the filter is empty, for reads that cannot apply it, such as the summaries
+    /// of formats that are not read as records. `what` names the read in the error.
+    pub fn require_empty(&self, what: &str,) -> Result<(), DataReaderError,> {
+        if self.is_empty() {
+            return Ok((),);
+        }
+        Err(DataReaderError::InvalidConfig(format!(
+            "Record filters (--where, --select) cannot be applied to {}",
+            what
+        ),),)
+    }
+
+    /// The keys of projected records, or `None` without a projection.
+    pub fn output_columns(&self,) -> Option<Vec<String,>,> {
+        self.projection.as_ref().map(Projection::names,)
+    }
+
+    /// The top-level columns a reader must produce for this filter, or `None` if every
+    /// column is needed. Readers that can skip columns at the source use this for pushdown.
+    pub fn required_columns(&self,) -> Option<Vec<String,>,> {
+        let projection = self.projection.as_ref()?;
+        let mut paths: Vec<&FieldPath,> = projection.paths().collect();
+        if let Some(predicate,) = &self.predicate {
+            paths.extend(predicate.fields(),);
+        }
+        let mut columns: Vec<String,> = Vec::new();
+        for path in paths {
+            let root = path.root()?.to_string();
+            if !columns.contains(&root,) {
+                columns.push(root,);
+            }
+        }
+        Some(columns,)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq,)]
+enum Token {
+    Path(FieldPath,),
+    Literal(Value,),
+    Keyword(&'static str,),
+    Op(&'static str,),
+    LParen,
+    RParen,
+    Comma,
+}
+
+const KEYWORDS: [&str; 9] = ["and", "or", "not", "is", "null", "true", "false", "matches", "as"];
+
+/// Recursive-descent parser over pre-lexed tokens. Each token keeps its byte offset so errors
+/// can point at it.
+struct Parser {
+    tokens:   Vec<(Token, usize,),>,
+    position: usize,
+    len:      usize,
+}
+
+impl Parser {
+    fn new(input: &str,) -> Result<Self, String,> {
+        Ok(Parser {
+            tokens:   lex(input,)?,
+            position: 0,
+            len:      input.len(),
+        },)
+    }
+
+    fn peek(&self,) -> Option<&Token,> {
+        self.tokens.get(self.position,).map(|(token, _,)| token,)
+    }
+
+    fn next(&mut self,) -> Option<Token,> {
+        let token = self.tokens.get(self.position,).map(|(token, _,)| token.clone(),);
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token,) -> bool {
+        if self.peek() == Some(token,) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str,) -> String {
+        let offset = self.tokens.get(self.position,).map_or(self.len, |(_, offset,)| *offset,);
+        format!("{} at offset {}", message, offset)
+    }
+
+    fn expect_end(&self,) -> Result<(), String,> {
+        match self.peek() {
+            None => Ok((),),
+            Some(_,) => Err(self.error("unexpected input",),),
+        }
+    }
+
+    fn path(&mut self,) -> Result<FieldPath, String,> {
+        match self.peek() {
+            Some(Token::Path(_,),) => match self.next() {
+                Some(Token::Path(path,),) => Ok(path,),
+                _ => unreachable!("peeked a path"),
+            },
+            _ => Err(self.error("expected a field path",),),
+        }
+    }
+
+    fn or_expr(&mut self,) -> Result<Expr, String,> {
+        let mut expr = self.and_expr()?;
+        while self.eat(&Token::Keyword("or",),) || self.eat(&Token::Op("||",),) {
+            expr = Expr::Or(Box::new(expr,), Box::new(self.and_expr()?,),);
+        }
+        Ok(expr,)
+    }
+
+    fn and_expr(&mut self,) -> Result<Expr, String,> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::Keyword("and",),) || self.eat(&Token::Op("&&",),) {
+            expr = Expr::And(Box::new(expr,), Box::new(self.unary()?,),);
+        }
+        Ok(expr,)
+    }
+
+    fn unary(&mut self,) -> Result<Expr, String,> {
+        if self.eat(&Token::Keyword("not",),) || self.eat(&Token::Op("!",),) {
+            return Ok(Expr::Not(Box::new(self.unary()?,),),);
+        }
+        if self.eat(&Token::LParen,) {
+            let expr = self.or_expr()?;
+            if !self.eat(&Token::RParen,) {
+                return Err(self.error("expected ')'",),);
+            }
+            return Ok(expr,);
+        }
+
+        let left = self.operand()?;
+        if self.eat(&Token::Keyword("is",),) {
+            let negated = self.eat(&Token::Keyword("not",),);
+            if !self.eat(&Token::Keyword("null",),) {
+                return Err(self.error("expected 'null'",),);
+            }
+            let expr = Expr::IsNull(left,);
+            return Ok(if negated { Expr::Not(Box::new(expr,),) } else { expr },);
+        }
+        if self.eat(&Token::Op("=~",),) || self.eat(&Token::Keyword("matches",),) {
+            return match self.next() {
+                Some(Token::Literal(Value::String(pattern,),),) => Regex::new(&pattern,)
+                    .map(|regex| Expr::Matches(left, regex,),)
+                    .map_err(|e| format!("invalid regex '{}': {}", pattern, e),),
+                _ => {
+                    self.position -= 1;
+                    Err(self.error("expected a quoted regex",),)
+                },
+            };
+        }
+        let op = match self.peek() {
+            Some(Token::Op("==" | "=",),) => CompareOp::Eq,
+            Some(Token::Op("!=",),) => CompareOp::Ne,
+            Some(Token::Op("<",),) => CompareOp::Lt,
+            Some(Token::Op("<=",),) => CompareOp::Le,
+            Some(Token::Op(">",),) => CompareOp::Gt,
+            Some(Token::Op(">=",),) => CompareOp::Ge,
+            _ => return Ok(Expr::Truthy(left,),),
+        };
+        self.position += 1;
+        Ok(Expr::Compare(left, op, self.operand()?,),)
+    }
+
+    fn operand(&mut self,) -> Result<Operand, String,> {
+        let operand = match self.peek() {
+            Some(Token::Path(path,),) => Operand::Field(path.clone(),),
+            Some(Token::Literal(value,),) => Operand::Literal(value.clone(),),
+            Some(Token::Keyword("null",),) => Operand::Literal(Value::Null,),
+            Some(Token::Keyword("true",),) => Operand::Literal(Value::Bool(true,),),
+            Some(Token::Keyword("false",),) => Operand::Literal(Value::Bool(false,),),
+            _ => return Err(self.error("expected a field or a value",),),
+        };
+        self.position += 1;
+        Ok(operand,)
+    }
+}
+
+fn lex(input: &str,) -> Result<Vec<(Token, usize,),>, String,> {
+    let chars: Vec<(usize, char,),> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, c,) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '"' | '\'' => {
+                let (text, end,) = quoted(&chars, i, c,)?;
+                i = end;
+                tokens.push((Token::Literal(Value::String(text,),), offset,),);
+                continue;
+            },
+            c if c.is_ascii_digit() || (c == '-' && next_is_digit(&chars, i,)) => {
+                let end = scan(&chars, i + 1, |c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')
+                },);
+                let text: String = chars[i..end].iter().map(|(_, c,)| c,).collect();
+                let number = match text.parse::<i64>() {
+                    Ok(n,) => Some(Value::from(n,),),
+                    Err(_,) => {
+                        text.parse::<f64>().ok().filter(|f| f.is_finite(),).map(Value::from,)
+                    },
+                }
+                .ok_or_else(|| format!("invalid number '{}' at offset {}", text, offset),)?;
+                i = end;
+                tokens.push((Token::Literal(number,), offset,),);
+                continue;
+            },
+            c if is_ident_start(c,) || c == '`' => {
+                let (path, end,) = lex_path(input, &chars, i,)?;
+                i = end;
+                let keyword = match path.segments.as_slice() {
+                    [PathSegment::Key(key,),] if !path.source.starts_with('`',) => {
+                        KEYWORDS.iter().find(|k| k.eq_ignore_ascii_case(key,),).copied()
+                    },
+                    _ => None,
+                };
+                tokens.push((keyword.map_or(Token::Path(path,), Token::Keyword,), offset,),);
+                continue;
+            },
+            _ => {
+                let rest = &input[offset..];
+                let op = ["==", "!=", "<=", ">=", "=~", "&&", "||", "<>", "=", "<", ">", "!"]
+                    .into_iter()
+                    .find(|op| rest.starts_with(op,),)
+                    .ok_or_else(|| format!("unexpected character '{}' at offset {}", c, offset),)?;
+                i += op.len();
+                tokens.push((Token::Op(if op == "<>" { "!=" } else { op },), offset,),);
+                continue;
+            },
+        };
+        tokens.push((token, offset,),);
+        i += 1;
+    }
+    Ok(tokens,)
+}
+
+/// Lexes a field path starting at `chars[start]`: identifiers or backticked keys joined by
+/// `.`, each optionally followed by `[N]` indexes.
+fn lex_path(
+    input: &str,
+    chars: &[(usize, char,)],
+    start: usize,
+) -> Result<(FieldPath, usize,), String,> {
+    let mut segments = Vec::new();
+    let mut i = start;
+    loop {
+        match chars.get(i,) {
+            Some((_, '`',),) => {
+                let (key, end,) = quoted(chars, i, '`',)?;
+                segments.push(PathSegment::Key(key,),);
+                i = end;
+            },
+            Some((_, c,),) if is_ident_start(*c,) => {
+                let end = scan(chars, i, |c| c.is_alphanumeric() || c == '_',);
+                segments.push(PathSegment::Key(chars[i..end].iter().map(|(_, c,)| c,).collect(),),);
+                i = end;
+            },
+            _ => {
+                let offset = offset_at(input, chars, i,);
+                return Err(format!("expected a field name at offset {}", offset),);
+            },
+        }
+        while let Some((_, '[',),) = chars.get(i,) {
+            let end = scan(chars, i + 1, |c| c.is_ascii_digit(),);
+            let digits: String = chars[i + 1..end].iter().map(|(_, c,)| c,).collect();
+            match (digits.parse::<usize>(), chars.get(end,),) {
+                (Ok(index,), Some((_, ']',),),) => segments.push(PathSegment::Index(index,),),
+                _ => {
+                    let offset = offset_at(input, chars, i,);
+                    return Err(format!("expected '[N]' at offset {}", offset),);
+                },
+            }
+            i = end + 1;
+        }
+        match chars.get(i,) {
+            Some((_, '.',),) => i += 1,
+            _ => break,
+        }
+    }
+    let source = input[chars[start].0..offset_at(input, chars, i,)].to_string();
+    Ok((
+        FieldPath {
+            segments,
+            source,
+        },
+        i,
+    ),)
+}
+
+/// Reads a quoted string starting at the opening `quote`; a backslash escapes the next
+/// character. Returns the unescaped text and the index after the closing quote.
+fn quoted(
+    chars: &[(usize, char,)],
+    start: usize,
+    quote: char,
+) -> Result<(String, usize,), String,> {
+    let mut text = String::new();
+    let mut i = start + 1;
+    while let Some((_, c,),) = chars.get(i,) {
+        match *c {
+            '\\' if quote != '`' => {
+                // Keep the backslash unless it escapes the quote, so regex escapes survive.
+                match chars.get(i + 1,) {
+                    Some((_, next,),) if *next == quote => text.push(*next,),
+                    Some((_, next,),) => {
+                        text.push('\\',);
+                        text.push(*next,);
+                    },
+                    None => break,
+                }
+                i += 2;
+            },
+            c if c == quote => return Ok((text, i + 1,),),
+            c => {
+                text.push(c,);
+                i += 1;
+            },
+        }
+    }
+    Err(format!("unterminated {} at offset {}", quote, chars[start].0),)
+}
+
+fn scan(chars: &[(usize, char,)], start: usize, accept: impl Fn(char,) -> bool,) -> usize {
+    let mut end = start;
+    while chars.get(end,).is_some_and(|(_, c,)| accept(*c,),) {
+        end += 1;
+    }
+    end
+}
+
+fn next_is_digit(chars: &[(usize, char,)], i: usize,) -> bool {
+    chars.get(i + 1,).is_some_and(|(_, c,)| c.is_ascii_digit(),)
+}
+
+fn is_ident_start(c: char,) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn offset_at(input: &str, chars: &[(usize, char,)], i: usize,) -> usize {
+    chars.get(i,).map_or(input.len(), |(offset, _,)| *offset,)
+}
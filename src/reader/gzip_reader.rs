@@ -6,6 +6,7 @@ use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize}; // Add this import
 
 use crate::error::DataReaderError;
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct GzipData {
@@ -14,6 +15,16 @@ pub struct GzipData {
 }
 
 pub fn read_gzip_data(file_path: &Path,) -> Result<GzipData, DataReaderError,> {
+    read_gzip_data_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_gzip_data` with an explicit `ReadConfig`. Decompression stops one byte past
+/// `config.limits.max_decompressed_bytes`, so a gzip bomb is never fully inflated.
+pub fn read_gzip_data_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<GzipData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
@@ -27,15 +38,18 @@ pub fn read_gzip_data(file_path: &Path,) -> Result<GzipData, DataReaderError,> {
         .len();
 
     let decoder = GzDecoder::new(file,);
-    let mut reader = io::BufReader::new(decoder,);
+    let mut reader = io::BufReader::new(decoder,).take(budget.decompressed_read_cap(),);
 
     let mut decompressed_data = Vec::new();
     reader
         .read_to_end(&mut decompressed_data,)
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?; // Changed to ParseError as it's an issue with decompression, not just reading
+    budget.check_decompressed_bytes(decompressed_data.len() as u64,)?;
+    budget.check()?;
 
     Ok(GzipData {
         compressed_size,
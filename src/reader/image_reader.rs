@@ -33,13 +33,15 @@ pub fn read_image_data(file_path: &Path,) -> Result<ImageData, DataReaderError,>
 
     let img_reader = ImageReader::open(file_path,)
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?
         .with_guessed_format()
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
     let format = img_reader.format().ok_or_else(|| {
         DataReaderError::InternalError("Could not guess image format".to_string(),)
@@ -48,8 +50,9 @@ pub fn read_image_data(file_path: &Path,) -> Result<ImageData, DataReaderError,>
     let img = img_reader
         .decode()
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
     let dimensions = format!("{}x{}", img.width(), img.height());
 
@@ -1,13 +1,23 @@
 use std::collections::HashMap;
 use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
+use arrow::datatypes::SchemaRef;
+use bytes::{Buf, Bytes};
 use nc_schema::{DataType, merge_nc_types};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::error::DataReaderError;
+use crate::error::{DataReaderError, ErrorPosition};
 use crate::nc_reader_result::RecordStream;
+use crate::reader::async_stream::{AsyncRecordStream, open_async_stream};
+use crate::reader::error_policy::{RejectReport, apply_error_policy, fuse_after_error};
+use crate::reader::limits::{apply_limits, json_depth};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::{
+    RecordBatchStream, arrow_schema_from_nc_types, infer_record_columns, json_records_to_batches,
+};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq,)]
 pub struct JsonSchema {
@@ -21,9 +31,12 @@ pub struct JsonData {
     pub first_lines:     Option<Vec<String,>,>,
     pub inferred_schema: Option<JsonSchema,>,
     pub line_count:      Option<usize,>,
+    /// Records skipped under a lenient error policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejects:         Option<RejectReport,>,
 }
 
-fn infer_json_nc_type(value: &serde_json::Value,) -> DataType {
+pub(crate) fn infer_json_nc_type(value: &serde_json::Value,) -> DataType {
     match value {
         serde_json::Value::Null => DataType::Null,
         serde_json::Value::Bool(_,) => DataType::Boolean,
@@ -93,8 +106,34 @@ pub fn read_json_stream(file_path: &Path,) -> Result<RecordStream, DataReaderErr
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
+    read_json_stream_from_reader(file, is_jsonl, file_path,)
+}
+
+/// Async variant of `read_json_stream`; parsing runs on tokio's blocking pool.
+pub async fn read_json_stream_async(
+    file_path: &Path,
+) -> Result<AsyncRecordStream, DataReaderError,> {
+    let path = file_path.to_path_buf();
+    open_async_stream(move || read_json_stream(&path,),).await
+}
+
+/// Streams JSON values from in-memory bytes. Concatenated or newline-delimited values are
+/// both accepted, so JSONL input does not need to be flagged.
+pub fn read_json_stream_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
+    read_json_stream_from_reader(data.reader(), false, file_path,)
+}
+
+/// Streams JSON values from any reader. `file_path` is only used to label errors.
+pub fn read_json_stream_from_reader<R: Read + Send + 'static,>(
+    reader: R,
+    is_jsonl: bool,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
     let path_clone = file_path.to_path_buf();
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
+    let decoder = crate::reader::charset::get_decoded_reader_from(reader,).map_err(|e| {
         DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
             source: e,
@@ -104,25 +143,36 @@ pub fn read_json_stream(file_path: &Path,) -> Result<RecordStream, DataReaderErr
     if is_jsonl {
         use std::io::{BufRead, BufReader};
         let reader = BufReader::new(decoder,);
-        let stream = reader.lines().filter_map(move |line_res| match line_res {
-            Ok(line,) => {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    None
-                } else {
+        let mut record_index = 0u64;
+        let stream = reader.lines().enumerate().filter_map(move |(line_index, line_res,)| {
+            match line_res {
+                Ok(line,) => {
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() {
+                        return None;
+                    }
+                    let index = record_index;
+                    record_index += 1;
                     match serde_json::from_str::<Value,>(trimmed,) {
                         Ok(v,) => Some(Ok(v,),),
-                        Err(e,) => Some(Err(DataReaderError::ParseError {
-                            path:   path_clone.clone(),
-                            source: Box::new(e,),
-                        },),),
+                        Err(e,) => {
+                            // The parser only saw the trimmed line; shift its column back.
+                            let indent = line.len() - line.trim_start().len();
+                            let position = ErrorPosition::line_column(
+                                line_index as u64 + 1,
+                                (e.column() + indent) as u64,
+                            )
+                            .with_record_index(index,)
+                            .with_raw(trimmed,);
+                            Some(Err(json_parse_error(&path_clone, e, position,),),)
+                        },
                     }
-                }
-            },
-            Err(e,) => Some(Err(DataReaderError::FileReadError {
-                path:   path_clone.clone(),
-                source: e,
-            },),),
+                },
+                Err(e,) => Some(Err(DataReaderError::FileReadError {
+                    path:   path_clone.clone(),
+                    source: e,
+                },),),
+            }
         },);
         Ok(Box::new(stream,),)
     } else {
@@ -130,24 +180,68 @@ pub fn read_json_stream(file_path: &Path,) -> Result<RecordStream, DataReaderErr
         let reader = BufReader::new(decoder,);
         let stream = serde_json::Deserializer::from_reader(reader,)
             .into_iter::<Value>()
-            .map(move |res| {
-                res.map_err(|e| DataReaderError::ParseError {
-                    path:   path_clone.clone(),
-                    source: Box::new(e,),
+            .enumerate()
+            .map(move |(index, res,)| {
+                res.map_err(|e| {
+                    let position = ErrorPosition::line_column(e.line() as u64, e.column() as u64,)
+                        .with_record_index(index as u64,);
+                    json_parse_error(&path_clone, e, position,)
                 },)
             },);
-        Ok(Box::new(stream,),)
+        // A syntax error leaves the deserializer mid-value; nothing after it can be recovered.
+        Ok(Box::new(fuse_after_error(stream,),),)
     }
 }
 
-pub fn read_json_value(
+fn json_parse_error(
     file_path: &Path,
-    head: Option<usize,>,
-) -> Result<JsonData, DataReaderError,> {
-    let num_lines_to_extract = head.unwrap_or(0,);
-    let is_jsonl = file_path.extension().is_some_and(|ext| ext == "jsonl",);
+    e: serde_json::Error,
+    position: ErrorPosition,
+) -> DataReaderError {
+    DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: Some(Box::new(position,),),
+    }
+}
 
-    let stream = read_json_stream(file_path,)?;
+/// Turns a stream of top-level JSON values into a stream of records: arrays are flattened into
+/// their elements and non-object values are wrapped as `{"value": ...}`.
+fn json_records(stream: RecordStream,) -> RecordStream {
+    Box::new(
+        stream
+            .flat_map(|result| match result {
+                Ok(Value::Array(items,),) => items.into_iter().map(Ok,).collect::<Vec<_,>>(),
+                other => vec![other],
+            },)
+            .map(|result| {
+                result.map(|value| match value {
+                    Value::Object(_,) => value,
+                    other => serde_json::json!({ "value": other }),
+                },)
+            },),
+    )
+}
+
+/// Reads a JSON or JSONL file as Arrow record batches. Top-level arrays are read element by
+/// element. A first pass infers the column types the same way `read_json_value` does; a second
+/// pass decodes the records into batches of `batch_size`.
+pub fn read_json_batches(
+    file_path: &Path,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    let (columns, types,) =
+        infer_record_columns(json_records(read_json_stream(file_path,)?,), infer_json_nc_type,)?;
+    let schema = arrow_schema_from_nc_types(&columns, &types,);
+
+    let records = json_records(read_json_stream(file_path,)?,);
+    let batches = json_records_to_batches(schema.clone(), records, batch_size, file_path,)?;
+    Ok((schema, batches,),)
+}
+
+fn collect_json_values(
+    stream: RecordStream,
+) -> Result<(Vec<serde_json::Value,>, Option<JsonSchema,>,), DataReaderError,> {
     let mut values = Vec::new();
     let mut inferred_schema: Option<JsonSchema,> = None;
 
@@ -161,6 +255,44 @@ pub fn read_json_value(
         values.push(value,);
     }
 
+    Ok((values, inferred_schema,),)
+}
+
+/// Summarizes any record stream as a JSON array with an inferred schema. Used for sources
+/// that are not files, where the format-specific summary readers cannot be applied.
+pub fn json_data_from_stream(
+    stream: RecordStream,
+    first_lines: Option<Vec<String,>,>,
+) -> Result<JsonData, DataReaderError,> {
+    let (values, inferred_schema,) = collect_json_values(stream,)?;
+    let line_count = Some(values.len(),);
+    Ok(JsonData {
+        value: serde_json::Value::Array(values,),
+        first_lines,
+        inferred_schema,
+        line_count,
+        rejects: None,
+    },)
+}
+
+pub fn read_json_value(
+    file_path: &Path,
+    head: Option<usize,>,
+) -> Result<JsonData, DataReaderError,> {
+    read_json_value_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_json_value` with an explicit `ReadConfig`. Malformed JSONL lines are handled according
+/// to `config.error_policy`; skipped lines are listed in `JsonData::rejects`. Reading stops with
+/// `LimitExceeded` once `config.limits` is exceeded.
+pub fn read_json_value_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<JsonData, DataReaderError,> {
+    let num_lines_to_extract = head.unwrap_or(0,);
+    let stream = read_json_stream(file_path,)?;
+
     let first_lines = if num_lines_to_extract > 0 {
         use std::io::{BufRead, BufReader};
         let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -187,6 +319,40 @@ pub fn read_json_value(
     } else {
         None
     };
+    summarize_json(stream, file_path, first_lines, config,)
+}
+
+/// `read_json_value_with_config` for JSON or JSONL held in memory. `file_path` is only used to
+/// label errors and rejects, and a `.jsonl` extension keeps a single record in an array.
+pub fn read_json_value_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<JsonData, DataReaderError,> {
+    let first_lines = head
+        .filter(|n| *n > 0,)
+        .map(|n| crate::reader::charset::head_lines(&data, n,),)
+        .filter(|lines| !lines.is_empty(),);
+    let is_jsonl = file_path.extension().is_some_and(|ext| ext == "jsonl",);
+    let stream = read_json_stream_from_reader(data.reader(), is_jsonl, file_path,)?;
+    summarize_json(stream, file_path, first_lines, config,)
+}
+
+/// Collects the values of `stream` into a `JsonData`: a single JSON document stays as it is,
+/// several values (or JSONL) become an array.
+fn summarize_json(
+    stream: RecordStream,
+    file_path: &Path,
+    first_lines: Option<Vec<String,>,>,
+    config: &ReadConfig,
+) -> Result<JsonData, DataReaderError,> {
+    let is_jsonl = file_path.extension().is_some_and(|ext| ext == "jsonl",);
+    let stream =
+        apply_error_policy(stream, file_path, config.error_policy, config.rejects.clone(),);
+    let stream = apply_limits(stream, config.budget(file_path,), json_depth,);
+    let stream = config.filter.apply(stream,);
+    let (values, inferred_schema,) = collect_json_values(stream,)?;
 
     let final_value = if values.len() == 1 && !is_jsonl {
         values.into_iter().next().unwrap()
@@ -204,6 +370,7 @@ pub fn read_json_value(
         first_lines,
         inferred_schema,
         line_count,
+        rejects: config.reject_report(file_path,),
     },)
 }
 
@@ -0,0 +1,303 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::error::DataReaderError;
+use crate::reader::record_batch::RecordBatchStream;
+use crate::record::NcValue;
+
+/// Upper bounds for reading untrusted input. `None` means unlimited, which is the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq,)]
+pub struct ReadLimits {
+    /// Size of the input file or in-memory source, checked before reading, and bytes read
+    /// through a `BudgetedReader` (stdin, text, PDF and spreadsheet files).
+    pub max_bytes:              Option<u64,>,
+    /// Bytes produced by decompressing a GZIP file.
+    pub max_decompressed_bytes: Option<u64,>,
+    /// Records read from a record-based source.
+    pub max_records:            Option<u64,>,
+    /// Nesting depth of a record or document (1 for a scalar).
+    pub max_depth:              Option<usize,>,
+    /// Wall-clock time allowed for reading one file.
+    pub timeout:                Option<Duration,>,
+}
+
+/// The limit that a read ran into, with its configured value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+pub enum Limit {
+    Bytes(u64,),
+    DecompressedBytes(u64,),
+    Records(u64,),
+    Depth(usize,),
+    Timeout(Duration,),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            Limit::Bytes(max,) => write!(f, "input larger than {} bytes", max),
+            Limit::DecompressedBytes(max,) => write!(f, "decompressed larger than {} bytes", max),
+            Limit::Records(max,) => write!(f, "more than {} records", max),
+            Limit::Depth(max,) => write!(f, "nested deeper than {} levels", max),
+            Limit::Timeout(timeout,) => write!(f, "took longer than {:?}", timeout),
+        }
+    }
+}
+
+/// A cheap, cloneable flag for cancelling reads from another thread or task. Every clone
+/// observes the same flag; readers check it between records.
+#[derive(Debug, Clone, Default,)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool,>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self,) {
+        self.cancelled.store(true, Ordering::Relaxed,);
+    }
+
+    pub fn is_cancelled(&self,) -> bool {
+        self.cancelled.load(Ordering::Relaxed,)
+    }
+}
+
+/// The limits of one file read, with the deadline fixed when the read started. Clones share
+/// the count of bytes read through `BudgetedReader`s.
+#[derive(Debug, Clone,)]
+pub struct ReadBudget {
+    path:       PathBuf,
+    limits:     ReadLimits,
+    cancel:     CancellationToken,
+    deadline:   Option<Instant,>,
+    bytes_read: Arc<AtomicU64,>,
+}
+
+impl ReadBudget {
+    pub fn new(file_path: &Path, limits: &ReadLimits, cancel: &CancellationToken,) -> Self {
+        Self {
+            path:       file_path.to_path_buf(),
+            limits:     limits.clone(),
+            cancel:     cancel.clone(),
+            deadline:   limits.timeout.map(|timeout| Instant::now() + timeout,),
+            bytes_read: Arc::default(),
+        }
+    }
+
+    fn exceeded(&self, limit: Limit,) -> DataReaderError {
+        DataReaderError::LimitExceeded {
+            path: self.path.clone(),
+            limit,
+        }
+    }
+
+    /// Fails if the read was cancelled, ran past its deadline, or read more than `max_bytes`
+    /// through a `BudgetedReader`.
+    pub fn check(&self,) -> Result<(), DataReaderError,> {
+        if self.cancel.is_cancelled() {
+            return Err(DataReaderError::Cancelled {
+                path: self.path.clone(),
+            },);
+        }
+        if let (Some(deadline,), Some(timeout,),) = (self.deadline, self.limits.timeout,)
+            && Instant::now() > deadline
+        {
+            return Err(self.exceeded(Limit::Timeout(timeout,),),);
+        }
+        self.check_bytes(self.bytes_read(),)
+    }
+
+    /// The bytes read through `BudgetedReader`s on this budget so far.
+    pub fn bytes_read(&self,) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed,)
+    }
+
+    pub fn check_bytes(&self, bytes: u64,) -> Result<(), DataReaderError,> {
+        match self.limits.max_bytes {
+            Some(max,) if bytes > max => Err(self.exceeded(Limit::Bytes(max,),),),
+            _ => Ok((),),
+        }
+    }
+
+    pub fn check_decompressed_bytes(&self, bytes: u64,) -> Result<(), DataReaderError,> {
+        match self.limits.max_decompressed_bytes {
+            Some(max,) if bytes > max => Err(self.exceeded(Limit::DecompressedBytes(max,),),),
+            _ => Ok((),),
+        }
+    }
+
+    /// `records` is the number of records read so far, including the current one.
+    pub fn check_records(&self, records: u64,) -> Result<(), DataReaderError,> {
+        match self.limits.max_records {
+            Some(max,) if records > max => Err(self.exceeded(Limit::Records(max,),),),
+            _ => Ok((),),
+        }
+    }
+
+    pub fn check_depth(&self, depth: usize,) -> Result<(), DataReaderError,> {
+        match self.limits.max_depth {
+            Some(max,) if depth > max => Err(self.exceeded(Limit::Depth(max,),),),
+            _ => Ok((),),
+        }
+    }
+
+    pub fn max_depth(&self,) -> Option<usize,> {
+        self.limits.max_depth
+    }
+
+    /// The most bytes worth decompressing: one past the limit, enough to tell that it was
+    /// exceeded without inflating the rest.
+    pub fn decompressed_read_cap(&self,) -> u64 {
+        self.limits.max_decompressed_bytes.map_or(u64::MAX, |max| max.saturating_add(1,),)
+    }
+}
+
+/// Counts the bytes read from `inner` towards `budget`. A reader made with `new` also enforces
+/// the budget: cancellation and the deadline are checked before each read, and the bytes read
+/// so far against `max_bytes`. Its limit errors surface as `io::Error`s wrapping the
+/// `DataReaderError`; `read_error` unwraps them again. A reader made with `counting` never
+/// fails on its own, for parsers that would report its errors as malformed input; the record
+/// stream's `apply_limits` enforces the budget instead.
+pub struct BudgetedReader<R,> {
+    inner:   R,
+    budget:  ReadBudget,
+    enforce: bool,
+}
+
+impl<R,> BudgetedReader<R,> {
+    pub fn new(inner: R, budget: ReadBudget,) -> Self {
+        Self {
+            inner,
+            budget,
+            enforce: true,
+        }
+    }
+
+    pub fn counting(inner: R, budget: ReadBudget,) -> Self {
+        Self {
+            inner,
+            budget,
+            enforce: false,
+        }
+    }
+}
+
+impl<R: Read,> Read for BudgetedReader<R,> {
+    fn read(&mut self, buf: &mut [u8],) -> io::Result<usize,> {
+        if self.enforce {
+            self.budget.check().map_err(io::Error::other,)?;
+        }
+        let n = self.inner.read(buf,)?;
+        let read = self.budget.bytes_read.fetch_add(n as u64, Ordering::Relaxed,) + n as u64;
+        if self.enforce {
+            self.budget.check_bytes(read,).map_err(io::Error::other,)?;
+        }
+        Ok(n,)
+    }
+}
+
+/// Turns an I/O error met while reading `file_path` into a `DataReaderError`: the limit or
+/// cancellation error a `BudgetedReader` wrapped, or a `FileReadError`.
+pub fn read_error(file_path: &Path, error: io::Error,) -> DataReaderError {
+    match error.downcast::<DataReaderError>() {
+        Ok(error,) => error,
+        Err(error,) => DataReaderError::FileReadError {
+            path:   file_path.to_path_buf(),
+            source: error,
+        },
+    }
+}
+
+/// Enforces `budget` on a record stream: cancellation and the deadline are checked before each
+/// record, and every record counts towards `max_records` and is measured against `max_depth`
+/// with `depth`. The stream ends after the first limit error.
+pub fn apply_limits<T: Send + 'static,>(
+    stream: Box<dyn Iterator<Item = Result<T, DataReaderError,>,> + Send,>,
+    budget: ReadBudget,
+    depth: fn(&T,) -> usize,
+) -> Box<dyn Iterator<Item = Result<T, DataReaderError,>,> + Send,> {
+    let mut stream = stream;
+    let mut records = 0u64;
+    let mut failed = false;
+    Box::new(std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        if let Err(e,) = budget.check() {
+            failed = true;
+            return Some(Err(e,),);
+        }
+        let record = match stream.next()? {
+            Ok(record,) => record,
+            Err(e,) => return Some(Err(e,),),
+        };
+        records += 1;
+        let mut checked = budget.check_records(records,);
+        if checked.is_ok() && budget.max_depth().is_some() {
+            checked = budget.check_depth(depth(&record,),);
+        }
+        match checked {
+            Ok((),) => Some(Ok(record,),),
+            Err(e,) => {
+                failed = true;
+                Some(Err(e,),)
+            },
+        }
+    },),)
+}
+
+/// Nesting depth of a JSON value: 1 for scalars, plus one per array or object level.
+pub fn json_depth(value: &serde_json::Value,) -> usize {
+    match value {
+        serde_json::Value::Array(items,) => 1 + items.iter().map(json_depth,).max().unwrap_or(0,),
+        serde_json::Value::Object(fields,) => {
+            1 + fields.values().map(json_depth,).max().unwrap_or(0,)
+        },
+        _ => 1,
+    }
+}
+
+/// Nesting depth of a typed value, counted like `json_depth`.
+pub fn nc_depth(value: &NcValue,) -> usize {
+    match value {
+        NcValue::Array(items,) => 1 + items.iter().map(nc_depth,).max().unwrap_or(0,),
+        NcValue::Object(fields,) => 1 + fields.values().map(nc_depth,).max().unwrap_or(0,),
+        _ => 1,
+    }
+}
+
+/// Enforces `budget` on a record batch stream: cancellation and the deadline are checked before
+/// each batch, and its rows count towards `max_records`. The stream ends after the first limit
+/// error.
+pub fn apply_batch_limits(batches: RecordBatchStream, budget: ReadBudget,) -> RecordBatchStream {
+    let mut batches = batches;
+    let mut rows = 0u64;
+    let mut failed = false;
+    Box::new(std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        if let Err(e,) = budget.check() {
+            failed = true;
+            return Some(Err(e,),);
+        }
+        let batch = match batches.next()? {
+            Ok(batch,) => batch,
+            Err(e,) => return Some(Err(e,),),
+        };
+        rows += batch.num_rows() as u64;
+        match budget.check_records(rows,) {
+            Ok((),) => Some(Ok(batch,),),
+            Err(e,) => {
+                failed = true;
+                Some(Err(e,),)
+            },
+        }
+    },),)
+}
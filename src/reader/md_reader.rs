@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 
 use pulldown_cmark::{Event, Parser, Tag};
 use serde::{Deserialize, Serialize};
 
 use crate::error::DataReaderError;
+use crate::reader::limits::{BudgetedReader, read_error};
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone,)]
 pub struct MarkdownElements {
@@ -47,13 +50,26 @@ fn extract_markdown_elements(content: &str,) -> MarkdownElements {
 pub fn read_md_content(
     file_path: &Path,
     head: Option<usize,>,
+) -> Result<MarkdownData, DataReaderError,> {
+    read_md_content_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_md_content` within the limits of `config`, read through a `BudgetedReader`.
+pub fn read_md_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
 ) -> Result<MarkdownData, DataReaderError,> {
     let num_lines_to_extract = head.unwrap_or(0,);
 
-    let content = fs::read_to_string(file_path,).map_err(|e| DataReaderError::FileReadError {
+    let file = fs::File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
+    let mut content = String::new();
+    BudgetedReader::new(file, config.budget(file_path,),)
+        .read_to_string(&mut content,)
+        .map_err(|e| read_error(file_path, e,),)?;
 
     let first_lines: Option<Vec<String,>,> = if num_lines_to_extract > 0 {
         let lines: Vec<String,> = content
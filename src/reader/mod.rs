@@ -1,15 +1,27 @@
+pub mod async_stream;
+pub mod builtin;
 pub mod charset;
+pub mod coercion;
+pub mod csv_dialect;
 pub mod csv_reader;
+pub mod error_policy;
+pub mod filter;
 pub mod gzip_reader;
 pub mod image_reader;
 pub mod json_reader;
+pub mod limits;
 pub mod md_reader;
 pub mod parquet_reader;
 pub mod pdf_reader;
+pub mod read_config;
+pub mod record_batch;
+pub mod registry;
+pub mod sample;
 pub mod spreadsheet_reader;
 pub mod sqlite_reader;
 pub mod toml_reader;
 pub mod txt_reader;
+pub mod type_inference;
 pub mod xml_reader;
 pub mod yaml_reader;
 pub mod zip_reader;
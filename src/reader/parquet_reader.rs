@@ -1,16 +1,20 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use std::str::FromStr;
 
 use arrow::array::Array;
-use chrono::{DateTime, NaiveDate};
+use arrow::datatypes::SchemaRef;
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate};
 use chrono_tz::Tz;
+use parquet::arrow::ProjectionMask;
 use parquet::arrow::arrow_reader::ArrowReaderBuilder;
-use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
 use serde::{Deserialize, Serialize};
 
-use crate::error::DataReaderError;
+use crate::error::{DataReaderError, ErrorPosition};
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct ParquetColumnInfo {
@@ -40,15 +44,32 @@ pub struct ParquetDataForAnalysis {
     pub column_null_counts: HashMap<String, u64,>,
     pub column_distinct_counts: HashMap<String, u64,>,
     pub column_uniqueness_percentages: HashMap<String, f64,>,
+    /// Columns with more than `MAX_DISTINCT_VALUES` distinct values. Their distinct count and
+    /// uniqueness stop at the cap, so they are lower bounds.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approximate_distinct_counts: Vec<String,>,
 }
 
-use crate::nc_reader_result::RecordStream;
+/// Distinct values remembered per column by the analysis. Counting stops at this many, which
+/// bounds its memory however many rows the file has.
+pub const MAX_DISTINCT_VALUES: usize = 100_000;
+
+use crate::nc_reader_result::{RecordStream, TypedRecordStream, typed_to_json_stream};
+use crate::reader::async_stream::{AsyncRecordStream, open_async_stream};
+use crate::reader::error_policy::fuse_after_error;
+use crate::reader::limits::ReadBudget;
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::RecordBatchStream;
+use crate::record::NcValue;
 
 pub struct ParquetStream {
-    reader:        parquet::arrow::arrow_reader::ParquetRecordBatchReader,
-    current_batch: Option<arrow::record_batch::RecordBatch,>,
-    current_row:   usize,
-    path:          std::path::PathBuf,
+    reader:         parquet::arrow::arrow_reader::ParquetRecordBatchReader,
+    current_batch:  Option<arrow::record_batch::RecordBatch,>,
+    current_row:    usize,
+    path:           std::path::PathBuf,
+    /// Rows decoded so far across all batches, i.e. the file index of the next batch's first row.
+    rows_decoded:   u64,
+    row_group_rows: Vec<u64,>,
 }
 
 impl ParquetStream {
@@ -61,27 +82,36 @@ impl ParquetStream {
             current_batch: None,
             current_row: 0,
             path,
+            rows_decoded: 0,
+            row_group_rows: Vec::new(),
         }
     }
+
+    /// Sets the row count of each row group, so decoding errors can name the row group they
+    /// occurred in.
+    pub fn with_row_group_sizes(mut self, row_group_rows: Vec<u64,>,) -> Self {
+        self.row_group_rows = row_group_rows;
+        self
+    }
 }
 
 impl Iterator for ParquetStream {
-    type Item = Result<serde_json::Value, DataReaderError,>;
+    type Item = Result<NcValue, DataReaderError,>;
 
     fn next(&mut self,) -> Option<Self::Item,> {
         loop {
             if let Some(batch,) = &self.current_batch {
                 if self.current_row < batch.num_rows() {
-                    let mut row_map = serde_json::Map::new();
+                    let mut row_map = BTreeMap::new();
                     let schema = batch.schema();
                     for col_idx in 0..batch.num_columns() {
                         let column = batch.column(col_idx,);
                         let column_name = schema.field(col_idx,).name();
-                        let value = arrow_to_json_value(column, self.current_row,);
+                        let value = arrow_to_nc_value(column, self.current_row,);
                         row_map.insert(column_name.to_string(), value,);
                     }
                     self.current_row += 1;
-                    return Some(Ok(serde_json::Value::Object(row_map,),),);
+                    return Some(Ok(NcValue::Object(row_map,),),);
                 } else {
                     self.current_batch = None;
                     self.current_row = 0;
@@ -90,13 +120,16 @@ impl Iterator for ParquetStream {
 
             match self.reader.next() {
                 Some(Ok(batch,),) => {
+                    self.rows_decoded += batch.num_rows() as u64;
                     self.current_batch = Some(batch,);
                     self.current_row = 0;
                 },
                 Some(Err(e,),) => {
+                    let position = row_position(&self.row_group_rows, self.rows_decoded,);
                     return Some(Err(DataReaderError::ParseError {
-                        path:   self.path.clone(),
-                        source: Box::new(e,),
+                        path:     self.path.clone(),
+                        source:   Box::new(e,),
+                        position: Some(Box::new(position,),),
                     },),);
                 },
                 None => return None,
@@ -105,157 +138,405 @@ impl Iterator for ParquetStream {
     }
 }
 
+/// The position of row `record_index` of the file, including its row group when the row group
+/// sizes are known.
+fn row_position(row_group_rows: &[u64], record_index: u64,) -> ErrorPosition {
+    let mut first_row = 0;
+    let row_group = row_group_rows.iter().position(|rows| {
+        first_row += rows;
+        record_index < first_row
+    },);
+    ErrorPosition {
+        record_index: Some(record_index,),
+        row_group,
+        ..Default::default()
+    }
+}
+
+fn row_group_sizes(metadata: &parquet::file::metadata::ParquetMetaData,) -> Vec<u64,> {
+    metadata.row_groups().iter().map(|rg| rg.num_rows().max(0,) as u64,).collect()
+}
+
 pub fn read_parquet_stream(file_path: &Path,) -> Result<RecordStream, DataReaderError,> {
+    Ok(typed_to_json_stream(read_parquet_typed_stream(file_path,)?,),)
+}
+
+/// Streams rows with only the top-level `columns` (all of them for `None`). Other columns are
+/// never decoded; names that are not in the file are ignored.
+pub fn read_parquet_stream_projected(
+    file_path: &Path,
+    columns: Option<&[String],>,
+) -> Result<RecordStream, DataReaderError,> {
+    Ok(typed_to_json_stream(read_parquet_typed_stream_projected(file_path, columns,)?,),)
+}
+
+/// Streams rows as `NcValue` objects, keeping decimals, timestamps and binary columns exact.
+pub fn read_parquet_typed_stream(file_path: &Path,) -> Result<TypedRecordStream, DataReaderError,> {
+    read_parquet_typed_stream_projected(file_path, None,)
+}
+
+/// Typed variant of `read_parquet_stream_projected`.
+pub fn read_parquet_typed_stream_projected(
+    file_path: &Path,
+    columns: Option<&[String],>,
+) -> Result<TypedRecordStream, DataReaderError,> {
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
 
     let builder = ArrowReaderBuilder::try_new(file,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
 
+    let row_group_rows = row_group_sizes(builder.metadata(),);
+    let builder = match columns {
+        Some(columns,) => {
+            let roots: Vec<usize,> = builder
+                .schema()
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, field,)| columns.iter().any(|c| c == field.name(),),)
+                .map(|(index, _,)| index,)
+                .collect();
+            let mask = ProjectionMask::roots(builder.parquet_schema(), roots,);
+            builder.with_projection(mask,)
+        },
+        None => builder,
+    };
     let reader = builder.build().map_err(|e| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    },)?;
+
+    let stream =
+        ParquetStream::new(reader, file_path.to_path_buf(),).with_row_group_sizes(row_group_rows,);
+    Ok(Box::new(fuse_after_error(stream,),),)
+}
+
+/// Reads the file as Arrow record batches of `batch_size` rows, without any row conversion.
+pub fn read_parquet_batches(
+    file_path: &Path,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        source: e,
+    },)?;
+
+    let builder = ArrowReaderBuilder::try_new(file,).map_err(|e| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    },)?;
+    let schema = builder.schema().clone();
+    let row_group_rows = row_group_sizes(builder.metadata(),);
+
+    let reader = builder
+        .with_batch_size(batch_size.max(1,),)
+        .build()
+        .map_err(|e| DataReaderError::ParseError {
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
+        },)?;
+
+    let path = file_path.to_path_buf();
+    let mut rows_decoded = 0u64;
+    let batches = reader.map(move |batch| match batch {
+        Ok(batch,) => {
+            rows_decoded += batch.num_rows() as u64;
+            Ok(batch,)
+        },
+        Err(e,) => Err(DataReaderError::ParseError {
+            path:     path.clone(),
+            source:   Box::new(e,),
+            position: Some(Box::new(row_position(&row_group_rows, rows_decoded,),),),
+        },),
+    },);
+    Ok((schema, Box::new(batches,),),)
+}
+
+/// Async variant of `read_parquet_stream`; decoding runs on tokio's blocking pool.
+pub async fn read_parquet_stream_async(
+    file_path: &Path,
+) -> Result<AsyncRecordStream, DataReaderError,> {
+    let path = file_path.to_path_buf();
+    open_async_stream(move || read_parquet_stream(&path,),).await
+}
+
+/// Streams rows from a Parquet file held in memory. `file_path` is only used to label errors.
+pub fn read_parquet_stream_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
+    Ok(typed_to_json_stream(read_parquet_typed_stream_from_bytes(data, file_path,)?,),)
+}
+
+/// Typed variant of `read_parquet_stream_from_bytes`.
+pub fn read_parquet_typed_stream_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    let builder = ArrowReaderBuilder::try_new(data,).map_err(|e| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    },)?;
+
+    let row_group_rows = row_group_sizes(builder.metadata(),);
+    let reader = builder.build().map_err(|e| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
 
-    Ok(Box::new(ParquetStream::new(
-        reader,
-        file_path.to_path_buf(),
-    ),),)
+    let stream =
+        ParquetStream::new(reader, file_path.to_path_buf(),).with_row_group_sizes(row_group_rows,);
+    Ok(Box::new(fuse_after_error(stream,),),)
+}
+
+/// Streams rows from a seekable Parquet source. The footer-first layout needs random access,
+/// so the source is buffered into memory before decoding.
+pub fn read_parquet_stream_from_reader<R: Read + Seek,>(
+    mut reader: R,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
+    let mut data = Vec::new();
+    reader.seek(SeekFrom::Start(0,),).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    },)?;
+    reader
+        .read_to_end(&mut data,)
+        .map_err(|e| DataReaderError::FileReadError {
+            path:   file_path.to_path_buf(),
+            source: e,
+        },)?;
+    read_parquet_stream_from_bytes(Bytes::from(data,), file_path,)
 }
 
-fn arrow_to_json_value(column: &dyn arrow::array::Array, row_idx: usize,) -> serde_json::Value {
+/// Converts one cell of an Arrow column into an `NcValue`, keeping decimals, timestamps (with
+/// their timezone), dates and binary data exact. Nested lists and structs are converted
+/// recursively; any other type falls back to Arrow's display formatting.
+pub fn arrow_to_nc_value(column: &dyn arrow::array::Array, row_idx: usize,) -> NcValue {
+    use arrow::array::AsArray;
+    use arrow::datatypes::{self as adt, DataType as ArrowType, TimeUnit};
+
     if column.is_null(row_idx,) {
-        return serde_json::Value::Null;
+        return NcValue::Null;
     }
 
     match column.data_type() {
-        arrow::datatypes::DataType::Int64 => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::Int64Array>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Value::Number(val.into(),)
+        ArrowType::Null => NcValue::Null,
+        ArrowType::Boolean => NcValue::Boolean(column.as_boolean().value(row_idx,),),
+        ArrowType::Int8 => {
+            NcValue::Integer(column.as_primitive::<adt::Int8Type>().value(row_idx,) as i64,)
+        },
+        ArrowType::Int16 => {
+            NcValue::Integer(column.as_primitive::<adt::Int16Type>().value(row_idx,) as i64,)
         },
-        arrow::datatypes::DataType::Int32 => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::Int32Array>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Value::Number(val.into(),)
+        ArrowType::Int32 => {
+            NcValue::Integer(column.as_primitive::<adt::Int32Type>().value(row_idx,) as i64,)
         },
-        arrow::datatypes::DataType::Float64 => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::Float64Array>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Number::from_f64(val,)
-                .map(serde_json::Value::Number,)
-                .unwrap_or(serde_json::Value::Null,)
+        ArrowType::Int64 => {
+            NcValue::Integer(column.as_primitive::<adt::Int64Type>().value(row_idx,),)
         },
-        arrow::datatypes::DataType::Float32 => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::Float32Array>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Number::from_f64(val as f64,)
-                .map(serde_json::Value::Number,)
-                .unwrap_or(serde_json::Value::Null,)
+        ArrowType::UInt8 => {
+            NcValue::Integer(column.as_primitive::<adt::UInt8Type>().value(row_idx,) as i64,)
         },
-        arrow::datatypes::DataType::Boolean => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::BooleanArray>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Value::Bool(val,)
+        ArrowType::UInt16 => {
+            NcValue::Integer(column.as_primitive::<adt::UInt16Type>().value(row_idx,) as i64,)
         },
-        arrow::datatypes::DataType::Utf8 => {
-            let val = column
-                .as_any()
-                .downcast_ref::<arrow::array::StringArray>()
-                .unwrap()
-                .value(row_idx,);
-            serde_json::Value::String(val.to_string(),)
+        ArrowType::UInt32 => {
+            NcValue::Integer(column.as_primitive::<adt::UInt32Type>().value(row_idx,) as i64,)
         },
-        arrow::datatypes::DataType::Date32 => {
-            let days = column
-                .as_any()
-                .downcast_ref::<arrow::array::Date32Array>()
-                .unwrap()
-                .value(row_idx,);
-            NaiveDate::from_ymd_opt(1970, 1, 1,)
-                .unwrap()
-                .checked_add_days(chrono::Days::new(days as u64,),)
-                .map_or_else(
-                    || serde_json::Value::Null,
-                    |d| serde_json::Value::String(d.to_string(),),
-                )
+        ArrowType::UInt64 => {
+            let val = column.as_primitive::<adt::UInt64Type>().value(row_idx,);
+            i64::try_from(val,).map_or(NcValue::BigInteger(val as i128,), NcValue::Integer,)
         },
-        arrow::datatypes::DataType::Timestamp(_, _,) => {
-            // Simplified timestamp handling for now, can be improved to match existing logic if
-            // needed
-            let ts_ns = column
-                .as_any()
-                .downcast_ref::<arrow::array::TimestampNanosecondArray>()
-                .map(|a| a.value(row_idx,),)
-                .or_else(|| {
-                    column
-                        .as_any()
-                        .downcast_ref::<arrow::array::TimestampMicrosecondArray>()
-                        .map(|a| a.value(row_idx,) * 1000,)
-                },)
-                .or_else(|| {
-                    column
-                        .as_any()
-                        .downcast_ref::<arrow::array::TimestampMillisecondArray>()
-                        .map(|a| a.value(row_idx,) * 1_000_000,)
-                },)
-                .or_else(|| {
-                    column
-                        .as_any()
-                        .downcast_ref::<arrow::array::TimestampSecondArray>()
-                        .map(|a| a.value(row_idx,) * 1_000_000_000,)
-                },);
-
-            match ts_ns {
-                Some(ns,) => {
-                    let dt = DateTime::from_timestamp_nanos(ns,);
-                    serde_json::Value::String(dt.to_string(),)
+        ArrowType::Float16 => NcValue::Float(
+            column
+                .as_primitive::<adt::Float16Type>()
+                .value(row_idx,)
+                .to_f64(),
+        ),
+        ArrowType::Float32 => {
+            NcValue::Float(column.as_primitive::<adt::Float32Type>().value(row_idx,) as f64,)
+        },
+        ArrowType::Float64 => {
+            NcValue::Float(column.as_primitive::<adt::Float64Type>().value(row_idx,),)
+        },
+        ArrowType::Decimal128(_, scale,) => NcValue::Decimal {
+            unscaled: column.as_primitive::<adt::Decimal128Type>().value(row_idx,),
+            scale:    *scale,
+        },
+        ArrowType::Decimal256(_, scale,) => {
+            let val = column.as_primitive::<adt::Decimal256Type>().value(row_idx,);
+            match val.to_i128() {
+                Some(unscaled,) => NcValue::Decimal {
+                    unscaled,
+                    scale: *scale,
                 },
-                None => serde_json::Value::String(format!("{:?}", column.data_type()),),
+                // Beyond i128: keep the exact digits as text
+                None => NcValue::String(display_value(column, row_idx,),),
+            }
+        },
+        ArrowType::Utf8 => NcValue::String(column.as_string::<i32>().value(row_idx,).to_string(),),
+        ArrowType::LargeUtf8 => {
+            NcValue::String(column.as_string::<i64>().value(row_idx,).to_string(),)
+        },
+        ArrowType::Utf8View => {
+            NcValue::String(column.as_string_view().value(row_idx,).to_string(),)
+        },
+        ArrowType::Binary => NcValue::Binary(column.as_binary::<i32>().value(row_idx,).to_vec(),),
+        ArrowType::LargeBinary => {
+            NcValue::Binary(column.as_binary::<i64>().value(row_idx,).to_vec(),)
+        },
+        ArrowType::BinaryView => NcValue::Binary(column.as_binary_view().value(row_idx,).to_vec(),),
+        ArrowType::FixedSizeBinary(_,) => {
+            NcValue::Binary(column.as_fixed_size_binary().value(row_idx,).to_vec(),)
+        },
+        ArrowType::Date32 => column
+            .as_primitive::<adt::Date32Type>()
+            .value_as_date(row_idx,)
+            .map_or(NcValue::Null, NcValue::Date,),
+        ArrowType::Date64 => column
+            .as_primitive::<adt::Date64Type>()
+            .value_as_date(row_idx,)
+            .map_or(NcValue::Null, NcValue::Date,),
+        ArrowType::Timestamp(unit, tz,) => {
+            let naive_utc = match unit {
+                TimeUnit::Second => column
+                    .as_primitive::<adt::TimestampSecondType>()
+                    .value_as_datetime(row_idx,),
+                TimeUnit::Millisecond => column
+                    .as_primitive::<adt::TimestampMillisecondType>()
+                    .value_as_datetime(row_idx,),
+                TimeUnit::Microsecond => column
+                    .as_primitive::<adt::TimestampMicrosecondType>()
+                    .value_as_datetime(row_idx,),
+                TimeUnit::Nanosecond => column
+                    .as_primitive::<adt::TimestampNanosecondType>()
+                    .value_as_datetime(row_idx,),
+            };
+            match (naive_utc, tz,) {
+                (None, _,) => NcValue::Null,
+                (Some(naive,), None,) => NcValue::LocalTimestamp(naive,),
+                (Some(naive,), Some(tz,),) => NcValue::Timestamp(with_timezone(naive, tz,),),
             }
         },
-        _ => serde_json::Value::String(format!("{:?}", column.data_type()),),
+        ArrowType::List(_,) => {
+            let values = column.as_list::<i32>().value(row_idx,);
+            NcValue::Array((0..values.len()).map(|i| arrow_to_nc_value(&values, i,),).collect(),)
+        },
+        ArrowType::LargeList(_,) => {
+            let values = column.as_list::<i64>().value(row_idx,);
+            NcValue::Array((0..values.len()).map(|i| arrow_to_nc_value(&values, i,),).collect(),)
+        },
+        ArrowType::FixedSizeList(_, _,) => {
+            let values = column.as_fixed_size_list().value(row_idx,);
+            NcValue::Array((0..values.len()).map(|i| arrow_to_nc_value(&values, i,),).collect(),)
+        },
+        ArrowType::Struct(fields,) => {
+            let struct_array = column.as_struct();
+            NcValue::Object(
+                fields
+                    .iter()
+                    .zip(struct_array.columns(),)
+                    .map(|(field, child,)| {
+                        (field.name().to_string(), arrow_to_nc_value(child, row_idx,),)
+                    },)
+                    .collect(),
+            )
+        },
+        _ => NcValue::String(display_value(column, row_idx,),),
     }
 }
 
+/// Interprets a naive UTC timestamp in an Arrow timezone, which is either a fixed offset
+/// (`+02:00`) or an IANA name (`Europe/Berlin`). Unknown timezones fall back to UTC.
+fn with_timezone(naive_utc: chrono::NaiveDateTime, tz: &str,) -> DateTime<FixedOffset,> {
+    use chrono::{Offset, TimeZone};
+
+    let offset = FixedOffset::from_str(tz,).unwrap_or_else(|_| {
+        Tz::from_str(tz,)
+            .unwrap_or(Tz::UTC,)
+            .offset_from_utc_datetime(&naive_utc,)
+            .fix()
+    },);
+    offset.from_utc_datetime(&naive_utc,)
+}
+
+fn display_value(column: &dyn arrow::array::Array, row_idx: usize,) -> String {
+    arrow::util::display::array_value_to_string(column, row_idx,)
+        .unwrap_or_else(|_| format!("{:?}", column.data_type()),)
+}
+
 pub fn read_parquet_data(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<ParquetData, DataReaderError,> {
-    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
-        path:   file_path.to_path_buf(),
-        source: e,
-    },)?;
-    let file_metanc_obj = file
+    read_parquet_data_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_parquet_data` with the limits of `config`. Cancellation and the deadline are checked
+/// before each batch of the sample, and the sampled rows count towards `max_records`; the row
+/// count comes from the footer without reading any rows.
+pub fn read_parquet_data_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<ParquetData, DataReaderError,> {
+    let open = || {
+        File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
+            path:   file_path.to_path_buf(),
+            source: e,
+        },)
+    };
+    let file_metanc_obj = open()?
         .metadata()
         .map_err(|e| DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
             source: e,
         },)?;
     let file_size = file_metanc_obj.len();
+    summarize_parquet(open, file_path, file_size, head, &config.budget(file_path,),)
+}
 
-    let reader = SerializedFileReader::new(file,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+/// `read_parquet_data_with_config` for a Parquet file held in memory. `file_path` is only used
+/// to label errors.
+pub fn read_parquet_data_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<ParquetData, DataReaderError,> {
+    let file_size = data.len() as u64;
+    let budget = config.budget(file_path,);
+    summarize_parquet(|| Ok(data.clone(),), file_path, file_size, head, &budget,)
+}
+
+/// Reads the column metadata of the footer `open` gives access to, and the first `head` rows.
+fn summarize_parquet<T, F,>(
+    open: F,
+    file_path: &Path,
+    file_size: u64,
+    head: Option<usize,>,
+    budget: &ReadBudget,
+) -> Result<ParquetData, DataReaderError,>
+where
+    T: ChunkReader + 'static,
+    F: Fn() -> Result<T, DataReaderError,>,
+{
+    let reader = SerializedFileReader::new(open()?,).map_err(|e| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
     let metadata = reader.metadata();
     let num_rows = metadata.file_metadata().num_rows();
@@ -328,34 +609,36 @@ pub fn read_parquet_data(
 
     let mut sample_rows: Option<Vec<ParquetRow,>,> = None;
     if let Some(num_rows_to_read,) = head {
-        let file_for_arrow =
-            File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
-                path:   file_path.to_path_buf(),
-                source: e,
-            },)?;
-        let builder = ArrowReaderBuilder::try_new(file_for_arrow,).map_err(|e| {
+        let builder = ArrowReaderBuilder::try_new(open()?,).map_err(|e| {
             DataReaderError::ParseError {
-                path:   file_path.to_path_buf(),
-                source: Box::new(e,),
+                path:     file_path.to_path_buf(),
+                source:   Box::new(e,),
+                position: None,
             }
         },)?;
         let mut reader = builder.build().map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
 
         let mut rows_read = 0;
         let mut collected_rows: Vec<ParquetRow,> = Vec::new();
 
-        while let Some(batch,) =
-            reader
-                .next()
-                .transpose()
-                .map_err(|e| DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
-                },)?
-        {
+        loop {
+            budget.check()?;
+            let Some(batch,) =
+                reader
+                    .next()
+                    .transpose()
+                    .map_err(|e| DataReaderError::ParseError {
+                        path:     file_path.to_path_buf(),
+                        source:   Box::new(e,),
+                        position: None,
+                    },)?
+            else {
+                break;
+            };
             for row_idx in 0..batch.num_rows() {
                 if rows_read >= num_rows_to_read {
                     break;
@@ -415,6 +698,7 @@ pub fn read_parquet_data(
                 collected_rows.push(ParquetRow(current_row_map,),);
                 rows_read += 1;
             }
+            budget.check_records(rows_read as u64,)?;
             if rows_read >= num_rows_to_read {
                 break;
             }
@@ -433,6 +717,17 @@ pub fn read_parquet_data(
 pub fn read_parquet_nc_for_analysis(
     file_path: &Path,
 ) -> Result<ParquetDataForAnalysis, DataReaderError,> {
+    read_parquet_nc_for_analysis_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_parquet_nc_for_analysis` with an explicit `ReadConfig`. The distinct-value sets grow
+/// with every row, so cancellation and the deadline are checked before each batch and the rows
+/// scanned count towards `config.limits.max_records`.
+pub fn read_parquet_nc_for_analysis_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<ParquetDataForAnalysis, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
@@ -440,8 +735,9 @@ pub fn read_parquet_nc_for_analysis(
 
     let reader_builder =
         ArrowReaderBuilder::try_new(file,).map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
 
     let num_rows = reader_builder.metadata().file_metadata().num_rows();
@@ -451,29 +747,39 @@ pub fn read_parquet_nc_for_analysis(
     let mut arrow_reader = reader_builder
         .build()
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
 
     let mut column_null_counts: HashMap<String, u64,> = HashMap::new();
-    let mut column_distinct_values: HashMap<String, std::collections::HashSet<serde_json::Value,>,> =
+    let mut column_distinct_values: HashMap<String, HashSet<serde_json::Value,>,> =
         HashMap::new();
+    let mut capped_columns: HashSet<String,> = HashSet::new();
 
     for field in schema.fields() {
         let col_name = field.name().to_string();
         column_null_counts.insert(col_name.clone(), 0,);
-        column_distinct_values.insert(col_name, std::collections::HashSet::new(),);
+        column_distinct_values.insert(col_name, HashSet::new(),);
     }
 
-    while let Some(record_batch,) =
-        arrow_reader
-            .next()
-            .transpose()
-            .map_err(|e| DataReaderError::ParseError {
-                path:   file_path.to_path_buf(),
-                source: Box::new(e,),
-            },)?
-    {
+    let mut rows_scanned = 0u64;
+    loop {
+        budget.check()?;
+        let Some(record_batch,) =
+            arrow_reader
+                .next()
+                .transpose()
+                .map_err(|e| DataReaderError::ParseError {
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
+                },)?
+        else {
+            break;
+        };
+        rows_scanned += record_batch.num_rows() as u64;
+        budget.check_records(rows_scanned,)?;
         for (idx, field,) in schema.fields().iter().enumerate() {
             let column_name = field.name().to_string();
             let array = record_batch.column(idx,);
@@ -482,6 +788,10 @@ pub fn read_parquet_nc_for_analysis(
 
             let distinct_set = column_distinct_values.get_mut(&column_name,).unwrap();
             for i in 0..array.len() {
+                if distinct_set.len() >= MAX_DISTINCT_VALUES {
+                    capped_columns.insert(column_name.clone(),);
+                    break;
+                }
                 if !array.is_null(i,) {
                     let value = match array.data_type() {
                         arrow::datatypes::DataType::Int64 => {
@@ -526,7 +836,7 @@ pub fn read_parquet_nc_for_analysis(
                                 .unwrap();
                             serde_json::Value::from(arr.value(i,).to_string(),)
                         },
-                        _ => serde_json::Value::String(format!("{:?}", array),),
+                        _ => serde_json::Value::String(display_value(array, i,),),
                     };
                     distinct_set.insert(value,);
                 }
@@ -552,40 +862,61 @@ pub fn read_parquet_nc_for_analysis(
         column_uniqueness_percentages.insert(col_name, uniqueness_percentage,);
     }
 
+    let mut approximate_distinct_counts: Vec<String,> = capped_columns.into_iter().collect();
+    approximate_distinct_counts.sort();
+
     Ok(ParquetDataForAnalysis {
         num_rows,
         column_null_counts,
         column_distinct_counts,
         column_uniqueness_percentages,
+        approximate_distinct_counts,
     },)
 }
 
 pub fn read_full_parquet_content(
     file_path: &Path,
 ) -> Result<Vec<HashMap<String, String,>,>, DataReaderError,> {
+    read_full_parquet_content_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_full_parquet_content` with the limits of `config`, checked before each batch.
+pub fn read_full_parquet_content_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<Vec<HashMap<String, String,>,>, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
     let builder = ArrowReaderBuilder::try_new(file,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
     let mut reader = builder.build().map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
 
     let mut all_rows: Vec<HashMap<String, String,>,> = Vec::new();
 
-    while let Some(batch,) = reader
-        .next()
-        .transpose()
-        .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
-        },)?
-    {
+    loop {
+        budget.check()?;
+        let Some(batch,) = reader
+            .next()
+            .transpose()
+            .map_err(|e| DataReaderError::ParseError {
+                path:     file_path.to_path_buf(),
+                source:   Box::new(e,),
+                position: None,
+            },)?
+        else {
+            break;
+        };
+        budget.check_records((all_rows.len() + batch.num_rows()) as u64,)?;
         for row_idx in 0..batch.num_rows() {
             let mut current_row_map = HashMap::new();
             for col_idx in 0..batch.num_columns() {
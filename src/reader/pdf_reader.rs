@@ -1,8 +1,12 @@
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize}; // Added Serialize and Deserialize
 
 use crate::error::DataReaderError;
+use crate::reader::limits::{BudgetedReader, read_error};
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct PdfData {
@@ -14,13 +18,35 @@ pub struct PdfData {
 }
 
 pub fn read_pdf_text(file_path: &Path, head: Option<usize,>,) -> Result<PdfData, DataReaderError,> {
+    read_pdf_text_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_pdf_text` within the limits of `config`. The file is read into memory through a
+/// `BudgetedReader`; the limits are checked again once the text is extracted.
+pub fn read_pdf_text_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<PdfData, DataReaderError,> {
     let num_lines_to_extract = head.unwrap_or(0,);
 
+    let budget = config.budget(file_path,);
+    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    },)?;
+    let mut bytes = Vec::new();
+    BudgetedReader::new(file, budget.clone(),)
+        .read_to_end(&mut bytes,)
+        .map_err(|e| read_error(file_path, e,),)?;
+
     let content =
-        pdf_extract::extract_text(file_path,).map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+        pdf_extract::extract_text_from_mem(&bytes,).map_err(|e| DataReaderError::ParseError {
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
+    budget.check()?;
 
     let file_metadata =
         std::fs::metadata(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use crate::reader::coercion::ValueCoercion;
+use crate::reader::csv_dialect::CsvOptions;
+use crate::reader::error_policy::{ErrorPolicy, RejectCollector, RejectReport};
+use crate::reader::filter::RecordFilter;
+use crate::reader::limits::{CancellationToken, ReadBudget, ReadLimits};
+use crate::reader::sample::SampleOptions;
+use crate::reader::type_inference::InferenceOptions;
+
+/// Settings shared by the readers for one read. `FileReaderOptions` carries one, and readers
+/// that honor a setting take it through the `*_with_config` functions.
+#[derive(Debug, Clone, Default,)]
+pub struct ReadConfig {
+    /// What to do with records that fail to parse.
+    pub error_policy: ErrorPolicy,
+    /// Where records skipped under a lenient `error_policy` are collected.
+    pub rejects:      RejectCollector,
+    /// Resource limits for untrusted input.
+    pub limits:       ReadLimits,
+    /// Cancels every read using this config (and its clones) when triggered.
+    pub cancel:       CancellationToken,
+    /// `--where` / `--select` applied to record streams.
+    pub filter:       RecordFilter,
+    /// CSV dialect settings that override detection.
+    pub csv:          CsvOptions,
+    /// How text fields (CSV) are read as numbers, booleans and dates.
+    pub inference:    InferenceOptions,
+    /// Null values, boolean words and declared types of text values (CSV, XML, spreadsheets).
+    pub coercion:     ValueCoercion,
+    /// The rows summaries keep (CSV).
+    pub sample:       SampleOptions,
+}
+
+impl ReadConfig {
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy,) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: ReadLimits,) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: RecordFilter,) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_csv(mut self, csv: CsvOptions,) -> Self {
+        self.csv = csv;
+        self
+    }
+
+    pub fn with_inference(mut self, inference: InferenceOptions,) -> Self {
+        self.inference = inference;
+        self
+    }
+
+    pub fn with_coercion(mut self, coercion: ValueCoercion,) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    pub fn with_sample(mut self, sample: SampleOptions,) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    /// Starts the limit budget of one file read; the timeout runs from this call.
+    pub fn budget(&self, file_path: &Path,) -> ReadBudget {
+        ReadBudget::new(file_path, &self.limits, &self.cancel,)
+    }
+
+    /// The rejects of one file, or `None` under `FailFast`, where nothing is ever skipped.
+    pub fn reject_report(&self, file_path: &Path,) -> Option<RejectReport,> {
+        match self.error_policy {
+            ErrorPolicy::FailFast => None,
+            _ => Some(self.rejects.report_for(file_path,),),
+        }
+    }
+}
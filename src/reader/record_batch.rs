@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType as ArrowType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::json::reader::Decoder;
+use arrow::record_batch::RecordBatch;
+use nc_schema::{DataType, merge_nc_types};
+use serde_json::Value;
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::RecordStream;
+use crate::record::NcValue;
+
+pub type RecordBatchStream =
+    Box<dyn Iterator<Item = Result<RecordBatch, DataReaderError,>,> + Send,>;
+
+/// Rows per `RecordBatch` when the caller does not choose a size.
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// Maps an inferred `nc_schema` type to the Arrow type used for its column.
+///
+/// Dates are `Date32` and binary data `Binary`. Timestamps are microseconds in UTC: values with
+/// an offset keep their instant, and values without one are read as UTC. Unions that are only
+/// numeric widen to `Float64`; any other mixed union, and columns whose type could not be
+/// inferred, fall back to `Utf8` (scalars are rendered as text).
+pub fn nc_type_to_arrow(nc_type: &DataType,) -> ArrowType {
+    match nc_type {
+        DataType::Boolean => ArrowType::Boolean,
+        DataType::Integer => ArrowType::Int64,
+        DataType::Float | DataType::Number => ArrowType::Float64,
+        DataType::Date => ArrowType::Date32,
+        DataType::Timestamp => ArrowType::Timestamp(TimeUnit::Microsecond, Some("UTC".into(),),),
+        DataType::Binary => ArrowType::Binary,
+        DataType::Array(element,) => {
+            ArrowType::List(Arc::new(Field::new("item", nc_type_to_arrow(element,), true,),),)
+        },
+        DataType::Object(properties,) => {
+            let mut names: Vec<&String,> = properties.keys().collect();
+            names.sort();
+            ArrowType::Struct(
+                names
+                    .into_iter()
+                    .map(|name| Field::new(name, nc_type_to_arrow(&properties[name],), true,),)
+                    .collect(),
+            )
+        },
+        DataType::Union(variants,) => {
+            let non_null: Vec<&DataType,> =
+                variants.iter().filter(|v| !matches!(v, DataType::Null),).collect();
+            match non_null.as_slice() {
+                [single,] => nc_type_to_arrow(single,),
+                many if !many.is_empty()
+                    && many.iter().all(|v| {
+                        matches!(v, DataType::Integer | DataType::Float | DataType::Number)
+                    },) =>
+                {
+                    ArrowType::Float64
+                },
+                _ => ArrowType::Utf8,
+            }
+        },
+        _ => ArrowType::Utf8,
+    }
+}
+
+/// Builds an Arrow schema with one nullable field per column, in `columns` order. Columns
+/// missing from `types` are typed as `Utf8`.
+pub fn arrow_schema_from_nc_types(
+    columns: &[String],
+    types: &HashMap<String, DataType,>,
+) -> SchemaRef {
+    let fields: Vec<Field,> = columns
+        .iter()
+        .map(|name| {
+            let arrow_type = types.get(name,).map_or(ArrowType::Utf8, nc_type_to_arrow,);
+            Field::new(name, arrow_type, true,)
+        },)
+        .collect();
+    Arc::new(Schema::new(fields,),)
+}
+
+/// Collects column names, in first-seen order, and their merged types from a stream of JSON
+/// object records. `infer` types a single field value.
+pub fn infer_record_columns(
+    records: RecordStream,
+    infer: fn(&Value,) -> DataType,
+) -> Result<(Vec<String,>, HashMap<String, DataType,>,), DataReaderError,> {
+    let mut columns = ColumnTypes::default();
+    for record in records {
+        if let Value::Object(fields,) = record? {
+            for (name, value,) in &fields {
+                columns.observe(name, infer(value,),);
+            }
+        }
+    }
+    Ok((columns.names, columns.types,),)
+}
+
+/// `infer_record_columns` for typed records, typed with `NcValue::data_type`, so date,
+/// timestamp and binary columns keep their type instead of the text JSON renders them as.
+pub fn infer_typed_columns(
+    records: impl Iterator<Item = Result<NcValue, DataReaderError,>,>,
+) -> Result<(Vec<String,>, HashMap<String, DataType,>,), DataReaderError,> {
+    let mut columns = ColumnTypes::default();
+    for record in records {
+        if let NcValue::Object(fields,) = record? {
+            for (name, value,) in &fields {
+                columns.observe(name, value.data_type(),);
+            }
+        }
+    }
+    Ok((columns.names, columns.types,),)
+}
+
+#[derive(Default,)]
+struct ColumnTypes {
+    names: Vec<String,>,
+    types: HashMap<String, DataType,>,
+}
+
+impl ColumnTypes {
+    fn observe(&mut self, name: &str, current_type: DataType,) {
+        match self.types.get_mut(name,) {
+            Some(prev,) => *prev = merge_nc_types(prev.clone(), current_type,),
+            None => {
+                self.names.push(name.to_string(),);
+                self.types.insert(name.to_string(), current_type,);
+            },
+        }
+    }
+}
+
+/// Groups a stream of JSON object records into `RecordBatch`es of at most `batch_size` rows.
+/// Fields missing from a record become nulls; fields not in `schema` are ignored. Date,
+/// timestamp and binary columns are read from the ISO 8601 and hex text of `NcValue::to_json`.
+pub fn json_records_to_batches(
+    schema: SchemaRef,
+    records: RecordStream,
+    batch_size: usize,
+    file_path: &Path,
+) -> Result<RecordBatchStream, DataReaderError,> {
+    let batch_size = batch_size.max(1,);
+    let decoder = arrow::json::ReaderBuilder::new(schema,)
+        .with_batch_size(batch_size,)
+        .with_coerce_primitive(true,)
+        .build_decoder()
+        .map_err(|e| DataReaderError::ParseError {
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
+        },)?;
+
+    Ok(Box::new(JsonBatchIter {
+        records,
+        decoder,
+        batch_size,
+        path: file_path.to_path_buf(),
+        done: false,
+    },),)
+}
+
+struct JsonBatchIter {
+    records:    RecordStream,
+    decoder:    Decoder,
+    batch_size: usize,
+    path:       PathBuf,
+    done:       bool,
+}
+
+impl JsonBatchIter {
+    fn parse_error(&self, e: arrow::error::ArrowError,) -> DataReaderError {
+        DataReaderError::ParseError {
+            path:     self.path.clone(),
+            source:   Box::new(e,),
+            position: None,
+        }
+    }
+}
+
+impl Iterator for JsonBatchIter {
+    type Item = Result<RecordBatch, DataReaderError,>;
+
+    fn next(&mut self,) -> Option<Self::Item,> {
+        if self.done {
+            return None;
+        }
+
+        let mut rows = Vec::with_capacity(self.batch_size,);
+        while rows.len() < self.batch_size {
+            match self.records.next() {
+                Some(Ok(record,),) => rows.push(record,),
+                Some(Err(e,),) => {
+                    self.done = true;
+                    return Some(Err(e,),);
+                },
+                None => {
+                    self.done = true;
+                    break;
+                },
+            }
+        }
+        if rows.is_empty() {
+            return None;
+        }
+
+        if let Err(e,) = self.decoder.serialize(&rows,) {
+            self.done = true;
+            return Some(Err(self.parse_error(e,),),);
+        }
+        match self.decoder.flush() {
+            Ok(Some(batch,),) => Some(Ok(batch,),),
+            Ok(None,) => None,
+            Err(e,) => {
+                self.done = true;
+                Some(Err(self.parse_error(e,),),)
+            },
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use arrow::datatypes::SchemaRef;
+use bytes::{Buf, Bytes};
+
+use crate::error::DataReaderError;
+use crate::file_reader::FileFormat;
+use crate::nc_reader_result::{DataReaderResult, RecordStream, TypedRecordStream};
+use crate::output::OutputFormat;
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::RecordBatchStream;
+use crate::record::NcValue;
+
+/// What a `FormatReader` reads: a file on disk, or data with no backing file. The path of data
+/// without a file only labels errors and rejects, and picks the format by its extension.
+pub enum ReadSource {
+    /// A file on disk.
+    File(PathBuf,),
+    /// Data held in memory (archive entries, buffered stdin).
+    Bytes(Bytes, PathBuf,),
+    /// A source that can only be read once, front to back (stdin, sockets).
+    Reader(Box<dyn Read + Send,>, PathBuf,),
+}
+
+impl ReadSource {
+    pub fn file(file_path: &Path,) -> Self {
+        ReadSource::File(file_path.to_path_buf(),)
+    }
+
+    /// The path of the file, or the path labelling data without one.
+    pub fn path(&self,) -> &Path {
+        match self {
+            ReadSource::File(path,) => path,
+            ReadSource::Bytes(_, path,) | ReadSource::Reader(_, path,) => path,
+        }
+    }
+
+    /// The path of a `File` source. Readers that can only read files on disk call this, so any
+    /// other source fails instead of being read some other way.
+    pub fn require_file(&self, reader_name: &str,) -> Result<&Path, DataReaderError,> {
+        match self {
+            ReadSource::File(path,) => Ok(path,),
+            other => Err(DataReaderError::UnsupportedFileFormat(format!(
+                "Format {} can only be read from a file, not from {}",
+                reader_name,
+                other.path().display()
+            ),),),
+        }
+    }
+
+    /// Opens the source to be read front to back.
+    pub fn into_reader(self,) -> Result<Box<dyn Read + Send,>, DataReaderError,> {
+        match self {
+            ReadSource::File(path,) => match std::fs::File::open(&path,) {
+                Ok(file,) => Ok(Box::new(file,),),
+                Err(e,) => Err(DataReaderError::FileReadError { path, source: e, },),
+            },
+            ReadSource::Bytes(data, _,) => Ok(Box::new(data.reader(),),),
+            ReadSource::Reader(reader, _,) => Ok(reader,),
+        }
+    }
+
+    /// Reads the whole source into memory, for formats that need random access (Parquet keeps
+    /// its footer last).
+    pub fn into_bytes(self,) -> Result<Bytes, DataReaderError,> {
+        let (mut reader, path,) = match self {
+            ReadSource::File(path,) => {
+                return std::fs::read(&path,)
+                    .map(Bytes::from,)
+                    .map_err(|e| DataReaderError::FileReadError { path, source: e, },);
+            },
+            ReadSource::Bytes(data, _,) => return Ok(data,),
+            ReadSource::Reader(reader, path,) => (reader, path,),
+        };
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data,)
+            .map_err(|e| DataReaderError::FileReadError { path, source: e, },)?;
+        Ok(Bytes::from(data,),)
+    }
+}
+
+/// A pluggable reader for one file format.
+///
+/// Built-in formats are implemented in `crate::reader::builtin`; downstream crates can add
+/// their own formats by implementing this trait and calling [`register_format_reader`].
+///
+/// There is one method per operation. Each takes the `ReadSource` to read and the `ReadConfig`
+/// of the read, whose error policy, limits and filter the caller applies to the records a
+/// reader returns; the reader applies the settings of its own parsing (the CSV dialect, null
+/// values and field types) and may read only the columns `config.filter` needs. Operations a
+/// format does not support return `None`, and sources a reader cannot read fail, so a reader
+/// is never silently read without its config.
+pub trait FormatReader: Send + Sync {
+    /// Canonical name, matched case-insensitively against `--file-type` overrides.
+    fn name(&self,) -> &str;
+
+    /// Lowercase file extensions (without the dot) handled by this reader.
+    fn extensions(&self,) -> &[&str];
+
+    /// Returns true if the leading bytes of a file identify this format.
+    fn matches_magic(&self, _header: &[u8],) -> bool {
+        false
+    }
+
+    /// Whether the format is text, which an explicit input encoding transcodes. Binary formats
+    /// refuse one rather than being corrupted by it.
+    fn is_text(&self,) -> bool {
+        false
+    }
+
+    /// The `FileFormat` reported for files handled by this reader.
+    fn file_format(&self,) -> FileFormat {
+        FileFormat::Other(self.name().to_string(),)
+    }
+
+    /// Reads the source into its summary representation.
+    fn read_data(
+        &self,
+        source: ReadSource,
+        head: Option<usize,>,
+        config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,>;
+
+    /// Opens a record stream over the source, or `None` if the format is not record based.
+    /// Records only need the top-level columns of `config.filter.required_columns()`; readers
+    /// that can skip columns at the source, like Parquet, read only those.
+    fn read_stream(
+        &self,
+        _source: ReadSource,
+        _config: &ReadConfig,
+    ) -> Result<Option<RecordStream,>, DataReaderError,> {
+        Ok(None,)
+    }
+
+    /// Opens a typed record stream over the source. The default converts the JSON records from
+    /// `read_stream`; readers whose sources carry richer types than JSON should override it.
+    fn read_typed_stream(
+        &self,
+        source: ReadSource,
+        config: &ReadConfig,
+    ) -> Result<Option<TypedRecordStream,>, DataReaderError,> {
+        Ok(self.read_stream(source, config,)?.map(|stream| {
+            Box::new(stream.map(|record| record.map(NcValue::from,),),) as TypedRecordStream
+        },),)
+    }
+
+    /// Reads the source as Arrow record batches of at most `batch_size` rows, or `None` if the
+    /// format is not tabular.
+    fn read_batches(
+        &self,
+        _source: ReadSource,
+        _batch_size: usize,
+        _config: &ReadConfig,
+    ) -> Result<Option<(SchemaRef, RecordBatchStream,),>, DataReaderError,> {
+        Ok(None,)
+    }
+
+    /// Reads the full raw content of the source, serialized in `output_format`.
+    fn read_raw(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        _output_format: OutputFormat,
+        _config: &ReadConfig,
+    ) -> Result<String, DataReaderError,> {
+        Err(DataReaderError::InternalError(format!(
+            "Unsupported file format for raw content output: {}",
+            source.path().display()
+        ),),)
+    }
+}
+
+/// An ordered set of format readers. Later registrations take precedence over earlier ones,
+/// so a custom reader can replace a built-in one for the same extension.
+#[derive(Clone, Default,)]
+pub struct FormatRegistry {
+    readers: Vec<Arc<dyn FormatReader,>,>,
+}
+
+impl FormatRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_builtin_readers() -> Self {
+        let mut registry = Self::new();
+        for reader in crate::reader::builtin::builtin_readers() {
+            registry.register(reader,);
+        }
+        registry
+    }
+
+    pub fn register(&mut self, reader: Arc<dyn FormatReader,>,) {
+        self.readers.push(reader,);
+    }
+
+    pub fn readers(&self,) -> impl Iterator<Item = &Arc<dyn FormatReader,>,> {
+        self.readers.iter().rev()
+    }
+
+    pub fn by_extension(&self, extension: &str,) -> Option<Arc<dyn FormatReader,>,> {
+        self.readers()
+            .find(|r| r.extensions().iter().any(|e| e.eq_ignore_ascii_case(extension,),),)
+            .cloned()
+    }
+
+    pub fn by_name(&self, name: &str,) -> Option<Arc<dyn FormatReader,>,> {
+        self.readers()
+            .find(|r| r.name().eq_ignore_ascii_case(name,),)
+            .cloned()
+    }
+
+    pub fn by_magic(&self, header: &[u8],) -> Option<Arc<dyn FormatReader,>,> {
+        self.readers().find(|r| r.matches_magic(header,),).cloned()
+    }
+
+    pub fn by_format(&self, format: &FileFormat,) -> Option<Arc<dyn FormatReader,>,> {
+        self.readers().find(|r| r.file_format() == *format,).cloned()
+    }
+}
+
+static GLOBAL_REGISTRY: OnceLock<RwLock<FormatRegistry,>,> = OnceLock::new();
+
+/// The process-wide registry that `file_reader` dispatches through.
+pub fn global_registry() -> &'static RwLock<FormatRegistry,> {
+    GLOBAL_REGISTRY.get_or_init(|| RwLock::new(FormatRegistry::with_builtin_readers(),),)
+}
+
+/// Registers a reader in the global registry.
+pub fn register_format_reader(reader: Arc<dyn FormatReader,>,) {
+    global_registry()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner(),)
+        .register(reader,);
+}
+
+/// Returns a snapshot of the global registry, so lookups don't hold the lock while reading.
+pub fn registry_snapshot() -> FormatRegistry {
+    global_registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner(),)
+        .clone()
+}
@@ -0,0 +1,107 @@
+/// Rows kept from the start of a file by default.
+pub const DEFAULT_SAMPLE_FIRST: usize = 1000;
+/// Rows picked at random from the rest of a file by default.
+pub const DEFAULT_SAMPLE_RESERVOIR: usize = 1000;
+
+/// How many rows a summary keeps. Summaries still count every row and infer their schema from
+/// every row; only the rows they return are limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+pub struct SampleOptions {
+    /// Rows kept from the start of the file.
+    pub first:     usize,
+    /// Rows picked uniformly at random from the rows after `first`.
+    pub reservoir: usize,
+    /// Seeds the random picks, so the same file always gives the same sample.
+    pub seed:      u64,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            first:     DEFAULT_SAMPLE_FIRST,
+            reservoir: DEFAULT_SAMPLE_RESERVOIR,
+            seed:      0,
+        }
+    }
+}
+
+/// Keeps the first rows pushed and a reservoir sample of the rest, in bounded memory.
+#[derive(Debug,)]
+pub struct RowSampler<T,> {
+    options:   SampleOptions,
+    first:     Vec<T,>,
+    /// Rows after `first`, with their index so they can be put back in file order.
+    reservoir: Vec<(u64, T,),>,
+    seen:      u64,
+    rng:       SplitMix64,
+}
+
+impl<T,> RowSampler<T,> {
+    pub fn new(options: SampleOptions,) -> Self {
+        Self {
+            options,
+            first: Vec::new(),
+            reservoir: Vec::new(),
+            seen: 0,
+            rng: SplitMix64(options.seed,),
+        }
+    }
+
+    pub fn push(&mut self, row: T,) {
+        let index = self.seen;
+        self.seen += 1;
+        if self.first.len() < self.options.first {
+            self.first.push(row,);
+            return;
+        }
+        if self.reservoir.len() < self.options.reservoir {
+            self.reservoir.push((index, row,),);
+            return;
+        }
+        // Algorithm R: the k-th row after `first` replaces a kept one with probability
+        // reservoir / k.
+        let k = index - self.first.len() as u64;
+        let slot = self.rng.below(k + 1,);
+        if slot < self.options.reservoir as u64 {
+            self.reservoir[slot as usize] = (index, row,);
+        }
+    }
+
+    /// Rows pushed so far.
+    pub fn seen(&self,) -> u64 {
+        self.seen
+    }
+
+    /// The kept rows in file order, and whether they are every row pushed.
+    pub fn finish(mut self,) -> (Vec<T,>, bool,) {
+        let complete = self.seen == (self.first.len() + self.reservoir.len()) as u64;
+        self.reservoir.sort_by_key(|(index, _,)| *index,);
+        self.first.extend(self.reservoir.into_iter().map(|(_, row,)| row,),);
+        (self.first, complete,)
+    }
+}
+
+/// A small, seedable generator; the sample only needs to be unbiased, not unpredictable.
+#[derive(Debug,)]
+struct SplitMix64(u64,);
+
+impl SplitMix64 {
+    fn next_u64(&mut self,) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15,);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9,);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb,);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, without modulo bias.
+    fn below(&mut self, bound: u64,) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
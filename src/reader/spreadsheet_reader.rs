@@ -1,9 +1,20 @@
+use std::fs::File;
+use std::io::{Cursor, Read};
 use std::path::Path;
 
-use calamine::{Data, Range, Reader, open_workbook_auto};
+use arrow::datatypes::SchemaRef;
+use bytes::Bytes;
+use calamine::{Data, Range, Reader, Sheets, open_workbook_auto_from_rs};
 use serde::{Deserialize, Serialize}; // Add this import
+use serde_json::Value;
 
 use crate::error::DataReaderError;
+use crate::reader::coercion::ValueCoercion;
+use crate::reader::limits::{BudgetedReader, ReadBudget, read_error};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::{
+    RecordBatchStream, arrow_schema_from_nc_types, infer_record_columns, json_records_to_batches,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone,)] // Added Serialize and Deserialize
 pub struct SpreadsheetSheetInfo {
@@ -22,6 +33,16 @@ pub struct SpreadsheetData {
 }
 
 pub fn read_spreadsheet_data(file_path: &Path,) -> Result<SpreadsheetData, DataReaderError,> {
+    read_spreadsheet_data_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_spreadsheet_data` within the limits of `config`. The workbook is read through a
+/// `BudgetedReader`, and the limits are checked again before each sheet.
+pub fn read_spreadsheet_data_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<SpreadsheetData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let total_size = std::fs::metadata(file_path,)
         .map_err(|e| DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
@@ -29,15 +50,13 @@ pub fn read_spreadsheet_data(file_path: &Path,) -> Result<SpreadsheetData, DataR
         },)?
         .len();
 
-    let mut workbook = open_workbook_auto(file_path,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
-    },)?;
+    let mut workbook = open_workbook(file_path, &budget,)?;
 
     let mut sheets_info = Vec::new();
     let sheet_names = workbook.sheet_names().to_owned();
 
     for sheet_name in sheet_names {
+        budget.check()?;
         let sheet_content: Option<Result<Range<Data,>, calamine::Error,>,> =
             Some(workbook.worksheet_range(&sheet_name,),);
         match sheet_content {
@@ -62,8 +81,9 @@ pub fn read_spreadsheet_data(file_path: &Path,) -> Result<SpreadsheetData, DataR
             },
             Some(Err(e,),) => {
                 return Err(DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
                 },);
             },
             None => {
@@ -78,3 +98,138 @@ pub fn read_spreadsheet_data(file_path: &Path,) -> Result<SpreadsheetData, DataR
         sheets: sheets_info,
     },)
 }
+
+/// Reads one sheet (the first one if `sheet` is `None`) as Arrow record batches. The first row
+/// holds the column names; column types are inferred from the cell values.
+pub fn read_spreadsheet_batches(
+    file_path: &Path,
+    sheet: Option<&str,>,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    read_spreadsheet_batches_with_config(file_path, sheet, batch_size, &ReadConfig::default(),)
+}
+
+/// `read_spreadsheet_batches` reading text cells that are null values or boolean words of
+/// `config.coercion` as nulls and booleans. Number, boolean and date cells keep their type.
+pub fn read_spreadsheet_batches_with_config(
+    file_path: &Path,
+    sheet: Option<&str,>,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    let budget = config.budget(file_path,);
+    let mut workbook = open_workbook(file_path, &budget,)?;
+
+    let sheet_name = match sheet {
+        Some(name,) => name.to_string(),
+        None => workbook.sheet_names().first().cloned().ok_or_else(|| {
+            DataReaderError::InternalError(format!(
+                "Spreadsheet has no sheets: {}",
+                file_path.display()
+            ),)
+        },)?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet_name,)
+        .map_err(|e| DataReaderError::ParseError {
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
+        },)?;
+    budget.check()?;
+
+    let mut rows = range.rows();
+    let headers: Vec<String,> = rows
+        .next()
+        .map(|header_row| {
+            header_row
+                .iter()
+                .enumerate()
+                .map(|(i, cell,)| match cell {
+                    Data::Empty => format!("column_{}", i + 1),
+                    other => other.to_string(),
+                },)
+                .collect()
+        },)
+        .unwrap_or_default();
+
+    let records: Vec<serde_json::Value,> = rows
+        .map(|row| {
+            let fields = headers
+                .iter()
+                .enumerate()
+                .map(|(i, name,)| {
+                    let value = match row.get(i,) {
+                        Some(cell,) => cell_to_json(cell, &config.coercion,),
+                        None => Value::Null,
+                    };
+                    (name.clone(), value,)
+                },)
+                .collect::<serde_json::Map<String, Value,>>();
+            Value::Object(fields,)
+        },)
+        .collect();
+
+    let (_, types,) = infer_record_columns(
+        Box::new(records.clone().into_iter().map(Ok,),),
+        crate::reader::json_reader::infer_json_nc_type,
+    )?;
+    let schema = arrow_schema_from_nc_types(&headers, &types,);
+    let batches = json_records_to_batches(
+        schema.clone(),
+        Box::new(records.into_iter().map(Ok,),),
+        batch_size,
+        file_path,
+    )?;
+    Ok((schema, batches,),)
+}
+
+/// Reads the workbook into memory through a `BudgetedReader`, then opens it.
+fn open_workbook(
+    file_path: &Path,
+    budget: &ReadBudget,
+) -> Result<Sheets<Cursor<Bytes,>,>, DataReaderError,> {
+    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    },)?;
+    let mut data = Vec::new();
+    BudgetedReader::new(file, budget.clone(),)
+        .read_to_end(&mut data,)
+        .map_err(|e| read_error(file_path, e,),)?;
+    open_workbook_auto_from_rs(Cursor::new(Bytes::from(data,),),).map_err(|e| {
+        DataReaderError::ParseError {
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
+        }
+    },)
+}
+
+fn cell_to_json(cell: &Data, coercion: &ValueCoercion,) -> Value {
+    match cell {
+        Data::Empty | Data::Error(_,) => Value::Null,
+        Data::Int(i,) => Value::from(*i,),
+        Data::Float(f,) => serde_json::Number::from_f64(*f,).map_or(Value::Null, Value::Number,),
+        Data::Bool(b,) => Value::Bool(*b,),
+        Data::String(s,) => coercion.coerce(s,).unwrap_or_else(|| Value::String(s.clone(),),),
+        Data::DateTimeIso(s,) | Data::DurationIso(s,) => Value::String(s.clone(),),
+        Data::DateTime(dt,) if dt.is_datetime() => {
+            excel_serial_to_iso(dt.as_f64(),).map_or(Value::Null, Value::String,)
+        },
+        Data::DateTime(dt,) => Value::from(dt.as_f64(),),
+    }
+}
+
+/// Converts an Excel serial date (days since 1899-12-30 in the 1900 date system) to an ISO 8601
+/// string, dropping the time part when it is midnight.
+fn excel_serial_to_iso(serial: f64,) -> Option<String,> {
+    let epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30,)?.and_hms_opt(0, 0, 0,)?;
+    let millis = (serial * 86_400_000.0).round() as i64;
+    let datetime = epoch.checked_add_signed(chrono::Duration::milliseconds(millis,),)?;
+    if datetime.time() == chrono::NaiveTime::MIN {
+        Some(datetime.date().format("%Y-%m-%d",).to_string(),)
+    } else {
+        Some(datetime.format("%Y-%m-%dT%H:%M:%S%.f",).to_string(),)
+    }
+}
@@ -1,10 +1,19 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use arrow::datatypes::SchemaRef;
+use nc_schema::DataType;
+use rusqlite::types::ValueRef;
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::error::DataReaderError;
+use crate::reader::limits::ReadBudget;
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::{
+    RecordBatchStream, arrow_schema_from_nc_types, json_records_to_batches,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct SqliteColumnInfo {
@@ -26,6 +35,16 @@ pub struct SqliteData {
 }
 
 pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError,> {
+    read_sqlite_data_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_sqlite_data` within the limits of `config`. SQLite reads the file itself, so
+/// cancellation and the deadline are checked before each table.
+pub fn read_sqlite_data_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<SqliteData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let total_size = fs::metadata(file_path,)
         .map_err(|e| DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
@@ -34,8 +53,9 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
         .len();
 
     let conn = Connection::open(file_path,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
 
     let mut tables_info = Vec::new();
@@ -44,20 +64,23 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
     let mut stmt = conn
         .prepare("SELECT name FROM sqlite_master WHERE type='table'",)
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?;
     let table_names: Vec<String,> = stmt
         .query_map([], |row| row.get(0,),)
         .map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(e,),
+            path:     file_path.to_path_buf(),
+            source:   Box::new(e,),
+            position: None,
         },)?
         .filter_map(|r| r.ok(),)
         .collect();
 
     if !table_names.is_empty() {
         for table_name in table_names {
+            budget.check()?;
             let mut table_info = SqliteTableInfo {
                 name:      table_name.clone(),
                 schema:    None,
@@ -68,8 +91,9 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
             let mut schema_stmt = conn
                 .prepare(&format!("PRAGMA table_info('{}')", table_name),)
                 .map_err(|e| DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
                 },)?;
             let column_info: Vec<SqliteColumnInfo,> = schema_stmt
                 .query_map([], |row| {
@@ -79,8 +103,9 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
                     },)
                 },)
                 .map_err(|e| DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
                 },)?
                 .filter_map(|r| r.ok(),)
                 .collect();
@@ -90,13 +115,15 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
             let mut count_stmt = conn
                 .prepare(&format!("SELECT COUNT(*) FROM '{}'", table_name),)
                 .map_err(|e| DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
                 },)?;
             let row_count: i64 = count_stmt.query_row([], |row| row.get(0,),).map_err(|e| {
                 DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
+                    path:     file_path.to_path_buf(),
+                    source:   Box::new(e,),
+                    position: None,
                 }
             },)?;
             table_info.row_count = Some(row_count,);
@@ -110,3 +137,191 @@ pub fn read_sqlite_data(file_path: &Path,) -> Result<SqliteData, DataReaderError
         tables: tables_info,
     },)
 }
+
+/// Returns the name of the first table in the file, in the order `read_sqlite_data` lists
+/// them, without reading any table schemas or row counts.
+pub fn first_sqlite_table(file_path: &Path,) -> Result<Option<String,>, DataReaderError,> {
+    let parse_error = |e: rusqlite::Error| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    };
+    let conn = Connection::open(file_path,).map_err(parse_error,)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' LIMIT 1",)
+        .map_err(parse_error,)?;
+    let mut names = stmt.query_map([], |row| row.get(0,),).map_err(parse_error,)?;
+    names.next().transpose().map_err(parse_error,)
+}
+
+/// Maps a declared SQLite column type to an `nc_schema` type using SQLite's type affinity
+/// rules; NUMERIC affinity (including `BOOLEAN` and `DECIMAL`) maps to `Number`.
+fn declared_type_to_nc(c_type: &str,) -> DataType {
+    let c_type = c_type.to_uppercase();
+    if c_type.contains("INT",) {
+        DataType::Integer
+    } else if c_type.contains("CHAR",) || c_type.contains("CLOB",) || c_type.contains("TEXT",) {
+        DataType::String
+    } else if c_type.contains("REAL",) || c_type.contains("FLOA",) || c_type.contains("DOUB",) {
+        DataType::Float
+    } else if c_type.is_empty() || c_type.contains("BLOB",) {
+        // Blobs are rendered as hex, and untyped columns may hold anything
+        DataType::String
+    } else {
+        DataType::Number
+    }
+}
+
+/// Reads one table as Arrow record batches. Column types come from the declared types in the
+/// table schema; rows are fetched one page of `batch_size` at a time.
+pub fn read_sqlite_batches(
+    file_path: &Path,
+    table: &str,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    read_sqlite_batches_with_config(file_path, table, batch_size, &ReadConfig::default(),)
+}
+
+/// `read_sqlite_batches` within the limits of `config`: cancellation and the deadline are
+/// checked before each page, and the rows fetched count towards `max_records`.
+pub fn read_sqlite_batches_with_config(
+    file_path: &Path,
+    table: &str,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    config.budget(file_path,).check()?;
+    let parse_error = |e: rusqlite::Error| DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
+    };
+    let conn = Connection::open(file_path,).map_err(parse_error,)?;
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type='table' AND name=?1)",
+            [table],
+            |row| row.get(0,),
+        )
+        .map_err(parse_error,)?;
+    if !exists {
+        return Err(DataReaderError::InternalError(format!(
+            "Table '{}' not found in {}",
+            table,
+            file_path.display()
+        ),),);
+    }
+    let columns: Vec<SqliteColumnInfo,> = conn
+        .prepare(&format!("PRAGMA table_info('{}')", table),)
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| {
+                Ok(SqliteColumnInfo {
+                    name:   row.get(1,)?,
+                    c_type: row.get(2,)?,
+                },)
+            },)?
+            .collect()
+        },)
+        .map_err(parse_error,)?;
+
+    let names: Vec<String,> = columns.iter().map(|c| c.name.clone(),).collect();
+    let types: HashMap<String, DataType,> = columns
+        .iter()
+        .map(|c| (c.name.clone(), declared_type_to_nc(&c.c_type,),),)
+        .collect();
+    let schema = arrow_schema_from_nc_types(&names, &types,);
+
+    let rows = SqliteRowPages {
+        conn,
+        query: format!("SELECT * FROM \"{}\" LIMIT ?1 OFFSET ?2", table.replace('"', "\"\""),),
+        columns: names,
+        page_size: batch_size.max(1,),
+        offset: 0,
+        buffer: Vec::new().into_iter(),
+        done: false,
+        path: file_path.to_path_buf(),
+        budget: config.budget(file_path,),
+    };
+    let batches =
+        json_records_to_batches(schema.clone(), Box::new(rows,), batch_size, file_path,)?;
+    Ok((schema, batches,),)
+}
+
+/// Iterates over the rows of a table as JSON objects, fetching one page at a time.
+struct SqliteRowPages {
+    conn:      Connection,
+    query:     String,
+    columns:   Vec<String,>,
+    page_size: usize,
+    offset:    usize,
+    buffer:    std::vec::IntoIter<serde_json::Value,>,
+    done:      bool,
+    path:      PathBuf,
+    budget:    ReadBudget,
+}
+
+impl SqliteRowPages {
+    fn fetch_page(&self,) -> rusqlite::Result<Vec<serde_json::Value,>,> {
+        let mut stmt = self.conn.prepare_cached(&self.query,)?;
+        let rows = stmt.query_map([self.page_size as i64, self.offset as i64], |row| {
+            let mut fields = serde_json::Map::new();
+            for (i, name,) in self.columns.iter().enumerate() {
+                fields.insert(name.clone(), sqlite_value_to_json(row.get_ref(i,)?,),);
+            }
+            Ok(serde_json::Value::Object(fields,),)
+        },)?;
+        rows.collect()
+    }
+}
+
+impl Iterator for SqliteRowPages {
+    type Item = Result<serde_json::Value, DataReaderError,>;
+
+    fn next(&mut self,) -> Option<Self::Item,> {
+        if let Some(row,) = self.buffer.next() {
+            return Some(Ok(row,),);
+        }
+        if self.done {
+            return None;
+        }
+        if let Err(e,) = self.budget.check() {
+            self.done = true;
+            return Some(Err(e,),);
+        }
+
+        match self.fetch_page() {
+            Ok(page,) => {
+                self.done = page.len() < self.page_size;
+                self.offset += page.len();
+                if let Err(e,) = self.budget.check_records(self.offset as u64,) {
+                    self.done = true;
+                    return Some(Err(e,),);
+                }
+                self.buffer = page.into_iter();
+                self.buffer.next().map(Ok,)
+            },
+            Err(e,) => {
+                self.done = true;
+                Some(Err(DataReaderError::ParseError {
+                    path:     self.path.clone(),
+                    source:   Box::new(e,),
+                    position: None,
+                },),)
+            },
+        }
+    }
+}
+
+fn sqlite_value_to_json(value: ValueRef<'_,>,) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i,) => serde_json::Value::from(i,),
+        ValueRef::Real(f,) => serde_json::Value::from(f,),
+        ValueRef::Text(bytes,) => {
+            serde_json::Value::String(String::from_utf8_lossy(bytes,).into_owned(),)
+        },
+        ValueRef::Blob(bytes,) => {
+            serde_json::Value::String(bytes.iter().map(|b| format!("{:02x}", b),).collect(),)
+        },
+    }
+}
@@ -6,7 +6,9 @@ use encoding_rs_io::DecodeReaderBytes;
 use serde::{Deserialize, Serialize}; // Added
 use toml::Value;
 
-use crate::error::DataReaderError;
+use crate::error::{DataReaderError, ErrorPosition};
+use crate::reader::limits::{BudgetedReader, read_error};
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)] // Added
 pub struct TomlData {
@@ -18,6 +20,17 @@ pub fn read_toml_value(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<TomlData, DataReaderError,> {
+    read_toml_value_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_toml_value` within the limits of `config`: the file is read through a
+/// `BudgetedReader`, and the nesting of the document is measured against `max_depth`.
+pub fn read_toml_value_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<TomlData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let num_lines_to_extract = head.unwrap_or(0,);
 
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -25,20 +38,24 @@ pub fn read_toml_value(
         source: e,
     },)?;
 
-    let mut decoder = DecodeReaderBytes::new(file,);
+    let mut decoder = DecodeReaderBytes::new(BudgetedReader::new(file, budget.clone(),),);
     let mut content = String::new();
-    decoder
-        .read_to_string(&mut content,)
-        .map_err(|e| DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        },)?;
+    decoder.read_to_string(&mut content,).map_err(|e| read_error(file_path, e,),)?;
 
-    let value: Value = toml::from_str(&content,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+    let value: Value = toml::from_str(&content,).map_err(|e| {
+        let position = e
+            .span()
+            .map(|span| Box::new(ErrorPosition::from_offset(&content, span.start,),),);
+        DataReaderError::ParseError {
+            path: file_path.to_path_buf(),
+            source: Box::new(e,),
+            position,
+        }
     },)?;
 
+    budget.check()?;
+    budget.check_depth(toml_depth(&value,),)?;
+
     let first_lines: Option<Vec<String,>,> = if num_lines_to_extract > 0 {
         let lines: Vec<String,> = content
             .lines()
@@ -59,9 +76,27 @@ pub fn get_toml_raw_content(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<String, DataReaderError,> {
-    let toml_data = read_toml_value(file_path, head,)?; // Use the new read_toml_value that returns TomlData
+    get_toml_raw_content_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `get_toml_raw_content` within the limits of `config`.
+pub fn get_toml_raw_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<String, DataReaderError,> {
+    let toml_data = read_toml_value_with_config(file_path, head, config,)?;
 
     serde_json::to_string_pretty(&toml_data.value,).map_err(|e| {
         DataReaderError::InternalError(format!("Failed to serialize TOML to JSON: {}", e),)
     },)
 }
+
+/// Nesting depth of a TOML value, counted like `json_depth`.
+fn toml_depth(value: &Value,) -> usize {
+    match value {
+        Value::Array(items,) => 1 + items.iter().map(toml_depth,).max().unwrap_or(0,),
+        Value::Table(table,) => 1 + table.values().map(toml_depth,).max().unwrap_or(0,),
+        _ => 1,
+    }
+}
@@ -5,6 +5,8 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 
 use crate::error::DataReaderError;
+use crate::reader::limits::{BudgetedReader, read_error};
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct TextData {
@@ -17,6 +19,16 @@ pub struct TextData {
 pub fn read_txt_content(
     file_path: &Path,
     head: Option<usize,>,
+) -> Result<TextData, DataReaderError,> {
+    read_txt_content_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_txt_content` within the limits of `config`: the file is read through a
+/// `BudgetedReader`, so cancellation, the deadline and `max_bytes` stop it part way.
+pub fn read_txt_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
 ) -> Result<TextData, DataReaderError,> {
     let num_lines_to_extract = head.unwrap_or(0,);
 
@@ -24,20 +36,12 @@ pub fn read_txt_content(
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
+    let file = BudgetedReader::new(file, config.budget(file_path,),);
 
-    let mut decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-        DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        }
-    },)?;
+    let mut decoder = crate::reader::charset::get_decoded_reader_from(file,)
+        .map_err(|e| read_error(file_path, e,),)?;
     let mut content = String::new();
-    decoder
-        .read_to_string(&mut content,)
-        .map_err(|e| DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        },)?;
+    decoder.read_to_string(&mut content,).map_err(|e| read_error(file_path, e,),)?;
 
     let total_size = std::fs::metadata(file_path,)
         .map(|m| m.len(),)
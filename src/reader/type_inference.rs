@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::reader::coercion::ValueCoercion;
+use crate::record::NcValue;
+
+/// Date and timestamp formats tried on text fields, in order. Where a value fits several (as
+/// `03/04/2024` fits both day-first and month-first), the column takes the first format that
+/// fits all of its values.
+pub const DEFAULT_DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f%:z",
+    "%Y-%m-%dT%H:%M:%S%.fZ",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f%:z",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%d.%m.%Y",
+    "%d/%m/%Y",
+    "%m/%d/%Y",
+    "%d-%m-%Y",
+    "%d.%m.%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M:%S",
+];
+
+/// Currency symbols stripped from the start or end of a number.
+const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥', '₹', '₽', '₩'];
+
+/// The separators of numbers written for a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+pub struct NumberLocale {
+    pub decimal:  char,
+    /// Separates groups of three digits; `None` when digits are not grouped.
+    pub grouping: Option<char,>,
+}
+
+impl NumberLocale {
+    /// `1,234.56`
+    pub const EN: NumberLocale = NumberLocale {
+        decimal:  '.',
+        grouping: Some(',',),
+    };
+    /// `1.234,56`
+    pub const DE: NumberLocale = NumberLocale {
+        decimal:  ',',
+        grouping: Some('.',),
+    };
+    /// `1 234,56`, grouped with a space or a no-break space.
+    pub const FR: NumberLocale = NumberLocale {
+        decimal:  ',',
+        grouping: Some(' ',),
+    };
+    /// `1'234.56`
+    pub const CH: NumberLocale = NumberLocale {
+        decimal:  '.',
+        grouping: Some('\'',),
+    };
+}
+
+impl FromStr for NumberLocale {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(NumberLocale::EN,),
+            "de" => Ok(NumberLocale::DE,),
+            "fr" => Ok(NumberLocale::FR,),
+            "ch" => Ok(NumberLocale::CH,),
+            other => Err(format!("unknown locale {:?} (expected en, de, fr or ch)", other),),
+        }
+    }
+}
+
+impl fmt::Display for NumberLocale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match *self {
+            NumberLocale::EN => write!(f, "en"),
+            NumberLocale::DE => write!(f, "de"),
+            NumberLocale::FR => write!(f, "fr"),
+            NumberLocale::CH => write!(f, "ch"),
+            NumberLocale {
+                decimal,
+                grouping,
+            } => write!(f, "{}{}", grouping.map(String::from,).unwrap_or_default(), decimal),
+        }
+    }
+}
+
+/// A hint for one column: the locale of its numbers (`price=de`) or the format of its dates
+/// (`day=%d.%m.%Y`).
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub enum ColumnHint {
+    Locale(NumberLocale,),
+    DateFormat(String,),
+}
+
+impl FromStr for ColumnHint {
+    type Err = String;
+
+    fn from_str(s: &str,) -> Result<Self, Self::Err,> {
+        if s.contains('%',) {
+            Ok(ColumnHint::DateFormat(s.to_string(),),)
+        } else {
+            s.parse().map(ColumnHint::Locale,)
+        }
+    }
+}
+
+impl fmt::Display for ColumnHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            ColumnHint::Locale(locale,) => write!(f, "{}", locale),
+            ColumnHint::DateFormat(format,) => write!(f, "{}", format),
+        }
+    }
+}
+
+/// How the text fields of delimited files are turned into values.
+///
+/// Empty fields and the null values of the [`ValueCoercion`] a field is parsed with are null;
+/// its boolean words (`true`/`false`/`yes`/`no` by default) are booleans. Numbers may carry
+/// grouping separators, a decimal comma, a leading or trailing currency symbol, or a trailing
+/// `%` (read as a fraction: `12.5%` is `0.125`). Dates and timestamps stay text in records, and
+/// their format is reported per column by [`FormatTracker`].
+#[derive(Debug, Clone, PartialEq, Eq,)]
+pub struct InferenceOptions {
+    /// The separators of numbers in columns without a hint. `None` accepts `1,234.56` and
+    /// `1.234,56` alike and reads a lone comma (`1,5`) as a decimal comma, but keeps `1,234`,
+    /// which either locale could mean, as text.
+    pub locale:       Option<NumberLocale,>,
+    /// Formats tried on text fields, in order; see [`DEFAULT_DATE_FORMATS`].
+    pub date_formats: Vec<String,>,
+    /// Per-column hints, by source column name.
+    pub columns:      HashMap<String, ColumnHint,>,
+}
+
+impl Default for InferenceOptions {
+    fn default() -> Self {
+        Self {
+            locale:       None,
+            date_formats: DEFAULT_DATE_FORMATS.iter().map(|f| f.to_string(),).collect(),
+            columns:      HashMap::new(),
+        }
+    }
+}
+
+impl InferenceOptions {
+    /// Tries `formats` before the default date formats.
+    pub fn with_date_formats(mut self, formats: Vec<String,>,) -> Self {
+        let defaults = std::mem::replace(&mut self.date_formats, formats,);
+        let extra: Vec<String,> =
+            defaults.into_iter().filter(|f| !self.date_formats.contains(f,),).collect();
+        self.date_formats.extend(extra,);
+        self
+    }
+
+    pub fn with_column_hint(mut self, column: &str, hint: ColumnHint,) -> Self {
+        self.columns.insert(column.to_string(), hint,);
+        self
+    }
+
+    /// The value of one field of `column`, or why it is not of the column's declared type.
+    /// Numbers are read without losing precision: integers beyond `i64` and decimals that
+    /// `f64` would round keep every digit (see [`NcValue::parse_number`]).
+    pub fn parse_field(
+        &self,
+        coercion: &ValueCoercion,
+        column: &str,
+        text: &str,
+    ) -> Result<NcValue, String,> {
+        if text.is_empty() || coercion.is_null(text,) {
+            return Ok(NcValue::Null,);
+        }
+        if let Some(value_type,) = coercion.declared_type(column,) {
+            return value_type.parse(coercion, text,);
+        }
+        if let Some(b_val,) = coercion.parse_bool(text,) {
+            return Ok(NcValue::Boolean(b_val,),);
+        }
+        let locale = match self.columns.get(column,) {
+            Some(ColumnHint::Locale(locale,),) => Some(*locale,),
+            Some(ColumnHint::DateFormat(_,),) => None,
+            None => self.locale,
+        };
+        // `1.234` is a thousand and change under a decimal comma.
+        match NcValue::parse_number(text,) {
+            Some(number @ (NcValue::Integer(_,) | NcValue::BigInteger(_,)),) => return Ok(number,),
+            Some(number,) if locale.is_none_or(|locale| locale.decimal == '.',) => {
+                return Ok(number,);
+            },
+            _ => {},
+        }
+        Ok(parse_number(text, locale,).unwrap_or_else(|| NcValue::String(text.to_string(),),),)
+    }
+
+    /// The date formats `text` fits: the column's hint, or each of `date_formats` that fits.
+    pub fn date_formats_of(&self, column: &str, text: &str,) -> Vec<&str,> {
+        match self.columns.get(column,) {
+            Some(ColumnHint::DateFormat(format,),) => vec![format.as_str()],
+            _ => self.date_formats.iter().map(String::as_str,).collect(),
+        }
+        .into_iter()
+        .filter(|format| fits_date_format(text, format,),)
+        .collect()
+    }
+}
+
+/// Narrows down the date format of each column from its values.
+#[derive(Debug, Default,)]
+pub struct FormatTracker {
+    /// The formats every value so far fits; `None` once a value is not a date.
+    candidates: HashMap<String, Option<Vec<String,>,>,>,
+}
+
+impl FormatTracker {
+    pub fn observe(
+        &mut self,
+        options: &InferenceOptions,
+        column: &str,
+        value: &serde_json::Value,
+    ) {
+        let text = match value {
+            serde_json::Value::Null => return,
+            serde_json::Value::String(text,) => text,
+            _ => {
+                self.candidates.insert(column.to_string(), None,);
+                return;
+            },
+        };
+        match self.candidates.get_mut(column,) {
+            Some(None,) => {},
+            Some(Some(candidates,),) => {
+                candidates.retain(|format| fits_date_format(text, format,),);
+                if candidates.is_empty() {
+                    self.candidates.insert(column.to_string(), None,);
+                }
+            },
+            None => {
+                let formats: Vec<String,> = options
+                    .date_formats_of(column, text,)
+                    .into_iter()
+                    .map(str::to_string,)
+                    .collect();
+                self.candidates
+                    .insert(column.to_string(), (!formats.is_empty()).then_some(formats,),);
+            },
+        }
+    }
+
+    /// The format of each column whose values are all dates or timestamps.
+    pub fn formats(self,) -> HashMap<String, String,> {
+        self.candidates
+            .into_iter()
+            .filter_map(|(column, candidates,)| Some((column, candidates?.into_iter().next()?,),),)
+            .collect()
+    }
+}
+
+/// Whether `text` is a date (or timestamp) written in `format`. Formats with `%z` are read as
+/// timestamps with an offset, formats with `%H` as local timestamps, and others as dates.
+fn fits_date_format(text: &str, format: &str,) -> bool {
+    if format.contains("%z",) || format.contains("%:z",) {
+        DateTime::parse_from_str(text, format,).is_ok()
+    } else if format.contains("%H",) {
+        NaiveDateTime::parse_from_str(text, format,).is_ok()
+    } else {
+        NaiveDate::parse_from_str(text, format,).is_ok()
+    }
+}
+
+/// Reads a localized number: sign, currency symbol, grouped digits, decimal separator and a
+/// trailing `%`, in the separators of `locale` (or guessed, for `None`).
+fn parse_number(text: &str, locale: Option<NumberLocale,>,) -> Option<NcValue,> {
+    let mut rest = text.trim();
+    let mut negative = false;
+    let mut strip_sign = |rest: &mut &str| {
+        if let Some(unsigned,) = rest.strip_prefix('-',) {
+            negative = !negative;
+            *rest = unsigned.trim_start();
+        } else if let Some(unsigned,) = rest.strip_prefix('+',) {
+            *rest = unsigned.trim_start();
+        }
+    };
+    strip_sign(&mut rest,);
+    let mut currency = false;
+    if let Some(amount,) = rest.strip_prefix(CURRENCY_SYMBOLS,) {
+        rest = amount.trim_start();
+        currency = true;
+        strip_sign(&mut rest,);
+    } else if let Some(amount,) = rest.strip_suffix(CURRENCY_SYMBOLS,) {
+        rest = amount.trim_end();
+        currency = true;
+    }
+    let percent = match rest.strip_suffix('%',) {
+        Some(amount,) if !currency => {
+            rest = amount.trim_end();
+            true
+        },
+        _ => false,
+    };
+    if rest.is_empty() || !rest.starts_with(|c: char| c.is_ascii_digit(),) {
+        return None;
+    }
+
+    let locale = locale.or_else(|| guess_locale(rest,),)?;
+    let (whole, fraction,) = match rest.rsplit_once(locale.decimal,) {
+        Some((whole, fraction,),) => (whole, Some(fraction,),),
+        None => (rest, None,),
+    };
+    let digits = ungroup(whole, locale.grouping,)?;
+    if fraction.is_some_and(|f| f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit(),),) {
+        return None;
+    }
+    let sign = if negative { "-" } else { "" };
+    let plain = match fraction {
+        Some(fraction,) => format!("{}{}.{}", sign, digits, fraction),
+        None => format!("{}{}", sign, digits),
+    };
+    if !percent {
+        return NcValue::parse_number(&plain,);
+    }
+    match NcValue::parse_number(&plain,)? {
+        NcValue::Decimal {
+            unscaled,
+            scale,
+        } => Some(NcValue::Decimal {
+            unscaled,
+            scale: scale.checked_add(2,)?,
+        },),
+        _ => plain.parse::<f64>().ok().map(|value| NcValue::Float(value / 100.0,),),
+    }
+}
+
+/// The separators of an unhinted number: with both `.` and `,`, the last one is the decimal
+/// separator. Several `,` group thousands; a single one is a decimal comma unless it could
+/// also group them (`1,234`), which only a hint can tell, so the value stays text.
+fn guess_locale(number: &str,) -> Option<NumberLocale,> {
+    let comma = number.rfind(',',);
+    let dot = number.rfind('.',);
+    match (comma, dot,) {
+        (Some(comma,), Some(dot,),) if comma > dot => Some(NumberLocale::DE,),
+        (Some(_,), Some(_,),) => Some(NumberLocale::EN,),
+        (Some(_,), None,) => {
+            let grouped = ungroup(number, Some(',',),).is_some();
+            match number.matches(',',).count() {
+                1 => (!grouped).then_some(NumberLocale::DE,),
+                _ => grouped.then_some(NumberLocale::EN,),
+            }
+        },
+        (None, Some(_,),) if number.matches('.',).count() > 1 => Some(NumberLocale::DE,),
+        (None, Some(_,),) => Some(NumberLocale::EN,),
+        (None, None,) => Some(NumberLocale::EN,),
+    }
+}
+
+/// The digits of `whole` with its grouping separators removed, or `None` if it is not a run of
+/// digits grouped in threes. A space also matches a no-break space.
+fn ungroup(whole: &str, grouping: Option<char,>,) -> Option<String,> {
+    let is_separator = |c: char| match grouping {
+        Some(' ',) => matches!(c, ' ' | '\u{a0}' | '\u{202f}'),
+        Some(separator,) => c == separator,
+        None => false,
+    };
+    let groups: Vec<&str,> = whole.split(is_separator,).collect();
+    let (first, rest,) = groups.split_first()?;
+    let valid = !first.is_empty()
+        && first.bytes().all(|b| b.is_ascii_digit(),)
+        && (rest.is_empty() || first.len() <= 3)
+        && rest.iter().all(|g| g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit(),),);
+    valid.then(|| groups.concat(),)
+}
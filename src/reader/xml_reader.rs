@@ -1,27 +1,45 @@
-use std::collections::HashMap;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 
+use arrow::datatypes::SchemaRef;
+use bytes::{Buf, Bytes};
 use nc_schema::{DataType, merge_nc_types};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
 
-use crate::error::DataReaderError;
+use crate::error::{DataReaderError, ErrorPosition};
+use crate::record::NcValue;
+use crate::reader::async_stream::{AsyncRecordStream, open_async_stream};
+use crate::reader::coercion::ValueCoercion;
+use crate::reader::limits::ReadBudget;
+use crate::nc_reader_result::{RecordStream, TypedRecordStream};
+use crate::reader::read_config::ReadConfig;
+use crate::reader::record_batch::{
+    RecordBatchStream, arrow_schema_from_nc_types, infer_record_columns, json_records_to_batches,
+};
 
 pub struct XmlReader<R: std::io::BufRead,> {
-    reader:   Reader<R,>,
+    reader:   Reader<LineTracker<R,>,>,
     buf:      Vec<u8,>,
     path:     PathBuf,
     depth:    usize,
     root_tag: Option<String,>,
+    /// Number of records returned so far, i.e. the index of the record being parsed.
+    records:  u64,
+    coercion: ValueCoercion,
+    /// First value of the record being parsed that is not of its declared type.
+    error:    Option<DataReaderError,>,
+    /// Set after an XML syntax or read error, which the reader cannot resynchronize after.
+    done:     bool,
 }
 
 impl<R: std::io::BufRead,> XmlReader<R,> {
     pub fn new(reader_input: R, path: PathBuf,) -> Self {
-        let mut reader = Reader::from_reader(reader_input,);
+        let mut reader = Reader::from_reader(LineTracker::new(reader_input,),);
         reader.config_mut().trim_text(true,);
         Self {
             reader,
@@ -29,70 +47,70 @@ impl<R: std::io::BufRead,> XmlReader<R,> {
             path,
             depth: 0,
             root_tag: None,
+            records: 0,
+            coercion: ValueCoercion::default(),
+            error: None,
+            done: false,
         }
     }
 
+    /// Reads attributes and text with `coercion` instead of the default null values and
+    /// boolean words.
+    pub fn with_coercion(mut self, coercion: ValueCoercion,) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    /// Drops what the reader keeps to place errors before the event it reads next.
+    fn settle(&mut self,) {
+        let offset = self.reader.buffer_position();
+        self.reader.get_mut().settle(offset,);
+    }
+
     fn parse_element(
         &mut self,
         start: quick_xml::events::BytesStart,
-    ) -> Result<Value, DataReaderError,> {
-        let mut map = Map::new();
+    ) -> Result<NcValue, DataReaderError,> {
+        let tag_name = String::from_utf8_lossy(start.name().into_inner(),).to_string();
+        let mut map = BTreeMap::new();
 
         // Handle attributes
         for attr_result in start.attributes() {
-            let attr = attr_result.map_err(|e| DataReaderError::ParseError {
-                path:   self.path.clone(),
-                source: Box::new(e,),
+            let attr = attr_result.map_err(|e| {
+                xml_parse_error(
+                    &self.path,
+                    e,
+                    self.reader.get_ref().position(self.reader.buffer_position(),),
+                    Some(self.records,),
+                )
             },)?;
-            let key = String::from_utf8_lossy(attr.key.into_inner(),).to_string();
+            let key = format!("@{}", String::from_utf8_lossy(attr.key.into_inner(),));
             let value_str = String::from_utf8_lossy(&attr.value,).to_string();
 
-            // Convert to JSON type
-            let value = if let Ok(i,) = value_str.parse::<i64>() {
-                Value::Number(i.into(),)
-            } else if let Ok(f,) = value_str.parse::<f64>() {
-                serde_json::Number::from_f64(f,)
-                    .map(Value::Number,)
-                    .unwrap_or(Value::String(value_str,),)
-            } else if value_str.to_lowercase() == "true" {
-                Value::Bool(true,)
-            } else if value_str.to_lowercase() == "false" {
-                Value::Bool(false,)
-            } else {
-                Value::String(value_str,)
-            };
+            let value = parse_xml_value(
+                &self.coercion,
+                &key,
+                &value_str,
+                &self.path,
+                || self.reader.get_ref().position(self.reader.buffer_position(),),
+                Some(self.records,),
+            );
 
-            map.insert(format!("@{}", key), value,);
+            map.insert(key, keep_first_error(value, &mut self.error,),);
         }
 
         let mut text_content = String::new();
-        let mut children = Map::new();
+        let mut children = BTreeMap::new();
 
         loop {
             self.buf.clear();
+            self.settle();
             match self.reader.read_event_into(&mut self.buf,) {
                 Ok(Event::Start(e,),) => {
                     let name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
                     let e_owned = e.into_owned();
                     let child_value = self.parse_element(e_owned,)?;
-
-                    // Handle multiple children with same name by converting to array
-                    match children.entry(name.clone(),) {
-                        serde_json::map::Entry::Vacant(entry,) => {
-                            entry.insert(child_value,);
-                        },
-                        serde_json::map::Entry::Occupied(mut entry,) => {
-                            if let Value::Array(arr,) = entry.get_mut() {
-                                arr.push(child_value,);
-                            } else {
-                                let old_val = entry.insert(Value::Array(vec![],),);
-                                if let Value::Array(arr,) = entry.get_mut() {
-                                    arr.push(old_val,);
-                                    arr.push(child_value,);
-                                }
-                            }
-                        },
-                    }
+                    insert_child(&mut children, name, child_value,);
                 },
                 Ok(Event::End(_,),) => break,
                 Ok(Event::Text(e,),) => {
@@ -100,66 +118,55 @@ impl<R: std::io::BufRead,> XmlReader<R,> {
                 },
                 Ok(Event::Empty(e,),) => {
                     let name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
-                    let mut child_map = Map::new();
+                    let mut child_map = BTreeMap::new();
                     for attr_result in e.attributes() {
-                        let attr = attr_result.map_err(|e| DataReaderError::ParseError {
-                            path:   self.path.clone(),
-                            source: Box::new(e,),
+                        let attr = attr_result.map_err(|e| {
+                            xml_parse_error(
+                                &self.path,
+                                e,
+                                self.reader.get_ref().position(self.reader.buffer_position(),),
+                                Some(self.records,),
+                            )
                         },)?;
-                        let key = String::from_utf8_lossy(attr.key.into_inner(),).to_string();
+                        let key = format!("@{}", String::from_utf8_lossy(attr.key.into_inner(),));
                         let value_str = String::from_utf8_lossy(&attr.value,).to_string();
 
-                        let value = if let Ok(i,) = value_str.parse::<i64>() {
-                            Value::Number(i.into(),)
-                        } else if let Ok(f,) = value_str.parse::<f64>() {
-                            serde_json::Number::from_f64(f,)
-                                .map(Value::Number,)
-                                .unwrap_or(Value::String(value_str,),)
-                        } else if value_str.to_lowercase() == "true" {
-                            Value::Bool(true,)
-                        } else if value_str.to_lowercase() == "false" {
-                            Value::Bool(false,)
-                        } else {
-                            Value::String(value_str,)
-                        };
+                        let value = parse_xml_value(
+                            &self.coercion,
+                            &key,
+                            &value_str,
+                            &self.path,
+                            || self.reader.get_ref().position(self.reader.buffer_position(),),
+                            Some(self.records,),
+                        );
 
-                        child_map.insert(format!("@{}", key), value,);
+                        child_map.insert(key, keep_first_error(value, &mut self.error,),);
                     }
 
                     let child_value = if child_map.is_empty() {
-                        Value::Null
+                        NcValue::Null
                     } else {
-                        Value::Object(child_map,)
+                        NcValue::Object(child_map,)
                     };
-
-                    match children.entry(name,) {
-                        serde_json::map::Entry::Vacant(entry,) => {
-                            entry.insert(child_value,);
-                        },
-                        serde_json::map::Entry::Occupied(mut entry,) => {
-                            if let Value::Array(arr,) = entry.get_mut() {
-                                arr.push(child_value,);
-                            } else {
-                                let old_val = entry.insert(Value::Array(vec![],),);
-                                if let Value::Array(arr,) = entry.get_mut() {
-                                    arr.push(old_val,);
-                                    arr.push(child_value,);
-                                }
-                            }
-                        },
-                    }
+                    insert_child(&mut children, name, child_value,);
                 },
                 Ok(Event::CData(e,),) => {
                     text_content.push_str(&String::from_utf8_lossy(&e,),);
                 },
                 Ok(Event::Eof,) => {
-                    return Err(DataReaderError::ParseError {
-                        path:   self.path.clone(),
-                        source: Box::new(std::io::Error::new(
+                    return Err(xml_parse_error(
+                        &self.path,
+                        std::io::Error::new(
                             std::io::ErrorKind::UnexpectedEof,
                             "Unexpected EOF while parsing element",
-                        ),),
-                    },);
+                        ),
+                        self.reader.get_ref().position(self.reader.buffer_position(),),
+                        Some(self.records,),
+                    ),);
+                },
+                Err(e,) => {
+                    let position = self.reader.get_ref().position(self.reader.error_position(),);
+                    return Err(xml_parse_error(&self.path, e, position, Some(self.records,),),);
                 },
                 _ => {},
             }
@@ -167,34 +174,28 @@ impl<R: std::io::BufRead,> XmlReader<R,> {
 
         if children.is_empty() {
             if map.is_empty() {
-                // If it's just text, return the text value (attempting to parse as number/bool)
+                // Just text: read it as a null value, boolean or number if it is one
                 let trimmed = text_content.trim();
                 if trimmed.is_empty() {
-                    return Ok(Value::Null,);
+                    return Ok(NcValue::Null,);
                 }
-                if let Ok(i,) = trimmed.parse::<i64>() {
-                    return Ok(Value::Number(i.into(),),);
-                }
-                if let Ok(f,) = trimmed.parse::<f64>()
-                    && let Some(num,) = serde_json::Number::from_f64(f,)
-                {
-                    return Ok(Value::Number(num,),);
-                }
-                if trimmed.to_lowercase() == "true" {
-                    return Ok(Value::Bool(true,),);
-                }
-                if trimmed.to_lowercase() == "false" {
-                    return Ok(Value::Bool(false,),);
-                }
-                Ok(Value::String(trimmed.to_string(),),)
+                let value = parse_xml_value(
+                    &self.coercion,
+                    &tag_name,
+                    trimmed,
+                    &self.path,
+                    || self.reader.get_ref().position(self.reader.buffer_position(),),
+                    Some(self.records,),
+                );
+                Ok(keep_first_error(value, &mut self.error,),)
             } else {
                 if !text_content.trim().is_empty() {
                     map.insert(
                         "#text".to_string(),
-                        Value::String(text_content.trim().to_string(),),
+                        NcValue::String(text_content.trim().to_string(),),
                     );
                 }
-                Ok(Value::Object(map,),)
+                Ok(NcValue::Object(map,),)
             }
         } else {
             // Merge map (attributes) and children
@@ -204,20 +205,40 @@ impl<R: std::io::BufRead,> XmlReader<R,> {
             if !text_content.trim().is_empty() {
                 map.insert(
                     "#text".to_string(),
-                    Value::String(text_content.trim().to_string(),),
+                    NcValue::String(text_content.trim().to_string(),),
                 );
             }
-            Ok(Value::Object(map,),)
+            Ok(NcValue::Object(map,),)
         }
     }
 }
 
+/// Adds a child element to its parent's fields. Repeated children become an array.
+fn insert_child(children: &mut BTreeMap<String, NcValue,>, name: String, value: NcValue,) {
+    match children.entry(name,) {
+        Entry::Vacant(entry,) => {
+            entry.insert(value,);
+        },
+        Entry::Occupied(mut entry,) => match entry.get_mut() {
+            NcValue::Array(items,) => items.push(value,),
+            existing => {
+                let first = std::mem::replace(existing, NcValue::Null,);
+                *existing = NcValue::Array(vec![first, value],);
+            },
+        },
+    }
+}
+
 impl<R: std::io::BufRead,> Iterator for XmlReader<R,> {
-    type Item = Result<serde_json::Value, DataReaderError,>;
+    type Item = Result<NcValue, DataReaderError,>;
 
     fn next(&mut self,) -> Option<Self::Item,> {
+        if self.done {
+            return None;
+        }
         loop {
             self.buf.clear();
+            self.settle();
             match self.reader.read_event_into(&mut self.buf,) {
                 Ok(Event::Start(e,),) => {
                     self.depth += 1;
@@ -231,7 +252,12 @@ impl<R: std::io::BufRead,> Iterator for XmlReader<R,> {
                         let e_owned = e.into_owned();
                         let res = self.parse_element(e_owned,);
                         self.depth -= 1; // parse_element consumed the End event
-                        return Some(res,);
+                        self.records += 1;
+                        // Only syntax and read errors stop the document; a value of the wrong
+                        // type fails just its record, which was parsed to its end.
+                        self.done = res.is_err();
+                        let value_error = self.error.take();
+                        return Some(res.and_then(|value| value_error.map_or(Ok(value,), Err,),),);
                     }
                 },
                 Ok(Event::End(_,),) => {
@@ -239,40 +265,39 @@ impl<R: std::io::BufRead,> Iterator for XmlReader<R,> {
                 },
                 Ok(Event::Empty(e,),) => {
                     if self.depth == 1 {
-                        let mut map = Map::new();
+                        let mut map = BTreeMap::new();
                         for attr in e.attributes().flatten() {
-                            let key = String::from_utf8_lossy(attr.key.into_inner(),).to_string();
+                            let name = String::from_utf8_lossy(attr.key.into_inner(),);
+                            let key = format!("@{}", name);
                             let value_str = String::from_utf8_lossy(&attr.value,).to_string();
 
-                            let value = if let Ok(i,) = value_str.parse::<i64>() {
-                                Value::Number(i.into(),)
-                            } else if let Ok(f,) = value_str.parse::<f64>() {
-                                serde_json::Number::from_f64(f,)
-                                    .map(Value::Number,)
-                                    .unwrap_or(Value::String(value_str,),)
-                            } else if value_str.to_lowercase() == "true" {
-                                Value::Bool(true,)
-                            } else if value_str.to_lowercase() == "false" {
-                                Value::Bool(false,)
-                            } else {
-                                Value::String(value_str,)
-                            };
-
-                            map.insert(format!("@{}", key), value,);
+                            let value = parse_xml_value(
+                                &self.coercion,
+                                &key,
+                                &value_str,
+                                &self.path,
+                                || self.reader.get_ref().position(self.reader.buffer_position(),),
+                                Some(self.records,),
+                            );
+                            map.insert(key, keep_first_error(value, &mut self.error,),);
+                        }
+                        self.records += 1;
+                        if let Some(error,) = self.error.take() {
+                            return Some(Err(error,),);
                         }
                         return Some(Ok(if map.is_empty() {
-                            Value::Null
+                            NcValue::Null
                         } else {
-                            Value::Object(map,)
+                            NcValue::Object(map,)
                         },),);
                     }
                 },
                 Ok(Event::Eof,) => return None,
                 Err(e,) => {
-                    return Some(Err(DataReaderError::ParseError {
-                        path:   self.path.clone(),
-                        source: Box::new(e,),
-                    },),);
+                    self.done = true;
+                    let position = self.reader.get_ref().position(self.reader.error_position(),);
+                    let records = Some(self.records,);
+                    return Some(Err(xml_parse_error(&self.path, e, position, records,),),);
                 },
                 _ => {},
             }
@@ -280,21 +305,275 @@ impl<R: std::io::BufRead,> Iterator for XmlReader<R,> {
     }
 }
 
+/// Returns the value of `result`, or reads it as null and keeps its error in `error` unless an
+/// earlier value of the record already failed, so the rest of the record is still parsed.
+fn keep_first_error(
+    result: Result<NcValue, DataReaderError,>,
+    error: &mut Option<DataReaderError,>,
+) -> NcValue {
+    result.unwrap_or_else(|e| {
+        error.get_or_insert(e,);
+        NcValue::Null
+    },)
+}
+
+/// Reads an attribute (`@name`) or text value of `field` with `coercion`, failing when it is not
+/// of the field's declared type.
+fn parse_xml_value(
+    coercion: &ValueCoercion,
+    field: &str,
+    text: &str,
+    file_path: &Path,
+    position: impl FnOnce() -> ErrorPosition,
+    record_index: Option<u64,>,
+) -> Result<NcValue, DataReaderError,> {
+    coercion.parse_value(field, text,).map_err(|message| {
+        let error = std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{}: {}", field, message),
+        );
+        xml_parse_error(file_path, error, position(), record_index,)
+    },)
+}
+
+/// Wraps an XML error with the position where it was detected and, for streamed records, the
+/// index of the record being parsed.
+fn xml_parse_error<E: std::error::Error + Send + Sync + 'static,>(
+    file_path: &Path,
+    e: E,
+    position: ErrorPosition,
+    record_index: Option<u64,>,
+) -> DataReaderError {
+    DataReaderError::ParseError {
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: Some(Box::new(ErrorPosition {
+            record_index,
+            ..position
+        },),),
+    }
+}
+
+/// Follows the lines of the bytes the XML parser consumes, so an error offset also gets its
+/// line and column. Only the bytes since the last `settle` are kept, which is the event being
+/// read: offsets before it fall back to where it starts.
+struct LineTracker<R,> {
+    inner:   R,
+    /// Offset of the first byte of `pending`.
+    settled: u64,
+    /// Line and column (in characters) of that byte, both 1-based.
+    line:    u64,
+    column:  u64,
+    pending: Vec<u8,>,
+}
+
+impl<R: BufRead,> LineTracker<R,> {
+    fn new(inner: R,) -> Self {
+        Self {
+            inner,
+            settled: 0,
+            line: 1,
+            column: 1,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Counts the lines of the bytes before `offset` and forgets them.
+    fn settle(&mut self, offset: u64,) {
+        let end = (offset.saturating_sub(self.settled,) as usize).min(self.pending.len(),);
+        (self.line, self.column,) = advance(self.line, self.column, &self.pending[..end],);
+        self.pending.drain(..end,);
+        self.settled += end as u64;
+    }
+
+    fn position(&self, offset: u64,) -> ErrorPosition {
+        let end = (offset.saturating_sub(self.settled,) as usize).min(self.pending.len(),);
+        let (line, column,) = advance(self.line, self.column, &self.pending[..end],);
+        ErrorPosition {
+            line: Some(line,),
+            column: Some(column,),
+            byte_offset: Some(offset,),
+            ..Default::default()
+        }
+    }
+}
+
+/// The line and column after `bytes`, starting at `line` and `column`. UTF-8 continuation
+/// bytes do not start a character, so they do not move the column.
+fn advance(mut line: u64, mut column: u64, bytes: &[u8],) -> (u64, u64,) {
+    for &byte in bytes {
+        if byte == b'\n' {
+            line += 1;
+            column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            column += 1;
+        }
+    }
+    (line, column,)
+}
+
+impl<R: BufRead,> Read for LineTracker<R,> {
+    fn read(&mut self, buf: &mut [u8],) -> std::io::Result<usize,> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len(),);
+        buf[..n].copy_from_slice(&available[..n],);
+        self.consume(n,);
+        Ok(n,)
+    }
+}
+
+impl<R: BufRead,> BufRead for LineTracker<R,> {
+    fn fill_buf(&mut self,) -> std::io::Result<&[u8],> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize,) {
+        // The buffer was filled before being consumed, so this returns it without reading.
+        if let Ok(available,) = self.inner.fill_buf() {
+            self.pending.extend_from_slice(&available[..amt.min(available.len(),)],);
+        }
+        self.inner.consume(amt,);
+    }
+}
+
 pub fn create_xml_stream(
     file_path: &Path,
-) -> Result<crate::nc_reader_result::RecordStream, DataReaderError,> {
+) -> Result<RecordStream, DataReaderError,> {
+    create_xml_stream_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `create_xml_stream` with the null values and boolean words of `config`.
+pub fn create_xml_stream_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<RecordStream, DataReaderError,> {
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
     },)?;
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
+    open_xml_stream(file, file_path, config,)
+}
+
+/// Async variant of `create_xml_stream`; parsing runs on tokio's blocking pool.
+pub async fn create_xml_stream_async(
+    file_path: &Path,
+) -> Result<AsyncRecordStream, DataReaderError,> {
+    let path = file_path.to_path_buf();
+    open_async_stream(move || create_xml_stream(&path,),).await
+}
+
+/// Reads the second-level elements of the document as Arrow record batches, one row per
+/// element. Column types are inferred from the streamed records in a first pass.
+pub fn read_xml_batches(
+    file_path: &Path,
+    batch_size: usize,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    read_xml_batches_with_config(file_path, batch_size, &ReadConfig::default(),)
+}
+
+/// `read_xml_batches` with the null values, boolean words and field types of `config`.
+pub fn read_xml_batches_with_config(
+    file_path: &Path,
+    batch_size: usize,
+    config: &ReadConfig,
+) -> Result<(SchemaRef, RecordBatchStream,), DataReaderError,> {
+    let (columns, types,) = infer_record_columns(
+        create_xml_stream_with_config(file_path, config,)?,
+        crate::reader::json_reader::infer_json_nc_type,
+    )?;
+    let schema = arrow_schema_from_nc_types(&columns, &types,);
+
+    let records = create_xml_stream_with_config(file_path, config,)?;
+    let batches = json_records_to_batches(schema.clone(), records, batch_size, file_path,)?;
+    Ok((schema, batches,),)
+}
+
+/// Streams the second-level elements of an XML document held in memory.
+pub fn create_xml_stream_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
+    create_xml_stream_from_bytes_with_config(data, file_path, &ReadConfig::default(),)
+}
+
+/// `create_xml_stream_from_bytes` with the null values and boolean words of `config`.
+pub fn create_xml_stream_from_bytes_with_config(
+    data: Bytes,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<RecordStream, DataReaderError,> {
+    open_xml_stream(data.reader(), file_path, config,)
+}
+
+/// Streams the second-level elements of an XML document read from any reader. `file_path` is
+/// only used to label errors.
+pub fn create_xml_stream_from_reader<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+) -> Result<RecordStream, DataReaderError,> {
+    create_xml_stream_from_reader_with_config(reader, file_path, &ReadConfig::default(),)
+}
+
+/// `create_xml_stream_from_reader` with the null values and boolean words of `config`.
+pub fn create_xml_stream_from_reader_with_config<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<RecordStream, DataReaderError,> {
+    open_xml_stream(reader, file_path, config,)
+}
+
+fn open_xml_stream<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<RecordStream, DataReaderError,> {
+    let records = open_xml_typed_stream(reader, file_path, config,)?;
+    Ok(Box::new(records.map(|record| record.map(|value| value.to_json(),),),),)
+}
+
+/// Streams the second-level elements of an XML file as `NcValue`s, keeping exact decimals and
+/// integers beyond `i64` that `create_xml_stream` renders as strings.
+pub fn create_xml_typed_stream(file_path: &Path,) -> Result<TypedRecordStream, DataReaderError,> {
+    create_xml_typed_stream_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `create_xml_typed_stream` with the null values, boolean words and field types of `config`.
+pub fn create_xml_typed_stream_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
+        path:   file_path.to_path_buf(),
+        source: e,
+    },)?;
+    open_xml_typed_stream(file, file_path, config,)
+}
+
+/// `create_xml_typed_stream_with_config` for any reader. `file_path` is only used to label
+/// errors.
+pub fn create_xml_typed_stream_from_reader_with_config<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    open_xml_typed_stream(reader, file_path, config,)
+}
+
+fn open_xml_typed_stream<R: Read + Send + 'static,>(
+    reader: R,
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<TypedRecordStream, DataReaderError,> {
+    let decoder = crate::reader::charset::get_decoded_reader_from(reader,).map_err(|e| {
         DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
             source: e,
         }
     },)?;
     let reader = BufReader::new(decoder,);
-    let xml_reader = XmlReader::new(reader, file_path.to_path_buf(),);
+    let xml_reader =
+        XmlReader::new(reader, file_path.to_path_buf(),).with_coercion(config.coercion.clone(),);
     Ok(Box::new(xml_reader,),)
 }
 
@@ -326,25 +605,22 @@ pub struct XmlData {
     pub inferred_schema: Option<XmlSchema,>,
 }
 
-fn identify_nc_type(s: &str,) -> DataType {
-    if s.parse::<i64>().is_ok() {
-        DataType::Integer
-    } else if s.parse::<f64>().is_ok() {
-        DataType::Float
-    } else if s.to_lowercase() == "true" || s.to_lowercase() == "false" {
-        DataType::Boolean
-    } else if s.is_empty() {
-        DataType::Null
-    } else {
-        DataType::String
+/// The type of an attribute or text value of `field`. Decimals and integers beyond `i64` are
+/// `Number`; values that are not of their declared type count as `String`.
+fn identify_nc_type(coercion: &ValueCoercion, field: &str, s: &str,) -> DataType {
+    if s.is_empty() {
+        return DataType::Null;
     }
+    coercion.parse_value(field, s,).map_or(DataType::String, |value| value.data_type(),)
 }
 
 fn infer_xml_schema<R: std::io::BufRead,>(
     reader_input: R,
     file_path: &Path,
+    coercion: &ValueCoercion,
+    budget: &ReadBudget,
 ) -> Result<XmlSchema, DataReaderError,> {
-    let mut reader = Reader::from_reader(reader_input,);
+    let mut reader = Reader::from_reader(LineTracker::new(reader_input,),);
     reader.config_mut().trim_text(true,);
 
     let mut buf = Vec::new();
@@ -352,25 +628,27 @@ fn infer_xml_schema<R: std::io::BufRead,>(
     let mut element_stack: Vec<(XmlSchema, HashMap<String, usize,>,),> = Vec::new();
 
     loop {
+        let offset = reader.buffer_position();
+        reader.get_mut().settle(offset,);
         match reader.read_event_into(&mut buf,) {
             Err(e,) => {
-                return Err(DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
-                },);
+                let position = reader.get_ref().position(reader.error_position(),);
+                return Err(xml_parse_error(file_path, e, position, None,),);
             },
             Ok(Event::Eof,) => break,
             Ok(Event::Start(e,),) => {
+                budget.check()?;
                 let tag_name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
                 let mut attributes = HashMap::new();
                 for attr_result in e.attributes() {
-                    let attr = attr_result.map_err(|e| DataReaderError::ParseError {
-                        path:   file_path.to_path_buf(),
-                        source: Box::new(e,),
+                    let attr = attr_result.map_err(|e| {
+                        let position = reader.get_ref().position(reader.buffer_position(),);
+                        xml_parse_error(file_path, e, position, None,)
                     },)?;
                     let key = String::from_utf8_lossy(attr.key.into_inner(),).to_string();
                     let value = String::from_utf8_lossy(&attr.value,).to_string();
-                    attributes.insert(key, identify_nc_type(&value,),);
+                    let field = format!("@{}", key);
+                    attributes.insert(key, identify_nc_type(coercion, &field, &value,),);
                 }
 
                 let new_schema = XmlSchema {
@@ -447,7 +725,8 @@ fn infer_xml_schema<R: std::io::BufRead,>(
                     let text = String::from_utf8_lossy(&e,).to_string();
                     if !text.trim().is_empty() {
                         current_schema.has_text_content = true;
-                        let new_type = identify_nc_type(&text,);
+                        let new_type =
+                            identify_nc_type(coercion, &current_schema.tag_name, &text,);
                         current_schema.text_content_type = match &current_schema.text_content_type {
                             Some(prev_type,) => Some(merge_nc_types(prev_type.clone(), new_type,),),
                             None => Some(new_type,),
@@ -459,13 +738,14 @@ fn infer_xml_schema<R: std::io::BufRead,>(
                 let tag_name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
                 let mut attributes = HashMap::new();
                 for attr_result in e.attributes() {
-                    let attr = attr_result.map_err(|e| DataReaderError::ParseError {
-                        path:   file_path.to_path_buf(),
-                        source: Box::new(e,),
+                    let attr = attr_result.map_err(|e| {
+                        let position = reader.get_ref().position(reader.buffer_position(),);
+                        xml_parse_error(file_path, e, position, None,)
                     },)?;
                     let key = String::from_utf8_lossy(attr.key.into_inner(),).to_string();
                     let value = String::from_utf8_lossy(&attr.value,).to_string();
-                    attributes.insert(key, identify_nc_type(&value,),);
+                    let field = format!("@{}", key);
+                    attributes.insert(key, identify_nc_type(coercion, &field, &value,),);
                 }
 
                 let new_schema = XmlSchema {
@@ -537,11 +817,12 @@ fn infer_xml_schema<R: std::io::BufRead,>(
         Ok(schema,)
     } else {
         Err(DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
-            source: Box::new(std::io::Error::new(
+            path:     file_path.to_path_buf(),
+            source:   Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "Could not infer XML schema",
             ),),
+            position: None,
         },)
     }
 }
@@ -637,10 +918,19 @@ pub fn read_xml_content(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<XmlData, DataReaderError,> {
-    let num_lines_to_extract = head.unwrap_or(0,);
+    read_xml_content_with_config(file_path, head, &ReadConfig::default(),)
+}
 
-    let first_lines: Option<Vec<String,>,> = if num_lines_to_extract > 0 {
-        use std::io::{BufRead, BufReader};
+/// `read_xml_content` inferring attribute and text types with the null values and boolean
+/// words of `config`, and within its limits: the elements below the root count as records,
+/// and element nesting is measured against `max_depth`.
+pub fn read_xml_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<XmlData, DataReaderError,> {
+    let size = file_path.metadata().map(|m| m.len(),).unwrap_or(0,);
+    let open = || {
         let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
             path:   file_path.to_path_buf(),
             source: e,
@@ -651,8 +941,48 @@ pub fn read_xml_content(
                 source: e,
             }
         },)?;
-        let reader = BufReader::new(decoder,);
-        let lines: Vec<String,> = reader
+        Ok(BufReader::new(decoder,),)
+    };
+    summarize_xml(open, file_path, size, head, config,)
+}
+
+/// `read_xml_content_with_config` for a document held in memory. `file_path` is only used to
+/// label errors.
+pub fn read_xml_content_from_bytes(
+    data: Bytes,
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<XmlData, DataReaderError,> {
+    let size = data.len() as u64;
+    let open = || {
+        let decoder = crate::reader::charset::get_decoded_reader_from(data.clone().reader(),)
+            .map_err(|e| DataReaderError::FileReadError {
+                path:   file_path.to_path_buf(),
+                source: e,
+            },)?;
+        Ok(BufReader::new(decoder,),)
+    };
+    summarize_xml(open, file_path, size, head, config,)
+}
+
+/// Summarizes the document `open` reads from the start, once per pass: the first lines, the
+/// element counts, the inferred schema and, below 10 MiB, the content.
+fn summarize_xml<R, F,>(
+    open: F,
+    file_path: &Path,
+    size: u64,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<XmlData, DataReaderError,>
+where
+    R: std::io::BufRead,
+    F: Fn() -> Result<R, DataReaderError,>,
+{
+    let num_lines_to_extract = head.unwrap_or(0,);
+
+    let first_lines: Option<Vec<String,>,> = if num_lines_to_extract > 0 {
+        let lines: Vec<String,> = open()?
             .lines()
             .take(num_lines_to_extract,)
             .filter_map(|l| l.ok(),)
@@ -662,78 +992,66 @@ pub fn read_xml_content(
         None
     };
 
-    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
-        path:   file_path.to_path_buf(),
-        source: e,
-    },)?;
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-        DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        }
-    },)?;
-    let buf_reader = BufReader::new(decoder,);
-    let mut reader = Reader::from_reader(buf_reader,);
+    let mut reader = Reader::from_reader(LineTracker::new(open()?,),);
     reader.config_mut().trim_text(true,);
 
+    let budget = config.budget(file_path,);
     let mut buf = Vec::new();
     let mut root_element: Option<String,> = None;
     let mut element_counts: HashMap<String, usize,> = HashMap::new();
+    let mut depth = 0usize;
+    let mut records = 0u64;
 
     loop {
-        match reader.read_event_into(&mut buf,) {
+        let offset = reader.buffer_position();
+        reader.get_mut().settle(offset,);
+        let (tag_name, element_depth,) = match reader.read_event_into(&mut buf,) {
             Err(e,) => {
-                return Err(DataReaderError::ParseError {
-                    path:   file_path.to_path_buf(),
-                    source: Box::new(e,),
-                },);
+                let position = reader.get_ref().position(reader.error_position(),);
+                return Err(xml_parse_error(file_path, e, position, None,),);
             },
             Ok(Event::Eof,) => break,
             Ok(Event::Start(e,),) => {
-                let tag_name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
-                if root_element.is_none() {
-                    root_element = Some(tag_name.clone(),);
-                }
-                *element_counts.entry(tag_name,).or_insert(0,) += 1;
+                depth += 1;
+                (String::from_utf8_lossy(e.name().into_inner(),).to_string(), depth,)
             },
             Ok(Event::Empty(e,),) => {
-                let tag_name = String::from_utf8_lossy(e.name().into_inner(),).to_string();
-                if root_element.is_none() {
-                    root_element = Some(tag_name.clone(),);
-                }
-                *element_counts.entry(tag_name,).or_insert(0,) += 1;
+                (String::from_utf8_lossy(e.name().into_inner(),).to_string(), depth + 1,)
             },
-            _ => {},
+            Ok(Event::End(_,),) => {
+                depth = depth.saturating_sub(1,);
+                buf.clear();
+                continue;
+            },
+            _ => {
+                buf.clear();
+                continue;
+            },
+        };
+        budget.check()?;
+        budget.check_depth(element_depth,)?;
+        if element_depth == 2 {
+            records += 1;
+            budget.check_records(records,)?;
+        }
+        if root_element.is_none() {
+            root_element = Some(tag_name.clone(),);
         }
+        *element_counts.entry(tag_name,).or_insert(0,) += 1;
         buf.clear();
     }
 
-    let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
-        path:   file_path.to_path_buf(),
-        source: e,
-    },)?;
-    let decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-        DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        }
-    },)?;
-    let buf_reader = BufReader::new(decoder,);
-    let inferred_schema = infer_xml_schema(buf_reader, file_path,).ok();
+    let inferred_schema = match infer_xml_schema(open()?, file_path, &config.coercion, &budget,) {
+        Ok(schema,) => Some(schema,),
+        Err(
+            e @ (DataReaderError::LimitExceeded { .. } | DataReaderError::Cancelled { .. }),
+        ) => return Err(e,),
+        Err(_,) => None,
+    };
 
-    let content = if file_path.metadata().map(|m| m.len(),).unwrap_or(0,) < 10 * 1024 * 1024 {
-        let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        },)?;
-        let mut decoder = crate::reader::charset::get_decoded_reader(file,).map_err(|e| {
-            DataReaderError::FileReadError {
-                path:   file_path.to_path_buf(),
-                source: e,
-            }
-        },)?;
+    let content = if size < 10 * 1024 * 1024 {
         let mut s = String::new();
-        if decoder.read_to_string(&mut s,).is_err() {
+        if open()?.read_to_string(&mut s,).is_err() {
             "[Error reading content]".to_string()
         } else {
             s
@@ -6,7 +6,9 @@ use encoding_rs_io::DecodeReaderBytes;
 use serde::{Deserialize, Serialize}; // Added
 use serde_yaml::Value;
 
-use crate::error::DataReaderError;
+use crate::error::{DataReaderError, ErrorPosition};
+use crate::reader::limits::{BudgetedReader, read_error};
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)] // Added
 pub struct YamlData {
@@ -18,6 +20,17 @@ pub fn read_yaml_value(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<YamlData, DataReaderError,> {
+    read_yaml_value_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `read_yaml_value` within the limits of `config`: the file is read through a
+/// `BudgetedReader`, and the nesting of the document is measured against `max_depth`.
+pub fn read_yaml_value_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<YamlData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let num_lines_to_extract = head.unwrap_or(0,);
 
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
@@ -25,20 +38,28 @@ pub fn read_yaml_value(
         source: e,
     },)?;
 
-    let mut decoder = DecodeReaderBytes::new(file,);
+    let mut decoder = DecodeReaderBytes::new(BudgetedReader::new(file, budget.clone(),),);
     let mut content = String::new();
-    decoder
-        .read_to_string(&mut content,)
-        .map_err(|e| DataReaderError::FileReadError {
-            path:   file_path.to_path_buf(),
-            source: e,
-        },)?;
+    decoder.read_to_string(&mut content,).map_err(|e| read_error(file_path, e,),)?;
 
-    let value: Value =
-        serde_yaml::from_str(&content,).map_err(|e| DataReaderError::ParseError {
-            path:   file_path.to_path_buf(),
+    let value: Value = serde_yaml::from_str(&content,).map_err(|e| {
+        let position = e.location().map(|loc| {
+            Box::new(ErrorPosition {
+                line: Some(loc.line() as u64,),
+                column: Some(loc.column() as u64,),
+                byte_offset: Some(loc.index() as u64,),
+                ..Default::default()
+            },)
+        },);
+        DataReaderError::ParseError {
+            path: file_path.to_path_buf(),
             source: Box::new(e,),
-        },)?;
+            position,
+        }
+    },)?;
+
+    budget.check()?;
+    budget.check_depth(yaml_depth(&value,),)?;
 
     let first_lines: Option<Vec<String,>,> = if num_lines_to_extract > 0 {
         let lines: Vec<String,> = content
@@ -60,9 +81,28 @@ pub fn get_yaml_raw_content(
     file_path: &Path,
     head: Option<usize,>,
 ) -> Result<String, DataReaderError,> {
-    let yaml_data = read_yaml_value(file_path, head,)?; // Use the new read_yaml_value that returns YamlData
+    get_yaml_raw_content_with_config(file_path, head, &ReadConfig::default(),)
+}
+
+/// `get_yaml_raw_content` within the limits of `config`.
+pub fn get_yaml_raw_content_with_config(
+    file_path: &Path,
+    head: Option<usize,>,
+    config: &ReadConfig,
+) -> Result<String, DataReaderError,> {
+    let yaml_data = read_yaml_value_with_config(file_path, head, config,)?;
 
     serde_json::to_string_pretty(&yaml_data.value,).map_err(|e| {
         DataReaderError::InternalError(format!("Failed to serialize YAML to JSON: {}", e),)
     },)
 }
+
+/// Nesting depth of a YAML value, counted like `json_depth`. Tags do not add a level.
+fn yaml_depth(value: &Value,) -> usize {
+    match value {
+        Value::Sequence(items,) => 1 + items.iter().map(yaml_depth,).max().unwrap_or(0,),
+        Value::Mapping(mapping,) => 1 + mapping.values().map(yaml_depth,).max().unwrap_or(0,),
+        Value::Tagged(tagged,) => yaml_depth(&tagged.value,),
+        _ => 1,
+    }
+}
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use zip::ZipArchive;
 
 use crate::error::DataReaderError;
+use crate::reader::read_config::ReadConfig;
 
 #[derive(Debug, Serialize, Deserialize, Clone,)]
 pub struct ZipEntryInfo {
@@ -21,6 +22,16 @@ pub struct ZipData {
 }
 
 pub fn read_zip_data(file_path: &Path,) -> Result<ZipData, DataReaderError,> {
+    read_zip_data_with_config(file_path, &ReadConfig::default(),)
+}
+
+/// `read_zip_data` within the limits of `config`: every entry listed counts as a record, and
+/// cancellation and the deadline are checked before each one.
+pub fn read_zip_data_with_config(
+    file_path: &Path,
+    config: &ReadConfig,
+) -> Result<ZipData, DataReaderError,> {
+    let budget = config.budget(file_path,);
     let file = File::open(file_path,).map_err(|e| DataReaderError::FileReadError {
         path:   file_path.to_path_buf(),
         source: e,
@@ -34,18 +45,22 @@ pub fn read_zip_data(file_path: &Path,) -> Result<ZipData, DataReaderError,> {
         .len();
 
     let mut archive = ZipArchive::new(file,).map_err(|e| DataReaderError::ParseError {
-        path:   file_path.to_path_buf(),
-        source: Box::new(e,),
+        path:     file_path.to_path_buf(),
+        source:   Box::new(e,),
+        position: None,
     },)?;
     let entry_count = archive.len();
 
     let mut entries_info = Vec::new();
     for i in 0..archive.len() {
+        budget.check()?;
+        budget.check_records(i as u64 + 1,)?;
         let file = archive
             .by_index(i,)
             .map_err(|e| DataReaderError::ParseError {
-                path:   file_path.to_path_buf(),
-                source: Box::new(e,),
+                path:     file_path.to_path_buf(),
+                source:   Box::new(e,),
+                position: None,
             },)?;
 
         let outpath = match file.enclosed_name() {
@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat};
+use nc_schema::{DataType, merge_nc_types};
+use serde::{Serialize, Serializer};
+
+/// A single value produced by a streaming reader.
+///
+/// The variants follow `nc_schema::DataType`, but keep the information that `serde_json::Value`
+/// cannot carry: exact decimals, integers beyond `i64`, dates, timestamps and binary data.
+/// Use [`NcValue::to_json`] (or `Serialize`) for output; the conversion never loses precision.
+#[derive(Debug, Clone, PartialEq,)]
+pub enum NcValue {
+    Null,
+    Boolean(bool,),
+    Integer(i64,),
+    /// An integer outside the `i64` range, such as a large `u64` column.
+    BigInteger(i128,),
+    Float(f64,),
+    /// A fixed-point decimal equal to `unscaled * 10^-scale`.
+    Decimal {
+        unscaled: i128,
+        scale:    i8,
+    },
+    String(String,),
+    Date(NaiveDate,),
+    /// A timestamp with a known UTC offset.
+    Timestamp(DateTime<FixedOffset,>,),
+    /// A timestamp without timezone information.
+    LocalTimestamp(NaiveDateTime,),
+    Binary(Vec<u8,>,),
+    Array(Vec<NcValue,>,),
+    Object(BTreeMap<String, NcValue,>,),
+}
+
+impl NcValue {
+    /// The `nc_schema` type of this value. Decimals and big integers map to `Number`, and
+    /// timestamps with and without an offset to `Timestamp`.
+    pub fn data_type(&self,) -> DataType {
+        match self {
+            NcValue::Null => DataType::Null,
+            NcValue::Boolean(_,) => DataType::Boolean,
+            NcValue::Integer(_,) => DataType::Integer,
+            NcValue::Float(_,) => DataType::Float,
+            NcValue::BigInteger(_,) | NcValue::Decimal { .. } => DataType::Number,
+            NcValue::String(_,) => DataType::String,
+            NcValue::Date(_,) => DataType::Date,
+            NcValue::Timestamp(_,) | NcValue::LocalTimestamp(_,) => DataType::Timestamp,
+            NcValue::Binary(_,) => DataType::Binary,
+            NcValue::Array(items,) => {
+                let element_type = items
+                    .iter()
+                    .map(NcValue::data_type,)
+                    .reduce(merge_nc_types,)
+                    .unwrap_or(DataType::Unknown,);
+                DataType::Array(Box::new(element_type,),)
+            },
+            NcValue::Object(fields,) => DataType::Object(
+                fields
+                    .iter()
+                    .map(|(k, v,)| (k.clone(), v.data_type(),),)
+                    .collect::<HashMap<String, DataType,>>(),
+            ),
+        }
+    }
+
+    pub fn is_null(&self,) -> bool {
+        matches!(self, NcValue::Null)
+    }
+
+    /// Reads a plain decimal number (`-12`, `3.25`, `6.02e23`) into the narrowest value that
+    /// holds it exactly: `Integer`, `BigInteger`, `Float` when `f64` keeps every significant
+    /// digit, and `Decimal` otherwise. `None` for anything else, including numbers with more
+    /// digits than a decimal holds, so callers can keep the text.
+    pub fn parse_number(text: &str,) -> Option<NcValue,> {
+        let parts = DecimalParts::parse(text,)?;
+        if let Ok(i,) = text.parse::<i64>() {
+            return Some(NcValue::Integer(i,),);
+        }
+        if parts.integer {
+            return text.parse::<i128>().ok().map(NcValue::BigInteger,);
+        }
+        let f: f64 = text.parse().ok()?;
+        if f.is_finite()
+            && DecimalParts::parse(&format!("{:e}", f),)
+                .is_some_and(|float| float.significant() == parts.significant(),)
+        {
+            return Some(NcValue::Float(f,),);
+        }
+        parts.to_decimal()
+    }
+
+    /// Reads a plain decimal number as an exact `Decimal`, whatever its digits. `None` when it
+    /// is not a number or has more digits than a decimal holds.
+    pub fn parse_decimal(text: &str,) -> Option<NcValue,> {
+        DecimalParts::parse(text,)?.to_decimal()
+    }
+
+    /// Converts to JSON without losing precision. Values that JSON numbers cannot hold exactly
+    /// (decimals, big integers, non-finite floats) become strings; dates and timestamps become
+    /// ISO 8601 strings and binary data becomes lowercase hex.
+    pub fn to_json(&self,) -> serde_json::Value {
+        match self {
+            NcValue::Null => serde_json::Value::Null,
+            NcValue::Boolean(b,) => serde_json::Value::Bool(*b,),
+            NcValue::Integer(i,) => serde_json::Value::from(*i,),
+            NcValue::BigInteger(i,) => match u64::try_from(*i,) {
+                Ok(u,) => serde_json::Value::from(u,),
+                Err(_,) => serde_json::Value::String(i.to_string(),),
+            },
+            NcValue::Float(f,) => serde_json::Number::from_f64(*f,)
+                .map(serde_json::Value::Number,)
+                .unwrap_or_else(|| serde_json::Value::String(f.to_string(),),),
+            NcValue::Decimal {
+                unscaled,
+                scale,
+            } => serde_json::Value::String(format_decimal(*unscaled, *scale,),),
+            NcValue::String(s,) => serde_json::Value::String(s.clone(),),
+            NcValue::Date(d,) => serde_json::Value::String(d.format("%Y-%m-%d",).to_string(),),
+            NcValue::Timestamp(ts,) => {
+                serde_json::Value::String(ts.to_rfc3339_opts(SecondsFormat::AutoSi, true,),)
+            },
+            NcValue::LocalTimestamp(ts,) => {
+                serde_json::Value::String(ts.format("%Y-%m-%dT%H:%M:%S%.f",).to_string(),)
+            },
+            NcValue::Binary(bytes,) => serde_json::Value::String(
+                bytes.iter().map(|b| format!("{:02x}", b),).collect(),
+            ),
+            NcValue::Array(items,) => {
+                serde_json::Value::Array(items.iter().map(NcValue::to_json,).collect(),)
+            },
+            NcValue::Object(fields,) => serde_json::Value::Object(
+                fields
+                    .iter()
+                    .map(|(k, v,)| (k.clone(), v.to_json(),),)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Renders `unscaled * 10^-scale` in plain decimal notation, e.g. `(-12345, 2)` as `-123.45`.
+pub fn format_decimal(unscaled: i128, scale: i8,) -> String {
+    if scale <= 0 {
+        let zeros = "0".repeat(scale.unsigned_abs() as usize,);
+        return if unscaled == 0 {
+            "0".to_string()
+        } else {
+            format!("{}{}", unscaled, zeros)
+        };
+    }
+
+    let scale = scale as usize;
+    let digits = unscaled.unsigned_abs().to_string();
+    let sign = if unscaled < 0 { "-" } else { "" };
+    if digits.len() > scale {
+        let (int_part, frac_part,) = digits.split_at(digits.len() - scale,);
+        format!("{}{}.{}", sign, int_part, frac_part)
+    } else {
+        format!("{}0.{}{}", sign, "0".repeat(scale - digits.len(),), digits)
+    }
+}
+
+/// A decimal number as written: `[-]digits[.digits][e[-]digits]`.
+struct DecimalParts<'a,> {
+    negative: bool,
+    whole:    &'a str,
+    fraction: &'a str,
+    exponent: Option<i32,>,
+    /// Written without a decimal point or exponent.
+    integer:  bool,
+}
+
+impl<'a,> DecimalParts<'a,> {
+    fn parse(text: &'a str,) -> Option<Self,> {
+        let (negative, rest,) = match text.strip_prefix('-',) {
+            Some(rest,) => (true, rest,),
+            None => (false, text.strip_prefix('+',).unwrap_or(text,),),
+        };
+        let (mantissa, exponent,) = match rest.split_once(['e', 'E'],) {
+            Some((mantissa, exponent,),) => (mantissa, Some(exponent.parse().ok()?,),),
+            None => (rest, None,),
+        };
+        let (whole, fraction,) = mantissa.split_once('.',).unwrap_or((mantissa, "",),);
+        let digits = whole.len() + fraction.len();
+        let valid = digits > 0
+            && whole.bytes().all(|b| b.is_ascii_digit(),)
+            && fraction.bytes().all(|b| b.is_ascii_digit(),);
+        valid.then_some(DecimalParts {
+            negative,
+            whole,
+            fraction,
+            exponent,
+            integer: exponent.is_none() && !mantissa.contains('.',),
+        },)
+    }
+
+    /// The sign, the digits without leading or trailing zeros, and the power of ten of the
+    /// last of them: equal for two texts exactly when they are the same number.
+    fn significant(&self,) -> (bool, String, i64,) {
+        let digits = format!("{}{}", self.whole, self.fraction);
+        let trimmed = digits.trim_start_matches('0',).trim_end_matches('0',);
+        if trimmed.is_empty() {
+            return (false, String::new(), 0,);
+        }
+        let trailing = (digits.len() - digits.trim_end_matches('0',).len()) as i64;
+        let power = self.exponent.unwrap_or(0,) as i64 - self.fraction.len() as i64 + trailing;
+        (self.negative, trimmed.to_string(), power,)
+    }
+
+    fn to_decimal(&self,) -> Option<NcValue,> {
+        let digits = format!("{}{}", self.whole, self.fraction);
+        let significant = digits.trim_start_matches('0',);
+        let magnitude: i128 = if significant.is_empty() { 0 } else { significant.parse().ok()? };
+        let scale = self.fraction.len() as i64 - self.exponent.unwrap_or(0,) as i64;
+        Some(NcValue::Decimal {
+            unscaled: if self.negative { -magnitude } else { magnitude },
+            scale:    i8::try_from(scale,).ok()?,
+        },)
+    }
+}
+
+impl From<serde_json::Value,> for NcValue {
+    fn from(value: serde_json::Value,) -> Self {
+        match value {
+            serde_json::Value::Null => NcValue::Null,
+            serde_json::Value::Bool(b,) => NcValue::Boolean(b,),
+            serde_json::Value::Number(n,) => {
+                if let Some(i,) = n.as_i64() {
+                    NcValue::Integer(i,)
+                } else if let Some(u,) = n.as_u64() {
+                    NcValue::BigInteger(u as i128,)
+                } else {
+                    NcValue::Float(n.as_f64().unwrap_or(f64::NAN,),)
+                }
+            },
+            serde_json::Value::String(s,) => NcValue::String(s,),
+            serde_json::Value::Array(items,) => {
+                NcValue::Array(items.into_iter().map(NcValue::from,).collect(),)
+            },
+            serde_json::Value::Object(fields,) => NcValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(k, v,)| (k, NcValue::from(v,),),)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<NcValue,> for serde_json::Value {
+    fn from(value: NcValue,) -> Self {
+        value.to_json()
+    }
+}
+
+impl Serialize for NcValue {
+    fn serialize<S: Serializer,>(&self, serializer: S,) -> Result<S::Ok, S::Error,> {
+        self.to_json().serialize(serializer,)
+    }
+}
+
+impl fmt::Display for NcValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self.to_json() {
+            serde_json::Value::String(s,) => write!(f, "{}", s),
+            other => write!(f, "{}", other),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use tempfile::{Builder, NamedTempFile};
+
+use crate::error::DataReaderError;
+
+/// Compression of the bytes written to an [`OutputSink`], on top of the output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum,)]
+pub enum SinkCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl fmt::Display for SinkCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_,>,) -> fmt::Result {
+        match self {
+            SinkCompression::None => write!(f, "none"),
+            SinkCompression::Gzip => write!(f, "gzip"),
+            SinkCompression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl SinkCompression {
+    /// The compression named by the last extension of `path`: `.gz` or `.zst`.
+    pub fn from_extension(path: &Path,) -> Option<SinkCompression,> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(SinkCompression::Gzip,),
+            "zst" | "zstd" => Some(SinkCompression::Zstd,),
+            _ => None,
+        }
+    }
+
+    /// `path` without a compression extension, for inferring the output format:
+    /// `out.json.gz` gives `out.json`.
+    pub fn strip_extension(path: &Path,) -> PathBuf {
+        match SinkCompression::from_extension(path,) {
+            Some(_,) => path.with_extension("",),
+            None => path.to_path_buf(),
+        }
+    }
+}
+
+/// How an [`OutputSink`] writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq,)]
+pub struct SinkOptions {
+    /// `None` infers the compression from the file extension; stdout is then uncompressed.
+    pub compression: Option<SinkCompression,>,
+    /// Write a file through a temporary file in the same directory, renamed over the target by
+    /// [`OutputSink::finish`], so the target is never seen half-written. The file gets the
+    /// permissions of the target it replaces, or those of a newly created file.
+    pub atomic:      bool,
+}
+
+/// Where the (compressed) bytes end up.
+enum Target {
+    Stdout(io::Stdout,),
+    File(File,),
+    Temp(NamedTempFile,),
+}
+
+impl Write for Target {
+    fn write(&mut self, buf: &[u8],) -> io::Result<usize,> {
+        match self {
+            Target::Stdout(out,) => out.write(buf,),
+            Target::File(file,) => file.write(buf,),
+            Target::Temp(file,) => file.write(buf,),
+        }
+    }
+
+    fn flush(&mut self,) -> io::Result<(),> {
+        match self {
+            Target::Stdout(out,) => out.flush(),
+            Target::File(file,) => file.flush(),
+            Target::Temp(file,) => file.flush(),
+        }
+    }
+}
+
+enum Encoder {
+    Plain(BufWriter<Target,>,),
+    Gzip(GzEncoder<BufWriter<Target,>,>,),
+    Zstd(zstd::Encoder<'static, BufWriter<Target,>,>,),
+}
+
+/// Data output to stdout or a file, written incrementally and optionally compressed. Logging
+/// never goes through a sink, so stdout carries only data.
+///
+/// Call [`OutputSink::finish`] once everything is written: it ends the compressed stream and,
+/// for atomic writes, moves the file into place. A sink dropped without `finish` leaves an
+/// atomic target untouched.
+pub struct OutputSink {
+    encoder: Encoder,
+    /// The final path of an atomic write.
+    path:    Option<PathBuf,>,
+}
+
+impl OutputSink {
+    /// A sink writing to `path`, or to stdout for `None` or `-`.
+    pub fn open(path: Option<&Path,>, options: SinkOptions,) -> Result<Self, DataReaderError,> {
+        match path {
+            Some(path,) if path.as_os_str() != "-" => Self::file(path, options,),
+            _ => Self::stdout(options.compression.unwrap_or_default(),),
+        }
+    }
+
+    pub fn stdout(compression: SinkCompression,) -> Result<Self, DataReaderError,> {
+        Self::new(Target::Stdout(io::stdout(),), compression, None,)
+    }
+
+    pub fn file(path: &Path, options: SinkOptions,) -> Result<Self, DataReaderError,> {
+        let compression = options
+            .compression
+            .or_else(|| SinkCompression::from_extension(path,),)
+            .unwrap_or_default();
+        let create_error = |e: io::Error| {
+            DataReaderError::InternalError(format!(
+                "Failed to create output file {}: {}",
+                path.display(),
+                e
+            ),)
+        };
+        if options.atomic {
+            let dir = match path.parent() {
+                Some(dir,) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new(".",),
+            };
+            let mut builder = Builder::new();
+            // Temporary files are private (0600); ask for what `File::create` asks for, so a
+            // new target gets the default permissions under the umask.
+            #[cfg(unix)]
+            builder.permissions(std::os::unix::fs::PermissionsExt::from_mode(0o666,),);
+            let temp = builder.tempfile_in(dir,).map_err(create_error,)?;
+            Self::new(Target::Temp(temp,), compression, Some(path.to_path_buf(),),)
+        } else {
+            let file = File::create(path,).map_err(create_error,)?;
+            Self::new(Target::File(file,), compression, None,)
+        }
+    }
+
+    fn new(
+        target: Target,
+        compression: SinkCompression,
+        path: Option<PathBuf,>,
+    ) -> Result<Self, DataReaderError,> {
+        let buffered = BufWriter::new(target,);
+        let encoder = match compression {
+            SinkCompression::None => Encoder::Plain(buffered,),
+            SinkCompression::Gzip => {
+                Encoder::Gzip(GzEncoder::new(buffered, flate2::Compression::default(),),)
+            },
+            SinkCompression::Zstd => {
+                Encoder::Zstd(zstd::Encoder::new(buffered, 0,).map_err(write_error,)?,)
+            },
+        };
+        Ok(Self {
+            encoder,
+            path,
+        },)
+    }
+
+    /// Ends the compressed stream, flushes, and renames an atomic write over its target. A
+    /// closed stdout pipe is not an error.
+    pub fn finish(self,) -> Result<(), DataReaderError,> {
+        let finished = match self.encoder {
+            Encoder::Plain(buffered,) => Ok(buffered,),
+            Encoder::Gzip(encoder,) => encoder.finish(),
+            Encoder::Zstd(encoder,) => encoder.finish(),
+        };
+        let target = match finished.and_then(|buffered| {
+            buffered.into_inner().map_err(|e| e.into_error(),)
+        },) {
+            Ok(target,) => target,
+            Err(e,) if e.kind() == io::ErrorKind::BrokenPipe => return Ok((),),
+            Err(e,) => return Err(write_error(e,),),
+        };
+        match (target, self.path,) {
+            (Target::Temp(temp,), Some(path,),) => {
+                if let Ok(existing,) = std::fs::metadata(&path,) {
+                    temp.as_file().set_permissions(existing.permissions(),).map_err(write_error,)?;
+                }
+                temp.as_file().sync_all().map_err(write_error,)?;
+                temp.persist(&path,).map_err(|e| {
+                    DataReaderError::InternalError(format!(
+                        "Failed to move output into place at {}: {}",
+                        path.display(),
+                        e
+                    ),)
+                },)?;
+                Ok((),)
+            },
+            (mut target, _,) => match target.flush() {
+                Err(e,) if e.kind() != io::ErrorKind::BrokenPipe => Err(write_error(e,),),
+                _ => Ok((),),
+            },
+        }
+    }
+}
+
+impl Write for OutputSink {
+    fn write(&mut self, buf: &[u8],) -> io::Result<usize,> {
+        match &mut self.encoder {
+            Encoder::Plain(writer,) => writer.write(buf,),
+            Encoder::Gzip(writer,) => writer.write(buf,),
+            Encoder::Zstd(writer,) => writer.write(buf,),
+        }
+    }
+
+    fn flush(&mut self,) -> io::Result<(),> {
+        match &mut self.encoder {
+            Encoder::Plain(writer,) => writer.flush(),
+            Encoder::Gzip(writer,) => writer.flush(),
+            Encoder::Zstd(writer,) => writer.flush(),
+        }
+    }
+}
+
+fn write_error(e: io::Error,) -> DataReaderError {
+    DataReaderError::InternalError(format!("Failed to write output: {}", e),)
+}
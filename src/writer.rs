@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::DataReaderError;
+use crate::nc_reader_result::RecordStream;
+use crate::output::OutputFormat;
+
+/// Writes records one at a time in any `OutputFormat`, so memory use does not grow with the
+/// input: one JSON object per line for `Json`, `Ndjson` and `Text`, one YAML document per
+/// record for `Yaml`, one flattened row per record for `Csv` and `Tsv`, a `<record>` element
+/// per record for `Xml` and a `[[records]]` table per record for `Toml`.
+///
+/// CSV and TSV hold back the first [`HEADER_SAMPLE_RECORDS`] records and take their header
+/// from every column those records have (unless `with_columns` gave one). Records missing a
+/// column leave it empty; a later record with a column outside the header is an error rather
+/// than being written without it.
+pub struct RecordWriter<'a, W: Write + ?Sized,> {
+    writer:  &'a mut W,
+    format:  OutputFormat,
+    columns: Option<Vec<String,>,>,
+    index:   HashMap<String, usize,>,
+    sample:  Vec<Vec<(String, String,),>,>,
+    rows:    u64,
+    started: bool,
+}
+
+/// How many records CSV and TSV output reads before writing its header.
+pub const HEADER_SAMPLE_RECORDS: usize = 1000;
+
+impl<'a, W: Write + ?Sized,> RecordWriter<'a, W,> {
+    pub fn new(writer: &'a mut W, format: OutputFormat,) -> Self {
+        Self {
+            writer,
+            format,
+            columns: None,
+            index: HashMap::new(),
+            sample: Vec::new(),
+            rows: 0,
+            started: false,
+        }
+    }
+
+    /// Fixes the CSV and TSV header instead of taking it from the first records.
+    pub fn with_columns(mut self, columns: Vec<String,>,) -> Self {
+        self.columns = Some(columns,);
+        self
+    }
+
+    /// Writes one record. Returns `false` if the reading end of a pipe was closed
+    /// (`nc_reader ... | head`), after which nothing more should be written.
+    pub fn write<T: Serialize + ?Sized,>(&mut self, record: &T,) -> Result<bool, DataReaderError,> {
+        let mut document = String::new();
+        match self.format {
+            OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Text => {
+                document.push_str(&serde_json::to_string(record,).map_err(serialize_error,)?,);
+                document.push('\n',);
+            },
+            OutputFormat::Yaml => {
+                document.push_str("---\n",);
+                document.push_str(&serde_yaml::to_string(record,).map_err(serialize_error,)?,);
+            },
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                self.sample.push(flatten(&to_json(record,)?,),);
+                if self.columns.is_none() && self.sample.len() < HEADER_SAMPLE_RECORDS {
+                    return Ok(true,);
+                }
+                return self.write_rows();
+            },
+            OutputFormat::Xml => {
+                if !self.started {
+                    document.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records>\n",);
+                }
+                write_xml_element(&mut document, "record", &to_json(record,)?, 1,);
+            },
+            OutputFormat::Toml => {
+                let record = match to_json(record,)? {
+                    table @ Value::Object(_,) => table,
+                    other => serde_json::json!({ "value": other }),
+                };
+                let records = Value::Array(vec![record],);
+                let table = toml_document(&serde_json::json!({ "records": records }),);
+                document.push_str(&toml::to_string(&table,).map_err(serialize_error,)?,);
+                document.push('\n',);
+            },
+            OutputFormat::Parquet | OutputFormat::ArrowIpc => {
+                return Err(columnar_error(self.format,),);
+            },
+        }
+        self.started = true;
+        self.write_raw(&document,)
+    }
+
+    /// Writes what follows the last record: the CSV and TSV records still held back for the
+    /// header, or the closing `</records>` of XML output.
+    pub fn finish(mut self,) -> Result<bool, DataReaderError,> {
+        match self.format {
+            OutputFormat::Csv | OutputFormat::Tsv if !self.sample.is_empty() => self.write_rows(),
+            OutputFormat::Xml if self.started => self.write_raw("</records>\n",),
+            OutputFormat::Xml => self.write_raw(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records/>\n",
+            ),
+            _ => Ok(true,),
+        }
+    }
+
+    /// Writes the held-back CSV or TSV records, preceded by the header if it was not written yet.
+    fn write_rows(&mut self,) -> Result<bool, DataReaderError,> {
+        let mut document = String::new();
+        if !self.started {
+            let columns = match self.columns.take() {
+                Some(columns,) => columns,
+                None => sample_columns(&self.sample,),
+            };
+            document.push_str(&delimited_line(&columns, self.format,)?,);
+            self.index =
+                columns.iter().enumerate().map(|(i, column,)| (column.clone(), i,),).collect();
+            self.columns = Some(columns,);
+            self.started = true;
+        }
+        for fields in std::mem::take(&mut self.sample,) {
+            self.rows += 1;
+            let mut row = vec![String::new(); self.index.len()];
+            for (column, value,) in fields {
+                let Some(&i,) = self.index.get(&column,) else {
+                    return Err(DataReaderError::InternalError(format!(
+                        "Record {} has column `{}`, which is not in the {} header taken from the \
+                         first {} records",
+                        self.rows, column, self.format, HEADER_SAMPLE_RECORDS
+                    ),),);
+                };
+                row[i] = value;
+            }
+            document.push_str(&delimited_line(&row, self.format,)?,);
+        }
+        self.write_raw(&document,)
+    }
+
+    fn write_raw(&mut self, document: &str,) -> Result<bool, DataReaderError,> {
+        match self.writer.write_all(document.as_bytes(),) {
+            Ok((),) => Ok(true,),
+            Err(e,) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false,),
+            Err(e,) => {
+                Err(DataReaderError::InternalError(format!("Failed to write output: {}", e),),)
+            },
+        }
+    }
+}
+
+/// Writes each record of `stream` to `writer` with a [`RecordWriter`] as soon as it is read.
+/// Returns the number of records written.
+pub fn write_record_stream<W: Write + ?Sized,>(
+    stream: RecordStream,
+    format: OutputFormat,
+    writer: &mut W,
+) -> Result<u64, DataReaderError,> {
+    let mut records = RecordWriter::new(writer, format,);
+    let mut written = 0u64;
+    for record in stream {
+        if !records.write(&record?,)? {
+            return Ok(written,);
+        }
+        written += 1;
+    }
+    records.finish()?;
+    Ok(written,)
+}
+
+/// Renders a whole value in `format`. Arrays become one record each in the record-oriented
+/// formats (NDJSON, CSV, TSV); `Text` has no generic rendering and is rejected.
+pub fn format_value(value: &Value, format: OutputFormat,) -> Result<String, DataReaderError,> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value,).map_err(serialize_error,),
+        OutputFormat::Yaml => serde_yaml::to_string(value,).map_err(serialize_error,),
+        OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::Tsv => {
+            let mut output = Vec::new();
+            let mut records = RecordWriter::new(&mut output, format,);
+            match value {
+                Value::Array(items,) => {
+                    // CSV and TSV take their columns from every row, not just a sample.
+                    if matches!(format, OutputFormat::Csv | OutputFormat::Tsv) {
+                        records = records.with_columns(union_columns(items,),);
+                    }
+                    for item in items {
+                        records.write(item,)?;
+                    }
+                },
+                other => {
+                    records.write(other,)?;
+                },
+            }
+            records.finish()?;
+            String::from_utf8(output,).map_err(serialize_error,)
+        },
+        OutputFormat::Xml => {
+            let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",);
+            write_xml_element(&mut output, "result", value, 0,);
+            Ok(output,)
+        },
+        OutputFormat::Toml => toml::to_string(&toml_document(value,),).map_err(serialize_error,),
+        OutputFormat::Text => Err(DataReaderError::InternalError(
+            "Text output has no generic value rendering".to_string(),
+        ),),
+        OutputFormat::Parquet | OutputFormat::ArrowIpc => Err(columnar_error(format,),),
+    }
+}
+
+fn columnar_error(format: OutputFormat,) -> DataReaderError {
+    DataReaderError::InternalError(format!(
+        "{} is a binary columnar format; write it with crate::columnar",
+        format
+    ),)
+}
+
+fn serialize_error(e: impl std::fmt::Display,) -> DataReaderError {
+    DataReaderError::InternalError(format!("Failed to serialize output: {}", e),)
+}
+
+fn to_json<T: Serialize + ?Sized,>(value: &T,) -> Result<Value, DataReaderError,> {
+    serde_json::to_value(value,).map_err(serialize_error,)
+}
+
+/// Flattens a value into `(column, cell)` pairs. Nested keys are joined with `.` and array
+/// elements are addressed by index (`tags.0`); a scalar becomes a single `value` column.
+pub fn flatten(value: &Value,) -> Vec<(String, String,),> {
+    let mut fields = Vec::new();
+    match value {
+        Value::Object(_,) | Value::Array(_,) => flatten_into(value, "", &mut fields,),
+        scalar => fields.push(("value".to_string(), cell(scalar,),),),
+    }
+    fields
+}
+
+fn flatten_into(value: &Value, prefix: &str, fields: &mut Vec<(String, String,),>,) {
+    let key = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        }
+    };
+    match value {
+        Value::Object(map,) => {
+            for (name, child,) in map {
+                flatten_into(child, &key(name,), fields,);
+            }
+        },
+        Value::Array(items,) => {
+            for (index, child,) in items.iter().enumerate() {
+                flatten_into(child, &key(&index.to_string(),), fields,);
+            }
+        },
+        scalar => fields.push((prefix.to_string(), cell(scalar,),),),
+    }
+}
+
+fn cell(value: &Value,) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s,) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The flattened columns of every row, in the order they are first met.
+fn union_columns(rows: &[Value],) -> Vec<String,> {
+    let rows: Vec<_,> = rows.iter().map(flatten,).collect();
+    sample_columns(&rows,)
+}
+
+/// The columns of already flattened rows, in the order they are first met.
+fn sample_columns(rows: &[Vec<(String, String,),>],) -> Vec<String,> {
+    let mut seen = HashSet::new();
+    let mut columns = Vec::new();
+    for (column, _,) in rows.iter().flatten() {
+        if seen.insert(column.as_str(),) {
+            columns.push(column.clone(),);
+        }
+    }
+    columns
+}
+
+fn delimited_line(fields: &[String], format: OutputFormat,) -> Result<String, DataReaderError,> {
+    let delimiter = if format == OutputFormat::Tsv { b'\t' } else { b',' };
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter,)
+        .from_writer(Vec::new(),);
+    writer.write_record(fields,).map_err(serialize_error,)?;
+    let line = writer.into_inner().map_err(serialize_error,)?;
+    String::from_utf8(line,).map_err(serialize_error,)
+}
+
+/// Appends `value` as an element named `name`, indented by `depth` levels. Array elements
+/// become `<item>` children; keys that are not valid XML names become `<entry key="...">`.
+fn write_xml_element(output: &mut String, name: &str, value: &Value, depth: usize,) {
+    let indent = "  ".repeat(depth,);
+    let (open, close,) = if is_xml_name(name,) {
+        (name.to_string(), name.to_string(),)
+    } else {
+        (format!("entry key=\"{}\"", quick_xml::escape::escape(name)), "entry".to_string(),)
+    };
+    match value {
+        Value::Null => output.push_str(&format!("{}<{}/>\n", indent, open),),
+        Value::Object(map,) if !map.is_empty() => {
+            output.push_str(&format!("{}<{}>\n", indent, open),);
+            for (key, child,) in map {
+                write_xml_element(output, key, child, depth + 1,);
+            }
+            output.push_str(&format!("{}</{}>\n", indent, close),);
+        },
+        Value::Array(items,) if !items.is_empty() => {
+            output.push_str(&format!("{}<{}>\n", indent, open),);
+            for item in items {
+                write_xml_element(output, "item", item, depth + 1,);
+            }
+            output.push_str(&format!("{}</{}>\n", indent, close),);
+        },
+        Value::Object(_,) | Value::Array(_,) => {
+            output.push_str(&format!("{}<{}/>\n", indent, open),)
+        },
+        scalar => output.push_str(&format!(
+            "{}<{}>{}</{}>\n",
+            indent,
+            open,
+            quick_xml::escape::escape(cell(scalar,).as_str()),
+            close
+        ),),
+    }
+}
+
+fn is_xml_name(name: &str,) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_',)
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'),)
+}
+
+/// Converts a value into a TOML document. TOML has no null, so nulls are left out, and a
+/// document must be a table: arrays are wrapped as `records` and scalars as `value`.
+fn toml_document(value: &Value,) -> toml::Table {
+    match toml_value(value,) {
+        Some(toml::Value::Table(table,),) => table,
+        Some(array @ toml::Value::Array(_,),) => {
+            toml::Table::from_iter([("records".to_string(), array,)],)
+        },
+        Some(scalar,) => toml::Table::from_iter([("value".to_string(), scalar,)],),
+        None => toml::Table::new(),
+    }
+}
+
+fn toml_value(value: &Value,) -> Option<toml::Value,> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b,) => Some(toml::Value::Boolean(*b,),),
+        Value::Number(n,) => Some(match (n.as_i64(), n.as_f64(),) {
+            (Some(i,), _,) => toml::Value::Integer(i,),
+            // Integers beyond i64 are kept exact as strings rather than rounded to a float.
+            (None, _,) if n.is_u64() => toml::Value::String(n.to_string(),),
+            (None, Some(f,),) => toml::Value::Float(f,),
+            (None, None,) => toml::Value::String(n.to_string(),),
+        },),
+        Value::String(s,) => Some(toml::Value::String(s.clone(),),),
+        Value::Array(items,) => {
+            Some(toml::Value::Array(items.iter().filter_map(toml_value,).collect(),),)
+        },
+        Value::Object(map,) => Some(toml::Value::Table(
+            map.iter()
+                .filter_map(|(k, v,)| toml_value(v,).map(|v| (k.clone(), v,),),)
+                .collect(),
+        ),),
+    }
+}
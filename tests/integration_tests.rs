@@ -1,13 +1,42 @@
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::{Criteria, FlightDescriptor, Ticket};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::StreamExt;
+use nc_reader::columnar::{ColumnarCompression, ColumnarOptions};
+use nc_reader::config::{ConfigSources, Settings};
+use nc_reader::convert::SchemaOverride;
+use nc_reader::envelope::{ENVELOPE_VERSION, ResultEnvelope, ResultFormat};
+use nc_reader::error::DataReaderError;
 use nc_reader::file_reader::{
-    FileFormat, FileReaderOptions, get_file_format, read_directory_content, read_file_to_data,
-    read_file_to_raw_content,
+    FileFormat, FileReaderOptions, expand_inputs, get_file_format, read_directory_content,
+    read_file_to_batches, read_file_to_batches_with_config, read_file_to_data,
+    read_file_to_data_with_config, read_file_to_raw_content, read_file_to_raw_content_with_config,
+    read_file_to_stream_with_config, read_file_to_typed_stream_with_config, read_inputs_content,
+    write_file_to_columnar, write_reader_to_columnar,
 };
-use nc_reader::nc_reader_result::DataReaderResult;
-use nc_reader::output::{OutputFormat, OutputMode};
+use nc_reader::flight::FileFlightService;
+use nc_reader::nc_reader_result::{CustomData, DataReaderResult, FileMetadata, RecordStream};
+use nc_reader::output::OutputFormat;
+use nc_reader::reader::coercion::{ValueCoercion, ValueType};
+use nc_reader::reader::csv_dialect::CsvOptions;
+use nc_reader::reader::csv_reader::CsvData;
+use nc_reader::reader::error_policy::{ErrorPolicy, KEPT_REJECTS};
+use nc_reader::reader::filter::{Predicate, Projection, RecordFilter};
+use nc_reader::reader::limits::{Limit, ReadLimits};
+use nc_reader::reader::read_config::ReadConfig;
+use nc_reader::reader::registry::{FormatReader, ReadSource, register_format_reader};
+use nc_reader::reader::sample::SampleOptions;
+use nc_reader::reader::type_inference::{ColumnHint, InferenceOptions, NumberLocale};
+use nc_reader::reader::xml_reader::XmlSchemaType;
+use nc_reader::record::NcValue;
+use nc_reader::sink::{OutputSink, SinkCompression, SinkOptions};
+use nc_reader::writer::{HEADER_SAMPLE_RECORDS, format_value, write_record_stream};
 use nc_schema::DataType;
 use tempfile::tempdir;
 
@@ -191,15 +220,7 @@ async fn test_read_directory_basic() {
     create_temp_file(dir.path(), "file1.txt", "content1",);
     create_temp_file(dir.path(), "file2.csv", "a,b\n1,2",);
 
-    let options = FileReaderOptions {
-        head:               None,
-        file_type_override: None,
-        output_mode:        OutputMode::Default,
-        output_format:      OutputFormat::Text,
-        recursive:          false,
-        filter_exts:        None,
-        output_path:        None,
-    };
+    let options = FileReaderOptions::default();
 
     let result = read_directory_content(dir.path(), options,).await;
     assert!(result.is_ok());
@@ -229,13 +250,8 @@ async fn test_read_directory_filter() {
     );
 
     let options = FileReaderOptions {
-        head:               None,
-        file_type_override: None,
-        output_mode:        OutputMode::Default,
-        output_format:      OutputFormat::Text,
-        recursive:          false,
-        filter_exts:        Some(vec!["csv".to_string()],),
-        output_path:        None,
+        filter_exts: Some(vec!["csv".to_string()],),
+        ..Default::default()
     };
 
     let result = read_directory_content(dir.path(), options,).await;
@@ -266,13 +282,9 @@ async fn test_read_directory_recursive() {
     );
 
     let options = FileReaderOptions {
-        head:               None,
-        file_type_override: None,
-        output_mode:        OutputMode::Default,
-        output_format:      OutputFormat::Text,
-        recursive:          true,
-        filter_exts:        Some(vec!["csv".to_string()],),
-        output_path:        None,
+        recursive: true,
+        filter_exts: Some(vec!["csv".to_string()],),
+        ..Default::default()
     };
 
     let result = read_directory_content(dir.path(), options,).await;
@@ -287,6 +299,37 @@ async fn test_read_directory_recursive() {
     }
 }
 
+#[tokio::test]
+async fn test_read_directory_parallel_order_and_errors() {
+    let dir = tempdir().unwrap();
+    for i in (0..8).rev() {
+        create_temp_file(dir.path(), &format!("file{}.csv", i), "a,b\n1,2\n",);
+    }
+    create_temp_file(dir.path(), "file3_broken.json", "{ not json",);
+
+    let options = FileReaderOptions {
+        jobs: 4,
+        ..Default::default()
+    };
+
+    let result = read_directory_content(dir.path(), options,).await.unwrap();
+    let DataReaderResult::DirectoryResults(results, _metadata,) = result else {
+        panic!("Expected DirectoryResults, got {:?}", result);
+    };
+
+    let names: Vec<String,> = results
+        .iter()
+        .map(|(p, _,)| p.file_name().unwrap().to_string_lossy().into_owned(),)
+        .collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+    assert_eq!(names.len(), 9);
+
+    let (_, broken,) = &results[4];
+    assert!(matches!(broken, DataReaderResult::Error(e, _) if !e.error.is_empty()));
+}
+
 #[tokio::test]
 async fn test_output_to_file_single_file() -> Result<(), Box<dyn std::error::Error,>,> {
     let temp_dir = tempdir()?;
@@ -328,23 +371,23 @@ async fn test_output_to_file_directory_results() -> Result<(), Box<dyn std::erro
     let output_content = fs::read_to_string(&output_file_path,)?;
     let json_output: serde_json::Value = serde_json::from_str(&output_content,)?;
 
-    assert!(json_output.is_array());
-    assert_eq!(json_output.as_array().unwrap().len(), 2);
+    assert_eq!(json_output["version"], 1);
+    assert_eq!(json_output["format"], "directory");
+    let entries = json_output["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
 
-    let file1_result = json_output
-        .as_array()
-        .unwrap()
+    let file1_result = entries
         .iter()
         .find(|item| item["path"].as_str().unwrap().contains("file1.txt",),)
         .unwrap();
-    assert_eq!(file1_result["result"][0]["content"], "content1");
+    assert_eq!(file1_result["format"], "text");
+    assert_eq!(file1_result["data"]["content"], "content1");
 
-    let _file2_result = json_output
-        .as_array()
-        .unwrap()
+    let file2_result = entries
         .iter()
         .find(|item| item["path"].as_str().unwrap().contains("file2.json",),)
         .unwrap();
+    assert_eq!(file2_result["format"], "json");
     Ok((),)
 }
 
@@ -431,3 +474,1637 @@ fn test_xml_mixed_schema_inference() {
         panic!("Expected Xml DataReaderResult");
     }
 }
+
+struct KeyValueFormat;
+
+impl FormatReader for KeyValueFormat {
+    fn name(&self,) -> &str {
+        "kv"
+    }
+
+    fn extensions(&self,) -> &[&str] {
+        &["kv"]
+    }
+
+    fn read_data(
+        &self,
+        source: ReadSource,
+        _head: Option<usize,>,
+        _config: &ReadConfig,
+    ) -> Result<DataReaderResult, DataReaderError,> {
+        let mut content = String::new();
+        source.into_reader()?.read_to_string(&mut content,).map_err(|e| {
+            DataReaderError::InternalError(format!("Failed to read kv input: {}", e),)
+        },)?;
+        let mut map = serde_json::Map::new();
+        for line in content.lines() {
+            if let Some((key, value,),) = line.split_once('=',) {
+                map.insert(key.trim().to_string(), value.trim().into(),);
+            }
+        }
+        Ok(DataReaderResult::Custom(
+            CustomData {
+                format: "kv".to_string(),
+                value:  serde_json::Value::Object(map,),
+            },
+            FileMetadata {
+                size:       content.len() as u64,
+                line_count: Some(content.lines().count(),),
+            },
+        ),)
+    }
+}
+
+#[test]
+fn test_custom_format_reader_registration() {
+    register_format_reader(Arc::new(KeyValueFormat,),);
+
+    let dir = tempdir().unwrap();
+    let path = create_temp_file(dir.path(), "settings.kv", "name = nc\nlevel = 3",);
+    let format = get_file_format(&path,);
+    assert_eq!(format, FileFormat::Other("kv".to_string()));
+
+    let result = read_file_to_data(&path, None, format,).unwrap();
+    if let DataReaderResult::Custom(data, metadata,) = result {
+        assert_eq!(data.value["name"], "nc");
+        assert_eq!(data.value["level"], "3");
+        assert_eq!(metadata.line_count, Some(2));
+    } else {
+        panic!("Expected Custom result, got {:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn test_flight_service_schema_and_do_get() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("sales",),).unwrap();
+    create_temp_file(&dir.path().join("sales",), "q1.csv", "id,amount\n1,9.5\n2,3.25\n",);
+    create_temp_file(dir.path(), "notes.md", "# not tabular\n",);
+
+    let service = FileFlightService::new(dir.path(), 1024,).unwrap();
+
+    let flights: Vec<_,> = service
+        .list_flights(tonic::Request::new(Criteria::default(),),)
+        .await
+        .unwrap()
+        .into_inner()
+        .collect()
+        .await;
+    assert_eq!(flights.len(), 1);
+    let info = flights[0].as_ref().unwrap();
+    assert_eq!(info.flight_descriptor.as_ref().unwrap().path, vec!["sales", "q1.csv"]);
+
+    let descriptor = FlightDescriptor::new_path(vec!["sales".to_string(), "q1.csv".to_string()],);
+    let schema_result = service
+        .get_schema(tonic::Request::new(descriptor,),)
+        .await
+        .unwrap()
+        .into_inner();
+    let schema = arrow::datatypes::Schema::try_from(&schema_result,).unwrap();
+    assert_eq!(schema.field(1,).data_type(), &arrow::datatypes::DataType::Float64);
+
+    let flight_data: Vec<_,> = service
+        .do_get(tonic::Request::new(Ticket::new("sales/q1.csv",),),)
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|d| d.unwrap(),)
+        .collect()
+        .await;
+    // One schema message followed by the data batch
+    assert_eq!(flight_data.len(), 2);
+
+    let escape = service
+        .do_get(tonic::Request::new(Ticket::new("../outside.csv",),),)
+        .await;
+    assert!(escape.is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_flight_service_rejects_symlinks_out_of_the_root() {
+    let outside = tempdir().unwrap();
+    let secret = create_temp_file(outside.path(), "secret.csv", "id\n1\n",);
+    let root = tempdir().unwrap();
+    create_temp_file(root.path(), "inside.csv", "id\n2\n",);
+    std::os::unix::fs::symlink(&secret, root.path().join("link.csv",),).unwrap();
+    std::os::unix::fs::symlink(root.path().join("inside.csv",), root.path().join("alias.csv",),)
+        .unwrap();
+
+    let service = FileFlightService::new(root.path(), 1024,).unwrap();
+    let Err(status,) = service.do_get(tonic::Request::new(Ticket::new("link.csv",),),).await else {
+        panic!("expected the symlink out of the root to be rejected");
+    };
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let descriptor = FlightDescriptor::new_path(vec!["link.csv".to_string()],);
+    let info = service.get_flight_info(tonic::Request::new(descriptor,),).await;
+    assert!(info.is_err());
+
+    assert!(service.do_get(tonic::Request::new(Ticket::new("alias.csv",),),).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_flight_info_leaves_csv_schemas_to_get_schema() {
+    let dir = tempdir().unwrap();
+    create_temp_file(dir.path(), "q1.csv", "id,amount\n1,9.5\n",);
+
+    let service = FileFlightService::new(dir.path(), 1024,).unwrap();
+    let descriptor = FlightDescriptor::new_path(vec!["q1.csv".to_string()],);
+    let info = service
+        .get_flight_info(tonic::Request::new(descriptor,),)
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(info.schema.is_empty());
+    assert_eq!(info.total_bytes, 16);
+}
+
+#[test]
+fn test_csv_skip_policy_collects_rejects() {
+    let dir = tempdir().unwrap();
+    let path = create_temp_file(dir.path(), "rejects.csv", "id,name\n1,ada\n2,alan,x\n3,grace\n",);
+
+    let config = ReadConfig::default().with_error_policy(ErrorPolicy::Skip,);
+    let result = read_file_to_data_with_config(&path, None, FileFormat::Csv, &config,).unwrap();
+    let DataReaderResult::Csv(data, _,) = result else {
+        panic!("Expected Csv result");
+    };
+    assert_eq!(data.num_rows, 2);
+    let rejects = data.rejects.expect("expected a reject report",);
+    assert_eq!(rejects.skipped, 1);
+    assert_eq!(rejects.records[0].raw.as_deref(), Some("2,alan,x"));
+    assert_eq!(rejects.records[0].position.as_ref().and_then(|p| p.line), Some(3));
+    assert_eq!(config.rejects.report().skipped, 1);
+
+    let strict = read_file_to_data(&path, None, FileFormat::Csv,);
+    assert!(matches!(strict, Err(DataReaderError::ParseError { .. })));
+}
+
+#[test]
+fn test_rejects_stream_to_their_sink_and_keep_a_prefix() {
+    let dir = tempdir().unwrap();
+    let mut content = String::from("id,name\n",);
+    for i in 0..1500 {
+        content.push_str(&format!("{},bad,row\n", i),);
+    }
+    content.push_str("1500,good\n",);
+    let path = create_temp_file(dir.path(), "many_rejects.csv", &content,);
+
+    let config = ReadConfig::default().with_error_policy(ErrorPolicy::Skip,);
+    let rejects_path = dir.path().join("rejects.jsonl",);
+    config.rejects.stream_to(Box::new(File::create(&rejects_path,).unwrap(),),);
+    let result = read_file_to_data_with_config(&path, None, FileFormat::Csv, &config,).unwrap();
+    let DataReaderResult::Csv(data, _,) = result else {
+        panic!("Expected Csv result");
+    };
+    assert_eq!(data.num_rows, 1);
+    let rejects = data.rejects.expect("expected a reject report",);
+    assert_eq!(rejects.skipped, 1500);
+    assert_eq!(rejects.records.len(), KEPT_REJECTS);
+
+    config.rejects.flush().unwrap();
+    let written = fs::read_to_string(&rejects_path,).unwrap();
+    assert_eq!(written.lines().count(), 1500);
+    assert!(written.lines().last().unwrap().contains("1499,bad,row"));
+}
+
+#[test]
+fn test_skip_with_limit_fails_after_limit() {
+    let dir = tempdir().unwrap();
+    let path = create_temp_file(dir.path(), "broken.jsonl", "{\"a\": 1}\nnot json\n{oops\n",);
+
+    let config = ReadConfig::default().with_error_policy("skip-with-limit:1".parse().unwrap(),);
+    let result = read_file_to_data_with_config(&path, None, FileFormat::Json, &config,);
+    assert!(matches!(result, Err(DataReaderError::RejectLimitExceeded { limit: 1, .. })));
+    assert_eq!(config.rejects.report().skipped, 1);
+    assert_eq!(
+        "skip-with-limit:1".parse::<ErrorPolicy>(),
+        Ok(ErrorPolicy::SkipWithLimit(1))
+    );
+}
+
+#[test]
+fn test_read_limits_fail_with_limit_exceeded() {
+    let dir = tempdir().unwrap();
+    let csv_path = create_temp_file(dir.path(), "rows.csv", "id\n1\n2\n3\n",);
+    let json_path = create_temp_file(dir.path(), "deep.json", "{\"a\": {\"b\": [1]}}",);
+
+    let with_limits = |limits: ReadLimits| ReadConfig::default().with_limits(limits,);
+    let records = with_limits(ReadLimits {
+        max_records: Some(2,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&csv_path, None, FileFormat::Csv, &records,);
+    assert!(matches!(
+        result,
+        Err(DataReaderError::LimitExceeded { limit: Limit::Records(2), .. })
+    ));
+
+    let bytes = with_limits(ReadLimits {
+        max_bytes: Some(4,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&csv_path, None, FileFormat::Csv, &bytes,);
+    assert!(matches!(result, Err(DataReaderError::LimitExceeded { limit: Limit::Bytes(4), .. })));
+
+    let depth = with_limits(ReadLimits {
+        max_depth: Some(3,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&json_path, None, FileFormat::Json, &depth,);
+    assert!(matches!(result, Err(DataReaderError::LimitExceeded { limit: Limit::Depth(3), .. })));
+
+    let within = with_limits(ReadLimits {
+        max_records: Some(3,),
+        max_depth: Some(4,),
+        ..Default::default()
+    },);
+    assert!(read_file_to_data_with_config(&csv_path, None, FileFormat::Csv, &within,).is_ok());
+    assert!(read_file_to_data_with_config(&json_path, None, FileFormat::Json, &within,).is_ok());
+}
+
+#[tokio::test]
+async fn test_read_limits_apply_to_every_entry_point() {
+    let dir = tempdir().unwrap();
+    let csv_path = create_temp_file(dir.path(), "rows.csv", "id\n1\n2\n3\n",);
+    let xml_path = create_temp_file(dir.path(), "rows.xml", "<r><i>1</i><i>2</i></r>",);
+    let yaml_path = create_temp_file(dir.path(), "deep.yaml", "a:\n  b:\n    - 1\n",);
+    let toml_path = create_temp_file(dir.path(), "app.toml", "name = \"x\"\n",);
+    let records = ReadConfig::default().with_limits(ReadLimits {
+        max_records: Some(2,),
+        ..Default::default()
+    },);
+    let is_records_limit = |e: &DataReaderError| {
+        matches!(e, DataReaderError::LimitExceeded { limit: Limit::Records(2), .. })
+    };
+
+    let typed: Vec<_,> =
+        read_file_to_typed_stream_with_config(&csv_path, FileFormat::Csv, &records,)
+            .unwrap()
+            .collect();
+    assert_eq!(typed.len(), 3);
+    assert!(is_records_limit(typed[2].as_ref().unwrap_err(),));
+
+    let (_schema, batches,) =
+        read_file_to_batches_with_config(&csv_path, FileFormat::Csv, 1, &records,).unwrap();
+    let batches: Vec<_,> = batches.collect();
+    assert!(is_records_limit(batches.last().unwrap().as_ref().unwrap_err(),));
+
+    let one = ReadConfig::default().with_limits(ReadLimits {
+        max_records: Some(1,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&xml_path, None, FileFormat::Xml, &one,);
+    assert!(matches!(result, Err(DataReaderError::LimitExceeded { limit: Limit::Records(1), .. })));
+
+    let depth = ReadConfig::default().with_limits(ReadLimits {
+        max_depth: Some(3,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&yaml_path, None, FileFormat::Yaml, &depth,);
+    assert!(matches!(result, Err(DataReaderError::LimitExceeded { limit: Limit::Depth(3), .. })));
+
+    let cancelled = ReadConfig::default();
+    cancelled.cancel.cancel();
+    let result = read_file_to_data_with_config(&toml_path, None, FileFormat::Toml, &cancelled,);
+    assert!(matches!(result, Err(DataReaderError::Cancelled { .. })));
+
+    let service = FileFlightService::new(dir.path(), 1024,).unwrap().with_config(cancelled,);
+    let descriptor = FlightDescriptor::new_path(vec!["rows.csv".to_string()],);
+    let status = service.get_schema(tonic::Request::new(descriptor,),).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Cancelled);
+}
+
+#[test]
+fn test_gzip_decompressed_limit_and_cancellation() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("zeros.gz",);
+    let mut encoder = GzEncoder::new(File::create(&path,).unwrap(), Compression::default(),);
+    encoder.write_all(&[0u8; 64 * 1024],).unwrap();
+    encoder.finish().unwrap();
+
+    let config = ReadConfig::default().with_limits(ReadLimits {
+        max_decompressed_bytes: Some(1024,),
+        ..Default::default()
+    },);
+    let result = read_file_to_data_with_config(&path, None, FileFormat::Gzip, &config,);
+    assert!(matches!(
+        result,
+        Err(DataReaderError::LimitExceeded { limit: Limit::DecompressedBytes(1024), .. })
+    ));
+
+    let config = ReadConfig::default();
+    config.cancel.cancel();
+    let result = read_file_to_data_with_config(&path, None, FileFormat::Gzip, &config,);
+    assert!(matches!(result, Err(DataReaderError::Cancelled { .. })));
+}
+
+#[test]
+fn test_cli_stream_writes_ndjson() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "events.jsonl", "{\"a\": 1}\n{\"a\": 2}\n",);
+    let output_path = temp_dir.path().join("events.ndjson",);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .args(["--stream", "--format", "json", "--output-path",],)
+        .arg(&output_path,)
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&output_path,)?;
+    let records: Vec<serde_json::Value,> =
+        output.lines().map(serde_json::from_str,).collect::<Result<_, _,>>()?;
+    assert_eq!(records, vec![serde_json::json!({"a": 1}), serde_json::json!({"a": 2})]);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .args(["--stream", "--all",],)
+        .assert()
+        .failure();
+    Ok((),)
+}
+
+#[test]
+fn test_cli_convert_with_schema() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "scores.csv", "id,score\n1,9.5\nx,7\n",);
+    let schema = "{\"id\": \"integer\", \"score\": \"string\"}";
+    let schema_path = create_temp_file(temp_dir.path(), "types.json", schema,);
+    let output_path = temp_dir.path().join("scores.ndjson",);
+
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&input_path,)
+        .arg(&output_path,)
+        .arg("--schema",)
+        .arg(&schema_path,)
+        .args(["--error-policy", "skip",],)
+        .assert()
+        .success();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone(),)?;
+    assert!(stderr.contains("Converted 1 rows (1 rejected)",), "{}", stderr);
+
+    let output = fs::read_to_string(&output_path,)?;
+    let records: Vec<serde_json::Value,> =
+        output.lines().map(serde_json::from_str,).collect::<Result<_, _,>>()?;
+    assert_eq!(records, vec![serde_json::json!({"id": 1, "score": "9.5"})]);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&input_path,)
+        .arg(temp_dir.path().join("scores.unknown",),)
+        .assert()
+        .failure();
+    Ok((),)
+}
+
+#[test]
+fn test_cli_convert_compresses_the_output() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "scores.csv", "id,score\n1,9.5\n",);
+    let zstd_path = temp_dir.path().join("scores.ndjson.zst",);
+    let gzip_path = temp_dir.path().join("scores.ndjson",);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&input_path,)
+        .arg(&zstd_path,)
+        .assert()
+        .success();
+    let output = String::from_utf8(zstd::decode_all(File::open(&zstd_path,)?,)?,)?;
+    assert_eq!(output.trim(), "{\"id\":1,\"score\":9.5}");
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&input_path,)
+        .arg(&gzip_path,)
+        .args(["--compression", "gzip",],)
+        .assert()
+        .success();
+    let mut output = String::new();
+    flate2::read::GzDecoder::new(File::open(&gzip_path,)?,).read_to_string(&mut output,)?;
+    assert_eq!(output.trim(), "{\"id\":1,\"score\":9.5}");
+    Ok((),)
+}
+
+#[test]
+fn test_cli_convert_streams_encoded_stdin() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let text = "name,city\nJosé,Zürich\n";
+    let utf16: Vec<u8,> = text.encode_utf16().flat_map(u16::to_le_bytes,).collect();
+
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .args(["convert", "-", "-", "--file-type", "csv", "--format", "ndjson",],)
+        .args(["--encoding", "utf-16le",],)
+        .write_stdin(utf16,)
+        .assert()
+        .success();
+    let output = String::from_utf8(assert.get_output().stdout.clone(),)?;
+    assert_eq!(output.trim(), "{\"name\":\"José\",\"city\":\"Zürich\"}");
+
+    // Binary input is refused rather than transcoded.
+    let csv_path = create_temp_file(temp_dir.path(), "cities.csv", text,);
+    let parquet_path = temp_dir.path().join("cities.parquet",);
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&csv_path,)
+        .arg(&parquet_path,)
+        .assert()
+        .success();
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("convert",)
+        .arg(&parquet_path,)
+        .arg(temp_dir.path().join("cities.ndjson",),)
+        .args(["--encoding", "latin1",],)
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone(),)?;
+    assert!(stderr.contains("binary"), "{}", stderr);
+    Ok((),)
+}
+
+#[test]
+fn test_schema_override_reads_values_like_declared_types() {
+    let schema = SchemaOverride::new()
+        .with_column("id", ValueType::Integer,)
+        .with_column("active", ValueType::Boolean,);
+    let records = vec![
+        serde_json::json!({"id": 3.0, "active": "Y"}),
+        serde_json::json!({"id": u64::MAX, "active": 1}),
+        serde_json::json!({"id": 1e19}),
+        serde_json::json!({"id": -1e19}),
+        serde_json::json!({"active": "true"}),
+    ];
+    let coercion = ValueCoercion::default()
+        .with_booleans(vec!["Y".into(), "1".into()], vec!["N".into()],)
+        .unwrap();
+    let stream: RecordStream = Box::new(records.into_iter().map(Ok,),);
+    let results: Vec<_,> = schema.apply(stream, Path::new("ids.json",), &coercion,).collect();
+    assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!({"id": 3, "active": true}));
+    assert_eq!(
+        results[1].as_ref().unwrap(),
+        &serde_json::json!({"id": u64::MAX, "active": true})
+    );
+    // Floats that are not whole i64 values and words outside the vocabulary are rejected.
+    assert!(results[2..].iter().all(Result::is_err,));
+}
+
+#[test]
+fn test_output_formats_render_values() {
+    let rows = serde_json::json!([
+        {"id": 1, "owner": {"name": "ada"}, "tags": ["a", "b"]},
+        {"id": 2, "note": "x,y"},
+    ]);
+
+    let csv = format_value(&rows, OutputFormat::Csv,).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("id,owner.name,tags.0,tags.1,note"));
+    assert_eq!(lines.next(), Some("1,ada,a,b,"));
+    assert_eq!(lines.next(), Some("2,,,,\"x,y\""));
+
+    let tsv = format_value(&rows, OutputFormat::Tsv,).unwrap();
+    assert!(tsv.starts_with("id\towner.name\t",));
+
+    let ndjson = format_value(&rows, OutputFormat::Ndjson,).unwrap();
+    assert_eq!(ndjson.lines().count(), 2);
+
+    let xml = format_value(&serde_json::json!({"a": [1, 2], "b c": "<x>"}), OutputFormat::Xml,)
+        .unwrap();
+    assert!(xml.contains("<item>1</item>"));
+    assert!(xml.contains("<entry key=\"b c\">&lt;x&gt;</entry>"));
+
+    let toml = format_value(&serde_json::json!({"a": 1, "b": null}), OutputFormat::Toml,).unwrap();
+    assert_eq!(toml.trim(), "a = 1");
+
+    for (file, format,) in [
+        ("out.csv", OutputFormat::Csv,),
+        ("out.tsv", OutputFormat::Tsv,),
+        ("out.ndjson", OutputFormat::Ndjson,),
+        ("out.xml", OutputFormat::Xml,),
+        ("out.toml", OutputFormat::Toml,),
+    ] {
+        assert_eq!(OutputFormat::from_extension(Path::new(file)), Some(format));
+    }
+}
+
+#[test]
+fn test_streamed_csv_header_covers_sampled_records() {
+    let records = vec![
+        serde_json::json!({"id": 1}),
+        serde_json::json!({"id": 2, "name": "b"}),
+    ];
+    let stream: RecordStream = Box::new(records.into_iter().map(Ok,),);
+    let mut output = Vec::new();
+    assert_eq!(write_record_stream(stream, OutputFormat::Csv, &mut output,).unwrap(), 2);
+    assert_eq!(String::from_utf8(output,).unwrap(), "id,name\n1,\n2,b\n");
+
+    // A column first seen after the sample cannot join the header and fails the write.
+    let records = (0..HEADER_SAMPLE_RECORDS)
+        .map(|id| serde_json::json!({"id": id}),)
+        .chain([serde_json::json!({"id": 0, "late": true})],);
+    let stream: RecordStream = Box::new(records.map(Ok,),);
+    let error = write_record_stream(stream, OutputFormat::Tsv, &mut Vec::new(),).unwrap_err();
+    assert!(error.to_string().contains("late"), "{}", error);
+}
+
+#[test]
+fn test_write_file_to_columnar_round_trips() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let input_path = dir.path().join("events.jsonl",);
+    fs::write(&input_path, "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"score\": 1.5}\n",)?;
+
+    let options = ColumnarOptions {
+        compression: Some(ColumnarCompression::Zstd,),
+        row_group_size: Some(1,),
+        ..Default::default()
+    };
+    let parquet_path = dir.path().join("events.parquet",);
+    let rows = write_file_to_columnar(
+        &input_path,
+        &FileReaderOptions::default(),
+        OutputFormat::Parquet,
+        &options,
+        File::create(&parquet_path,)?,
+    )?;
+    assert_eq!(rows, 2);
+
+    let (schema, batches,) = read_file_to_batches(&parquet_path, FileFormat::Parquet, 1024,)?;
+    let names: Vec<&str,> = schema.fields().iter().map(|f| f.name().as_str(),).collect();
+    assert_eq!(names.len(), 3);
+    assert!(names.contains(&"id",) && names.contains(&"name",) && names.contains(&"score",));
+    let read: usize = batches.map(|b| b.map(|b| b.num_rows(),),).sum::<Result<_, _,>>()?;
+    assert_eq!(read, 2);
+
+    let ipc_path = dir.path().join("events.arrow",);
+    let ipc = write_file_to_columnar(
+        &input_path,
+        &FileReaderOptions::default(),
+        OutputFormat::ArrowIpc,
+        &ColumnarOptions::default(),
+        File::create(&ipc_path,)?,
+    )?;
+    assert_eq!(ipc, 2);
+    assert_eq!(&fs::read(&ipc_path,)?[..6], b"ARROW1");
+
+    let rejected = write_file_to_columnar(
+        &input_path,
+        &FileReaderOptions::default(),
+        OutputFormat::ArrowIpc,
+        &options,
+        Vec::new(),
+    );
+    assert!(rejected.is_err());
+    Ok((),)
+}
+
+#[test]
+fn test_write_reader_to_columnar_reads_once() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let source_path = dir.path().join("stdin.jsonl",);
+    let input = "{\"id\": 1}\n{\"id\": \n{\"id\": 3, \"name\": \"c\"}\n";
+    let options = FileReaderOptions {
+        read_config: ReadConfig::default().with_error_policy(ErrorPolicy::Skip,),
+        ..Default::default()
+    };
+
+    let parquet_path = dir.path().join("stdin.parquet",);
+    let rows = write_reader_to_columnar(
+        Box::new(std::io::Cursor::new(input,),),
+        &source_path,
+        &options,
+        OutputFormat::Parquet,
+        &ColumnarOptions::default(),
+        File::create(&parquet_path,)?,
+    )?;
+    assert_eq!(rows, 2);
+    assert_eq!(options.read_config.rejects.report_for(&source_path,).skipped, 1);
+
+    let (schema, batches,) = read_file_to_batches(&parquet_path, FileFormat::Parquet, 1024,)?;
+    assert_eq!(schema.fields().len(), 2);
+    let read: usize = batches.map(|b| b.map(|b| b.num_rows(),),).sum::<Result<_, _,>>()?;
+    assert_eq!(read, 2);
+    Ok((),)
+}
+
+#[test]
+fn test_columnar_output_keeps_typed_columns() -> Result<(), Box<dyn std::error::Error,>,> {
+    use arrow::array::{ArrayRef, AsArray, BinaryArray, Date32Array, TimestampMicrosecondArray};
+    use arrow::datatypes::{DataType as ArrowType, TimeUnit};
+
+    let dir = tempdir()?;
+    let input_path = dir.path().join("events.parquet",);
+    let batch = arrow::record_batch::RecordBatch::try_from_iter([
+        ("day", Arc::new(Date32Array::from(vec![19_800],),) as ArrayRef,),
+        (
+            "at",
+            Arc::new(
+                TimestampMicrosecondArray::from(vec![1_700_000_000_000_000],).with_timezone("UTC",),
+            ),
+        ),
+        ("payload", Arc::new(BinaryArray::from(vec![&[1u8, 255][..]],),),),
+    ],)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(
+        File::create(&input_path,)?,
+        batch.schema(),
+        None,
+    )?;
+    writer.write(&batch,)?;
+    writer.close()?;
+
+    let record = read_file_to_typed_stream_with_config(
+        &input_path,
+        FileFormat::Parquet,
+        &ReadConfig::default(),
+    )?
+    .next()
+    .unwrap()?;
+    let DataType::Object(types,) = record.data_type() else {
+        panic!("expected an object, got {:?}", record);
+    };
+    assert_eq!(types["day"], DataType::Date);
+    assert_eq!(types["at"], DataType::Timestamp);
+    assert_eq!(types["payload"], DataType::Binary);
+
+    let output_path = dir.path().join("copy.parquet",);
+    write_file_to_columnar(
+        &input_path,
+        &FileReaderOptions::default(),
+        OutputFormat::Parquet,
+        &ColumnarOptions::default(),
+        File::create(&output_path,)?,
+    )?;
+    let (schema, mut batches,) = read_file_to_batches(&output_path, FileFormat::Parquet, 1024,)?;
+    assert_eq!(schema.field_with_name("day",)?.data_type(), &ArrowType::Date32);
+    assert_eq!(
+        schema.field_with_name("at",)?.data_type(),
+        &ArrowType::Timestamp(TimeUnit::Microsecond, Some("UTC".into(),),)
+    );
+    assert_eq!(schema.field_with_name("payload",)?.data_type(), &ArrowType::Binary);
+    let batch = batches.next().unwrap()?;
+    let payload = batch.column_by_name("payload",).unwrap().as_binary::<i32>();
+    assert_eq!(payload.value(0,), &[1u8, 255]);
+    let day = batch.column_by_name("day",).unwrap().as_primitive::<arrow::datatypes::Date32Type>();
+    assert_eq!(day.value(0,), 19_800);
+    Ok((),)
+}
+
+#[test]
+fn test_record_filter_where_and_select() -> Result<(), Box<dyn std::error::Error,>,> {
+    let predicate: Predicate =
+        "age >= 18 and (name =~ \"^A\" or tags[0] == 'admin') and nick is null".parse()?;
+    assert!(predicate.matches(&serde_json::json!({"age": 30, "name": "Ada"})));
+    assert!(predicate.matches(&serde_json::json!({"age": 18.0, "name": "Bo", "tags": ["admin"]})));
+    assert!(!predicate.matches(&serde_json::json!({"age": 17, "name": "Ada"})));
+    assert!(!predicate.matches(&serde_json::json!({"age": 30, "name": "Ada", "nick": "a"})));
+    assert!(!predicate.matches(&serde_json::json!({"age": "thirty", "name": "Ada"})));
+    assert!("age >=".parse::<Predicate>().is_err());
+
+    // Integers compare exactly, and decimal strings as numbers.
+    let big: Predicate = "id > 9007199254740992".parse()?;
+    assert!(big.matches(&serde_json::json!({"id": 9007199254740993u64})));
+    assert!(!big.matches(&serde_json::json!({"id": 9007199254740992u64})));
+    let amount: Predicate = "amount >= 0.3 and amount < 100".parse()?;
+    assert!(amount.matches(&serde_json::json!({"amount": "0.30"})));
+    assert!(amount.matches(&serde_json::json!({"amount": "99.999999999999999999"})));
+    assert!(!amount.matches(&serde_json::json!({"amount": "0.29999999999999999999"})));
+    assert!(!amount.matches(&serde_json::json!({"amount": "n/a"})));
+    assert!("name =~ \"(\"".parse::<Predicate>().is_err());
+
+    let projection: Projection = "id, user.name as name, `first tag`".parse()?;
+    assert_eq!(
+        projection.project(&serde_json::json!({"id": 1, "user": {"name": "Ada"}, "x": 2})),
+        serde_json::json!({"id": 1, "name": "Ada", "first tag": null})
+    );
+
+    let temp_dir = tempdir()?;
+    let input_path = temp_dir.path().join("people.jsonl",);
+    fs::write(
+        &input_path,
+        "{\"id\": 1, \"age\": 30, \"name\": \"Ada\"}\n{\"id\": 2, \"age\": 12, \"name\": \"Bo\"}\n",
+    )?;
+    let parquet_path = temp_dir.path().join("people.parquet",);
+    write_file_to_columnar(
+        &input_path,
+        &FileReaderOptions::default(),
+        OutputFormat::Parquet,
+        &ColumnarOptions::default(),
+        File::create(&parquet_path,)?,
+    )?;
+
+    let filter = RecordFilter {
+        predicate:  Some("age > 18".parse()?,),
+        projection: Some("id".parse()?,),
+    };
+    assert_eq!(filter.required_columns(), Some(vec!["id".to_string(), "age".to_string()]));
+    let config = ReadConfig::default().with_filter(filter,);
+    let summary = read_file_to_data_with_config(&parquet_path, None, FileFormat::Parquet, &config,);
+    assert!(matches!(summary, Err(DataReaderError::InvalidConfig(_))));
+    for path in [&input_path, &parquet_path,] {
+        let DataReaderResult::Stream(stream, _,) =
+            read_file_to_stream_with_config(path, get_file_format(path,), &config,)?
+        else {
+            panic!("expected a record stream for {}", path.display());
+        };
+        let records: Vec<serde_json::Value,> = stream.collect::<Result<_, _,>>()?;
+        assert_eq!(records, vec![serde_json::json!({"id": 1})]);
+    }
+    Ok((),)
+}
+
+#[test]
+fn test_config_layers_and_profiles() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let user_file = create_temp_file(temp_dir.path(), "cli.yml", "format: yaml\njobs: 2\n",);
+    let project_file = create_temp_file(
+        temp_dir.path(),
+        "nc_reader.yml",
+        "jobs: 8\nprofiles:\n  audit:\n    error-policy: skip\n    max-depth: 16\n",
+    );
+    let sources = ConfigSources {
+        files: vec![user_file, project_file, temp_dir.path().join("missing.yml",)],
+        env:   vec![("NC_READER_MAX_RECORDS".to_string(), "100".to_string(),)],
+    };
+
+    let loaded = sources.load(Some("audit",),)?;
+    assert_eq!(loaded.sources.len(), 3);
+    assert_eq!(loaded.settings.format.as_deref(), Some("yaml"));
+    assert_eq!(loaded.settings.jobs, Some(8));
+    assert_eq!(loaded.settings.max_depth, Some(16));
+    assert_eq!(loaded.settings.max_records, Some(100));
+
+    let flags = Settings {
+        jobs: Some(1,),
+        ..Settings::default()
+    };
+    let options = loaded.settings.merge(flags,).reader_options()?;
+    assert_eq!(options.jobs, 1);
+    assert_eq!(options.output_format, OutputFormat::Yaml);
+    assert_eq!(options.read_config.limits.max_depth, Some(16));
+
+    assert!(matches!(sources.load(Some("missing",),), Err(DataReaderError::InvalidConfig(_,),)));
+    Ok((),)
+}
+
+#[test]
+fn test_config_from_environment_variables() -> Result<(), Box<dyn std::error::Error,>,> {
+    let env = |pairs: &[(&str, &str,)]| {
+        pairs.iter().map(|(name, value,)| (name.to_string(), value.to_string(),),).collect()
+    };
+    let sources = ConfigSources {
+        files: Vec::new(),
+        env:   env(&[
+            ("NC_READER_NULL_VALUES", "NA, -",),
+            ("NC_READER_TYPES", "zip:string,id:integer",),
+            ("NC_READER_FILE_TYPE", "123",),
+            ("NC_READER_RECURSIVE", "false",),
+            ("NC_READER_MAX_DEPTH", "64",),
+            ("NC_READER_NO_SUCH_SETTING", "1",),
+        ],),
+    };
+    let loaded = sources.load(None,)?;
+    let settings = &loaded.settings;
+    assert_eq!(settings.null_values, Some(vec!["NA".to_string(), "-".to_string()]));
+    let types = settings.types.as_ref().unwrap();
+    assert_eq!(types.get("zip",).map(String::as_str,), Some("string"));
+    assert_eq!(types.get("id",).map(String::as_str,), Some("integer"));
+    assert_eq!(settings.file_type.as_deref(), Some("123"));
+    assert_eq!(settings.recursive, Some(false));
+    assert_eq!(settings.max_depth, Some(64));
+    assert_eq!(loaded.warnings.len(), 1);
+    assert!(loaded.warnings[0].contains("NC_READER_NO_SUCH_SETTING",));
+
+    let invalid = ConfigSources {
+        files: Vec::new(),
+        env:   env(&[("NC_READER_MAX_DEPTH", "deep",)],),
+    };
+    assert!(matches!(invalid.load(None,), Err(DataReaderError::InvalidConfig(_,),)));
+    Ok((),)
+}
+
+#[test]
+fn test_cli_flags_turn_off_configured_settings() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    create_temp_file(temp_dir.path(), "nc_reader.yml", "no-header: true
+",);
+    create_temp_file(temp_dir.path(), "people.csv", "id,name\n1,ada\n",);
+
+    let read = |flags: &[&str]| -> Result<String, Box<dyn std::error::Error,>,> {
+        let output = assert_cmd::Command::cargo_bin("nc-reader",)?
+            .current_dir(temp_dir.path(),)
+            .env("HOME", temp_dir.path(),)
+            .env_remove("NC_READER_CONFIG",)
+            .env_remove("XDG_CONFIG_HOME",)
+            .args(["--file-path", "people.csv", "--stream",],)
+            .args(flags,)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        Ok(String::from_utf8(output,)?,)
+    };
+    assert!(read(&[],)?.contains("column_1",));
+    let with_header = read(&["--header",],)?;
+    assert!(!with_header.contains("column_1",), "{}", with_header);
+    assert!(with_header.contains("\"name\":\"ada\"",), "{}", with_header);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_config_show() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    create_temp_file(
+        temp_dir.path(),
+        "nc_reader.yml",
+        "format: json\nprofiles:\n  untrusted:\n    timeout: 30\n",
+    );
+
+    let output = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .current_dir(temp_dir.path(),)
+        .env("HOME", temp_dir.path(),)
+        .env_remove("NC_READER_CONFIG",)
+        .env_remove("XDG_CONFIG_HOME",)
+        .args(["config", "show", "--profile", "untrusted",],)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let shown = String::from_utf8(output,)?;
+    assert!(shown.contains("nc_reader.yml"));
+    assert!(shown.contains("format: json"));
+    assert!(shown.contains("timeout: 30"));
+    Ok((),)
+}
+
+#[test]
+fn test_output_sink_compression_and_atomic_writes() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let gzip_path = temp_dir.path().join("out.json.gz",);
+    let mut sink = OutputSink::open(Some(&gzip_path,), SinkOptions::default(),)?;
+    sink.write_all(b"{\"a\": 1}\n",)?;
+    sink.finish()?;
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(File::open(&gzip_path,)?,).read_to_string(&mut text,)?;
+    assert_eq!(text, "{\"a\": 1}\n");
+
+    let zstd_path = temp_dir.path().join("out.ndjson",);
+    let options = SinkOptions {
+        compression: Some(SinkCompression::Zstd,),
+        atomic:      true,
+    };
+    fs::write(&zstd_path, "previous",)?;
+    let mut sink = OutputSink::open(Some(&zstd_path,), options,)?;
+    sink.write_all(b"partial",)?;
+    drop(sink,);
+    assert_eq!(fs::read_to_string(&zstd_path,)?, "previous");
+
+    let mut sink = OutputSink::open(Some(&zstd_path,), options,)?;
+    sink.write_all(b"complete",)?;
+    sink.finish()?;
+    assert_eq!(zstd::decode_all(File::open(&zstd_path,)?,)?, b"complete");
+    assert_eq!(fs::read_dir(temp_dir.path(),)?.count(), 2);
+
+    assert_eq!(
+        SinkCompression::strip_extension(Path::new("out.json.zst",),),
+        PathBuf::from("out.json")
+    );
+    Ok((),)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_atomic_output_keeps_file_permissions() -> Result<(), Box<dyn std::error::Error,>,> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempdir()?;
+    let mode = |path: &Path| fs::metadata(path,).map(|m| m.permissions().mode() & 0o777,);
+    let options = SinkOptions {
+        compression: None,
+        atomic:      true,
+    };
+
+    let existing = temp_dir.path().join("existing.json",);
+    fs::write(&existing, "previous",)?;
+    fs::set_permissions(&existing, fs::Permissions::from_mode(0o640,),)?;
+    let mut sink = OutputSink::open(Some(&existing,), options,)?;
+    sink.write_all(b"{}",)?;
+    sink.finish()?;
+    assert_eq!(mode(&existing,)?, 0o640);
+
+    // A new target gets what a plain `File::create` would get under the current umask.
+    let plain = temp_dir.path().join("plain.json",);
+    File::create(&plain,)?;
+    let created = temp_dir.path().join("created.json",);
+    let mut sink = OutputSink::open(Some(&created,), options,)?;
+    sink.write_all(b"{}",)?;
+    sink.finish()?;
+    assert_eq!(mode(&created,)?, mode(&plain,)?);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_writes_results_to_stdout() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "data.json", "{\"key\": \"value\"}",);
+
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .current_dir(temp_dir.path(),)
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .args(["--all", "--format", "json",],)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone(),)?;
+    let stderr = String::from_utf8(assert.get_output().stderr.clone(),)?;
+    assert!(stdout.contains("\"key\""), "{}", stdout);
+    assert!(!stderr.contains("\"key\""), "{}", stderr);
+    let log = fs::read_to_string(temp_dir.path().join("reader.log",),).unwrap_or_default();
+    assert!(!log.contains("\"key\""));
+    Ok((),)
+}
+
+#[test]
+fn test_cli_structured_log_file() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "data.csv", "id,name\n1,Ada\n",);
+    let log_path = temp_dir.path().join("logs.jsonl",);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .current_dir(temp_dir.path(),)
+        .env_remove("RUST_LOG",)
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .args(["-v", "--log-format", "json", "--log-file",],)
+        .arg(&log_path,)
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(&log_path,)?;
+    let read_event = log
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value,>,)
+        .collect::<Result<Vec<_,>, _,>>()?
+        .into_iter()
+        .find(|event| event["fields"]["message"] == "Read finished",)
+        .expect("a read event");
+    assert_eq!(read_event["span"]["name"], "read_file");
+    assert_eq!(read_event["span"]["bytes"], 14);
+    assert!(read_event["fields"]["elapsed_ms"].is_u64());
+    assert!(!temp_dir.path().join("reader.log",).exists());
+
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .current_dir(temp_dir.path(),)
+        .env_remove("RUST_LOG",)
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .arg("-q",)
+        .assert()
+        .success();
+    assert!(assert.get_output().stderr.is_empty());
+    Ok((),)
+}
+
+#[test]
+fn test_cli_stream_logs_the_read_at_its_end() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    let input_path = create_temp_file(temp_dir.path(), "data.csv", "id,name\n1,Ada\n2,Alan\n",);
+    let log_path = temp_dir.path().join("logs.jsonl",);
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .current_dir(temp_dir.path(),)
+        .env_remove("RUST_LOG",)
+        .arg("--file-path",)
+        .arg(&input_path,)
+        .args(["--stream", "-v", "--log-format", "json", "--log-file",],)
+        .arg(&log_path,)
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(&log_path,)?;
+    let read_event = log
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value,>,)
+        .collect::<Result<Vec<_,>, _,>>()?
+        .into_iter()
+        .find(|event| event["fields"]["message"] == "Read finished",)
+        .expect("a read event");
+    assert_eq!(read_event["fields"]["records"], 2);
+    assert_eq!(read_event["span"]["bytes"], 21);
+    Ok((),)
+}
+
+#[tokio::test]
+async fn test_read_inputs_with_globs() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let nested = dir.path().join("2024/01",);
+    fs::create_dir_all(&nested,)?;
+    create_temp_file(&nested, "b.csv", "a\n1\n",);
+    create_temp_file(&nested, "a.csv", "a\n2\n",);
+    create_temp_file(&nested, ".hidden.csv", "a\n3\n",);
+    create_temp_file(&nested, "notes.txt", "text",);
+    let extra = create_temp_file(dir.path(), "extra.json", "{\"k\": 1}",);
+
+    let pattern = dir.path().join("**/*.csv",);
+    let inputs = vec![pattern.clone(), extra.clone(), nested.join("a.csv",)];
+    let paths = expand_inputs(&inputs, &FileReaderOptions::default(),)?;
+    let names: Vec<_,> =
+        paths.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned(),).collect();
+    assert_eq!(names, ["a.csv", "b.csv", "extra.json"]);
+
+    let options = FileReaderOptions {
+        filter_exts: Some(vec!["json".to_string(), "txt".to_string()],),
+        ..Default::default()
+    };
+    let DataReaderResult::DirectoryResults(results, metadata,) =
+        read_inputs_content(&[extra, dir.path().join("2024/*",)], options,).await?
+    else {
+        panic!("expected directory results");
+    };
+    let names: Vec<_,> = results
+        .iter()
+        .map(|(p, _,)| p.file_name().unwrap().to_string_lossy().into_owned(),)
+        .collect();
+    assert_eq!(names, ["extra.json", "notes.txt"]);
+    assert_eq!(metadata.size, 12);
+
+    let missing = [dir.path().join("*.parquet",),];
+    assert!(expand_inputs(&missing, &FileReaderOptions::default(),).is_err());
+    Ok((),)
+}
+
+#[test]
+fn test_cli_reads_several_inputs() -> Result<(), Box<dyn std::error::Error,>,> {
+    let temp_dir = tempdir()?;
+    create_temp_file(temp_dir.path(), "a.csv", "id\n1\n",);
+    create_temp_file(temp_dir.path(), "b.csv", "id\n2\n",);
+    let extra = create_temp_file(temp_dir.path(), "c.json", "{\"id\": 3}",);
+
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(temp_dir.path().join("*.csv",),)
+        .arg(&extra,)
+        .args(["--format", "json",],)
+        .assert()
+        .success();
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout,)?;
+    let paths: Vec<&str,> = output["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["path"].as_str().unwrap(),)
+        .collect();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].ends_with("a.csv") && paths[1].ends_with("b.csv"));
+    assert!(paths[2].ends_with("c.json"));
+    Ok((),)
+}
+
+#[tokio::test]
+async fn test_result_envelope_round_trips() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let csv_path = create_temp_file(dir.path(), "people.csv", "id,name\n1,Ada\n",);
+    let broken_path = create_temp_file(dir.path(), "broken.json", "{ not json",);
+
+    let csv = read_file_to_data(&csv_path, None, FileFormat::Csv,)?;
+    let envelope = csv.to_envelope();
+    assert_eq!(envelope.version, ENVELOPE_VERSION);
+    assert_eq!(envelope.format, ResultFormat::Csv);
+    assert!(envelope.schema.is_some());
+    assert!(envelope.errors.is_empty());
+
+    for format in [OutputFormat::Json, OutputFormat::Yaml,] {
+        let parsed = ResultEnvelope::parse(&csv.to_string_formatted(format,),)?;
+        assert_eq!(parsed, envelope);
+        let DataReaderResult::Csv(data, metadata,) = parsed.into_result()? else {
+            panic!("expected CSV data");
+        };
+        assert_eq!(data.column_headers, ["id", "name"]);
+        assert_eq!(&metadata, csv_metadata(&csv));
+    }
+
+    let inputs = [csv_path, broken_path,];
+    let results = read_inputs_content(&inputs, FileReaderOptions::default(),).await?;
+    let envelope = ResultEnvelope::parse(&results.to_string_formatted(OutputFormat::Json,),)?;
+    assert_eq!(envelope.format, ResultFormat::Directory);
+    let DataReaderResult::DirectoryResults(entries, _,) = envelope.into_result()? else {
+        panic!("expected directory results");
+    };
+    assert!(matches!(entries[0].1, DataReaderResult::Csv(..)));
+    assert!(matches!(&entries[1].1, DataReaderResult::Error(e, _) if !e.error.is_empty()));
+
+    let future = serde_json::json!({"version": 2, "format": "csv", "metadata": {"size": 0}});
+    assert!(ResultEnvelope::parse(&future.to_string(),).is_err());
+    Ok((),)
+}
+
+fn csv_metadata(result: &DataReaderResult,) -> &FileMetadata {
+    match result {
+        DataReaderResult::Csv(_, metadata,) => metadata,
+        other => panic!("expected CSV data, got {:?}", other),
+    }
+}
+
+fn read_csv_data(path: &Path, config: &ReadConfig,) -> Result<CsvData, DataReaderError,> {
+    match read_file_to_data_with_config(path, None, FileFormat::Csv, config,)? {
+        DataReaderResult::Csv(data, _,) => Ok(data,),
+        other => panic!("expected CSV data, got {:?}", other),
+    }
+}
+
+const SALES_CSV: &str = "day;amount;share;price;paid;at\n\
+                         03.04.2024;1.234,56;12,5%;$1,234.50;yes;2024-04-03T10:00:00+02:00\n\
+                         31.12.2024;2.000,00;7%;-€3;No;2024-12-31T08:30:00+01:00\n";
+const HINTED_CSV: &str = "id,amount,due\n1,1.234,03/04/2024\n2,1.5,12/05/2024\n";
+const FLAGS_CSV: &str =
+    "id,score,flag,active,note\n10,NA,Y,1,yes\n20,\\N,n,0,-3\n30,-,y,1,NULL\n";
+const FLAGS_XML: &str =
+    "<rows><row id=\"7\" ok=\"Y\"><score>NA</score><active>0</active></row></rows>";
+const ACCOUNTS_CSV: &str =
+    "zip,account,amount,ratio\n01234,123456789012345678901234,0.1234567890123456789,1.5\n";
+
+fn flag_words() -> Result<ReadConfig, DataReaderError,> {
+    let words = |values: &[&str]| values.iter().map(|v| v.to_string(),).collect::<Vec<_,>>();
+    let coercion = ValueCoercion::default()
+        .with_null_values(words(&["NA", "\\N", "-"],),)
+        .with_booleans(words(&["Y", "1"],), words(&["N", "0"],),)?;
+    Ok(ReadConfig::default().with_coercion(coercion,),)
+}
+
+#[test]
+fn test_csv_dialect_semicolons_and_quotes() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(
+        dir.path(),
+        "export.csv",
+        "name;price\n\"Müller; GmbH\";1,5\nAcme;2,0\n",
+    );
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    let dialect = data.dialect.unwrap();
+    assert_eq!((dialect.delimiter, dialect.quote, dialect.has_header,), (';', '"', true,));
+    assert_eq!(data.column_headers, ["name", "price"]);
+    assert_eq!(data.nc_rows[0]["name"], "Müller; GmbH");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_dialect_headerless_tabs() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "numbers.tsv", "1\t2\n3\t4\n",);
+    assert_eq!(get_file_format(&path), FileFormat::Csv);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert!(!data.dialect.unwrap().has_header);
+    assert_eq!(data.column_headers, ["column_1", "column_2"]);
+    assert_eq!(data.num_rows, 2);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_dialect_pipes_and_comments() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "codes.psv", "# exported nightly\ncode|label\nA|x\n",);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    let dialect = data.dialect.unwrap();
+    assert_eq!((dialect.delimiter, dialect.comment,), ('|', Some('#'),));
+    assert_eq!(data.column_headers, ["code", "label"]);
+    assert_eq!(data.num_rows, 1);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_dialect_header_override() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "numbers.tsv", "1\t2\n3\t4\n",);
+    let config = ReadConfig::default().with_csv(CsvOptions {
+        has_header: Some(true,),
+        ..CsvOptions::default()
+    },);
+    let data = read_csv_data(&path, &config,)?;
+    assert_eq!(data.column_headers, ["1", "2"]);
+    assert_eq!(data.num_rows, 1);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_dialect_comments_below_the_header() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(
+        dir.path(),
+        "codes.csv",
+        "code;label\nA;x\n# totals follow\nB;y\n# end of export\n",
+    );
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert_eq!(data.dialect.unwrap().comment, Some('#'));
+    assert_eq!(data.num_rows, 2);
+    assert_eq!(data.nc_rows[1]["code"], "B");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_raw_content_uses_dialect_overrides() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "numbers.tsv", "1\t2\n3\t4\n",);
+    let config = ReadConfig::default().with_csv(CsvOptions {
+        has_header: Some(true,),
+        ..CsvOptions::default()
+    },);
+    let raw = read_file_to_raw_content_with_config(&path, None, OutputFormat::Json, &config,)?;
+    let rows: serde_json::Value = serde_json::from_str(&raw,)?;
+    assert_eq!(rows, serde_json::json!([{"1": "3", "2": "4"}]));
+    Ok((),)
+}
+
+#[test]
+fn test_cli_csv_dialect_flags() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "export.csv", "name;price\nAcme;2,0\n",);
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--stream", "--format", "json", "--no-header", "--csv-delimiter", ";",],)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone(),)?;
+    let first: serde_json::Value = serde_json::from_str(stdout.lines().next().unwrap(),)?;
+    assert_eq!(first, serde_json::json!({"column_1": "name", "column_2": "price"}));
+    Ok((),)
+}
+
+#[test]
+fn test_csv_inference_localized_values() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "sales.csv", SALES_CSV,);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    let row = &data.nc_rows[0];
+    assert_eq!(row["amount"], 1234.56);
+    assert_eq!(row["share"], 0.125);
+    assert_eq!(row["price"], 1234.5);
+    assert_eq!(row["paid"], true);
+    assert_eq!(row["day"], "03.04.2024");
+    let row = &data.nc_rows[1];
+    assert_eq!((row["amount"].as_f64(), row["price"].as_i64(),), (Some(2000.0), Some(-3)));
+    assert_eq!(row["paid"], false);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_inference_schema_and_date_formats() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "sales.csv", SALES_CSV,);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    let schema = data.inferred_schema.unwrap();
+    assert_eq!(schema["amount"], DataType::Float);
+    assert_eq!(schema["paid"], DataType::Boolean);
+    assert_eq!(schema["day"], DataType::String);
+    let formats = data.inferred_formats.unwrap();
+    assert_eq!(formats.len(), 2);
+    assert_eq!(formats["day"], "%d.%m.%Y");
+    assert_eq!(formats["at"], "%Y-%m-%dT%H:%M:%S%.f%:z");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_inference_day_first_dates() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "hinted.csv", HINTED_CSV,);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert_eq!(data.nc_rows[0]["amount"], 1.234);
+    assert_eq!(data.inferred_formats.unwrap()["due"], "%d/%m/%Y");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_inference_column_hints() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "hinted.csv", HINTED_CSV,);
+    let inference = InferenceOptions::default()
+        .with_column_hint("amount", ColumnHint::Locale(NumberLocale::DE,),)
+        .with_column_hint("due", "%m/%d/%Y".parse()?,);
+    let data = read_csv_data(&path, &ReadConfig::default().with_inference(inference,),)?;
+    assert_eq!(data.nc_rows[0]["amount"], 1234);
+    assert_eq!(data.nc_rows[1]["amount"], "1.5");
+    assert_eq!(data.inferred_formats.unwrap()["due"], "%m/%d/%Y");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_inference_ambiguous_comma_is_text() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "amounts.csv", "id;amount\n1;12,34\n2;1,234\n",);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert_eq!(data.nc_rows[0]["amount"], 12.34);
+    assert_eq!(data.nc_rows[1]["amount"], "1,234");
+
+    let inference = InferenceOptions {
+        locale: Some(NumberLocale::DE,),
+        ..Default::default()
+    };
+    let data = read_csv_data(&path, &ReadConfig::default().with_inference(inference,),)?;
+    assert_eq!(data.nc_rows[1]["amount"], 1.234);
+    assert_eq!(data.inferred_schema.unwrap()["amount"], DataType::Float);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_column_format() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "hinted.csv", HINTED_CSV,);
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--format", "json", "--column-format", "amount=de",],)
+        .assert()
+        .success();
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout,)?;
+    assert_eq!(output["data"]["nc_rows"][0]["amount"], 1234);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_summary_samples_large_files() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let mut content = String::from("id,value\n",);
+    for id in 1..=5000 {
+        content.push_str(&format!("{},{}.5\n", id, id),);
+    }
+    let path = create_temp_file(dir.path(), "large.csv", &content,);
+    let config = ReadConfig::default().with_sample(SampleOptions {
+        first: 10,
+        reservoir: 5,
+        ..SampleOptions::default()
+    },);
+    let read_ids = || -> Result<Vec<i64,>, DataReaderError,> {
+        let data = read_csv_data(&path, &config,)?;
+        assert_eq!(data.num_rows, 5000);
+        assert!(data.sampled);
+        assert_eq!(data.inferred_schema.as_ref().unwrap()["value"], DataType::Float);
+        Ok(data.nc_rows.iter().map(|row| row["id"].as_i64().unwrap(),).collect(),)
+    };
+
+    let ids = read_ids()?;
+    assert_eq!(ids.len(), 15);
+    assert_eq!(ids[..10], (1..=10).collect::<Vec<i64,>>()[..]);
+    assert!(ids.windows(2,).all(|pair| pair[0] < pair[1]));
+    assert_eq!(read_ids()?, ids, "the same seed picks the same rows");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_summary_keeps_small_files_whole() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "small.csv", "id\n1\n2\n3\n",);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert!(!data.sampled);
+    assert_eq!(data.nc_rows.len(), 3);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_sample_flags() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "small.csv", "id\n1\n2\n3\n",);
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--format", "json", "--sample-first", "2", "--sample-reservoir", "0",],)
+        .assert()
+        .success();
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout,)?;
+    assert_eq!(output["data"]["num_rows"], 3);
+    assert_eq!(output["data"]["sampled"], true);
+    assert_eq!(output["data"]["nc_rows"].as_array().unwrap().len(), 2);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_null_values_and_boolean_words() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "flags.csv", FLAGS_CSV,);
+    let data = read_csv_data(&path, &flag_words()?,)?;
+    assert_eq!(
+        data.nc_rows[1],
+        serde_json::json!({"id": 20, "score": null, "flag": false, "active": false, "note": -3})
+    );
+    assert_eq!(data.nc_rows[0]["note"], "yes", "the boolean words were replaced");
+    assert_eq!(data.nc_rows[2]["note"], "NULL", "null values match exactly");
+    let schema = data.inferred_schema.unwrap();
+    assert_eq!(schema["id"], DataType::Integer);
+    assert_eq!(schema["score"], DataType::Null);
+    assert_eq!(schema["flag"], DataType::Boolean);
+    assert_eq!(schema["active"], DataType::Boolean);
+    Ok((),)
+}
+
+#[test]
+fn test_xml_null_values_and_boolean_words() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "flags.xml", FLAGS_XML,);
+    let config = flag_words()?;
+    let DataReaderResult::Stream(records, _,) =
+        read_file_to_stream_with_config(&path, FileFormat::Xml, &config,)?
+    else {
+        panic!("expected a record stream");
+    };
+    let records = records.collect::<Result<Vec<_,>, _,>>()?;
+    assert_eq!(
+        records,
+        vec![serde_json::json!({"@id": 7, "@ok": true, "score": null, "active": false})]
+    );
+    Ok((),)
+}
+
+#[test]
+fn test_xml_schema_with_boolean_words() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "flags.xml", FLAGS_XML,);
+    let DataReaderResult::Xml(data, _,) =
+        read_file_to_data_with_config(&path, None, FileFormat::Xml, &flag_words()?,)?
+    else {
+        panic!("expected XML data");
+    };
+    let schema = data.inferred_schema.unwrap();
+    let XmlSchemaType::Element(row,) = &schema.children["row"] else {
+        panic!("expected one row element");
+    };
+    assert_eq!(row.attributes["ok"], DataType::Boolean);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_null_value_and_boolean_word_flags() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "flags.csv", FLAGS_CSV,);
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--format", "json", "--null-value", "NA", "--null-value", "-", "--true-value", "Y"],)
+        .args(["--false-value", "n"],)
+        .assert()
+        .success();
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout,)?;
+    let rows = output["data"]["nc_rows"].as_array().unwrap();
+    assert!(rows[0]["score"].is_null() && rows[2]["score"].is_null());
+    assert_eq!(rows[1]["score"], "\\N");
+    assert_eq!(rows[1]["flag"], false);
+    assert_eq!(rows[0]["active"], 1);
+    Ok((),)
+}
+
+#[test]
+fn test_cli_rejects_a_word_that_is_true_and_false() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "flags.csv", FLAGS_CSV,);
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--true-value", "Y", "--false-value", "y"],)
+        .assert()
+        .failure();
+    Ok((),)
+}
+
+#[test]
+fn test_parse_number_keeps_exact_values() {
+    assert_eq!(NcValue::parse_number("1.50"), Some(NcValue::Float(1.5)));
+    assert_eq!(NcValue::parse_number("18446744073709551616"), Some(NcValue::BigInteger(1 << 64)));
+    assert_eq!(
+        NcValue::parse_number("-0.12345678901234567890"),
+        Some(NcValue::Decimal {
+            unscaled: -12345678901234567890,
+            scale:    20,
+        })
+    );
+    assert_eq!(NcValue::parse_number("1e400"), None);
+    assert_eq!(NcValue::parse_number("inf"), None);
+}
+
+#[test]
+fn test_csv_exact_numbers() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "accounts.csv", ACCOUNTS_CSV,);
+    let data = read_csv_data(&path, &ReadConfig::default(),)?;
+    assert_eq!(
+        data.nc_rows[0],
+        serde_json::json!({
+            "zip": 1234,
+            "account": "123456789012345678901234",
+            "amount": "0.1234567890123456789",
+            "ratio": 1.5
+        })
+    );
+    let schema = data.inferred_schema.unwrap();
+    assert_eq!(schema["account"], DataType::Number);
+    assert_eq!(schema["amount"], DataType::Number);
+    assert_eq!(schema["ratio"], DataType::Float);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_declared_string_type() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "accounts.csv", ACCOUNTS_CSV,);
+    let typed = ValueCoercion::default().with_type("zip", ValueType::String,);
+    let data = read_csv_data(&path, &ReadConfig::default().with_coercion(typed,),)?;
+    assert_eq!(data.nc_rows[0]["zip"], "01234");
+    assert_eq!(data.inferred_schema.unwrap()["zip"], DataType::String);
+    Ok((),)
+}
+
+#[test]
+fn test_csv_preserve_strings() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "accounts.csv", ACCOUNTS_CSV,);
+    let preserved = ValueCoercion::default().with_preserve_strings(true,);
+    let data = read_csv_data(&path, &ReadConfig::default().with_coercion(preserved,),)?;
+    assert_eq!(data.nc_rows[0]["ratio"], "1.5");
+    assert_eq!(data.nc_rows[0]["zip"], "01234");
+    Ok((),)
+}
+
+#[test]
+fn test_csv_declared_type_mismatch_fails() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "accounts.csv", ACCOUNTS_CSV,);
+    let strict = ValueCoercion::default().with_type("ratio", ValueType::Integer,);
+    let error = read_csv_data(&path, &ReadConfig::default().with_coercion(strict,),).unwrap_err();
+    assert!(error.to_string().contains("ratio"), "{}", error);
+    Ok((),)
+}
+
+#[test]
+fn test_xml_declared_types_and_exact_numbers() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(
+        dir.path(),
+        "accounts.xml",
+        "<rows><row id=\"007\"><amount>12345678901234567890.5</amount></row></rows>",
+    );
+    let typed = ValueCoercion::default().with_type("@id", ValueType::String,);
+    let config = ReadConfig::default().with_coercion(typed,);
+    let DataReaderResult::Stream(records, _,) =
+        read_file_to_stream_with_config(&path, FileFormat::Xml, &config,)?
+    else {
+        panic!("expected a record stream");
+    };
+    let records = records.collect::<Result<Vec<_,>, _,>>()?;
+    assert_eq!(
+        records,
+        vec![serde_json::json!({"@id": "007", "amount": "12345678901234567890.5"})]
+    );
+    Ok((),)
+}
+
+#[test]
+fn test_xml_skip_policy_keeps_records_after_a_declared_type_mismatch()
+-> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(
+        dir.path(),
+        "typed.xml",
+        "<rows><row id=\"x\"><name>ada</name></row><row id=\"2\"><name>alan</name></row>\
+         <row id=\"3\"/></rows>",
+    );
+    let typed = ValueCoercion::default().with_type("@id", ValueType::Integer,);
+    let config =
+        ReadConfig::default().with_coercion(typed,).with_error_policy(ErrorPolicy::Skip,);
+    let DataReaderResult::Stream(records, _,) =
+        read_file_to_stream_with_config(&path, FileFormat::Xml, &config,)?
+    else {
+        panic!("expected a record stream");
+    };
+    let records = records.collect::<Result<Vec<_,>, _,>>()?;
+    assert_eq!(
+        records,
+        vec![serde_json::json!({"@id": 2, "name": "alan"}), serde_json::json!({"@id": 3})]
+    );
+    let rejects = config.rejects.report();
+    assert_eq!(rejects.skipped, 1);
+    assert_eq!(rejects.records[0].position.as_ref().and_then(|p| p.record_index), Some(0));
+    Ok((),)
+}
+
+#[test]
+fn test_cli_declared_types() -> Result<(), Box<dyn std::error::Error,>,> {
+    let dir = tempdir()?;
+    let path = create_temp_file(dir.path(), "accounts.csv", ACCOUNTS_CSV,);
+    let assert = assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--format", "json", "--type", "zip=string"],)
+        .assert()
+        .success();
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout,)?;
+    assert_eq!(output["data"]["nc_rows"][0]["zip"], "01234");
+
+    assert_cmd::Command::cargo_bin("nc-reader",)?
+        .arg("--file-path",)
+        .arg(&path,)
+        .args(["--type", "zip=date"],)
+        .assert()
+        .failure();
+    Ok((),)
+}
@@ -1,7 +1,14 @@
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use nc_reader::file_reader::{
+    FileReaderOptions, read_bytes_content_blocking, read_reader_content_blocking,
+};
+use nc_reader::nc_reader_result::DataReaderResult;
+use nc_reader::output::OutputMode;
 use nc_reader::reader::json_reader::read_json_value;
 use nc_reader::reader::xml_reader::read_xml_content;
 use tempfile::tempdir;
@@ -125,3 +132,640 @@ fn test_parquet_streaming() {
     // We don't know the exact count without reading it, but we can check it's > 0
     assert!(count > 0);
 }
+
+#[test]
+fn test_csv_stream_from_bytes() {
+    let data = bytes::Bytes::from_static(b"id,name\n1,alpha\n2,beta\n",);
+    let (headers, stream,) =
+        nc_reader::reader::csv_reader::read_csv_stream_from_bytes(data, Path::new("-",),)
+            .unwrap();
+    assert_eq!(headers, vec!["id".to_string(), "name".to_string()]);
+
+    let records: Vec<serde_json::Value,> = stream.map(|r| r.unwrap(),).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1]["id"], serde_json::Value::from(2));
+    assert_eq!(records[1]["name"], "beta");
+}
+
+#[test]
+fn test_json_stream_from_reader() {
+    let input = std::io::Cursor::new(b"{\"a\": 1}\n{\"a\": 2}\n".to_vec(),);
+    let stream = nc_reader::reader::json_reader::read_json_stream_from_reader(
+        input,
+        true,
+        Path::new("socket",),
+    )
+    .unwrap();
+
+    let values: Vec<serde_json::Value,> = stream.map(|r| r.unwrap(),).collect();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0]["a"], serde_json::Value::from(1));
+}
+
+#[test]
+fn test_xml_stream_from_bytes() {
+    let data = bytes::Bytes::from_static(b"<root><item id=\"7\">x</item></root>",);
+    let stream =
+        nc_reader::reader::xml_reader::create_xml_stream_from_bytes(data, Path::new("-",),)
+            .unwrap();
+
+    let records: Vec<serde_json::Value,> = stream.map(|r| r.unwrap(),).collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["@id"], serde_json::Value::from(7));
+}
+
+#[test]
+fn test_bytes_get_the_summary_of_their_format() {
+    let data = bytes::Bytes::from_static(b"id;name\n1;alpha\n2;beta\n",);
+    let options = FileReaderOptions {
+        file_type_override: Some("csv".to_string(),),
+        ..FileReaderOptions::default()
+    };
+    let result = read_bytes_content_blocking(data, Path::new("-",), options,).unwrap();
+
+    let DataReaderResult::Csv(csv, metadata,) = result else {
+        panic!("expected a CSV summary");
+    };
+    assert_eq!(csv.num_rows, 2);
+    assert_eq!(csv.dialect.unwrap().delimiter, ';');
+    assert_eq!(metadata.size, 23);
+}
+
+/// CSV rows generated as they are read, counting the bytes handed out.
+struct GeneratedCsv {
+    next_row:  usize,
+    rows:      usize,
+    pending:   Vec<u8,>,
+    delivered: Arc<AtomicUsize,>,
+}
+
+impl Read for GeneratedCsv {
+    fn read(&mut self, buf: &mut [u8],) -> std::io::Result<usize,> {
+        if self.pending.is_empty() && self.next_row <= self.rows {
+            self.pending = match self.next_row {
+                0 => b"id,name\n".to_vec(),
+                row => format!("{},row{}\n", row, row).into_bytes(),
+            };
+            self.next_row += 1;
+        }
+        let n = buf.len().min(self.pending.len(),);
+        buf[..n].copy_from_slice(&self.pending[..n],);
+        self.pending.drain(..n,);
+        self.delivered.fetch_add(n, Ordering::SeqCst,);
+        Ok(n,)
+    }
+}
+
+#[test]
+fn test_reader_streams_records_as_the_input_arrives() {
+    let delivered = Arc::new(AtomicUsize::new(0,),);
+    let source = GeneratedCsv {
+        next_row:  0,
+        rows:      100_000,
+        pending:   Vec::new(),
+        delivered: delivered.clone(),
+    };
+    let options = FileReaderOptions {
+        file_type_override: Some("csv".to_string(),),
+        output_mode: OutputMode::Stream,
+        ..FileReaderOptions::default()
+    };
+    let result =
+        read_reader_content_blocking(Box::new(source,), Path::new("-",), options,).unwrap();
+    let DataReaderResult::Stream(mut stream, _,) = result else {
+        panic!("expected a record stream");
+    };
+
+    let first = stream.next().unwrap().unwrap();
+    assert_eq!(first["id"], serde_json::Value::from(1));
+    // Only the start of the roughly 1.4 MB of input has been read.
+    assert!(delivered.load(Ordering::SeqCst,) < 100_000);
+    assert_eq!(stream.count(), 99_999);
+}
+
+#[test]
+fn test_reader_input_stops_at_max_bytes() {
+    use nc_reader::error::DataReaderError;
+    use nc_reader::reader::limits::{Limit, ReadLimits};
+    use nc_reader::reader::read_config::ReadConfig;
+
+    let endless = |delivered: &Arc<AtomicUsize,>| GeneratedCsv {
+        next_row:  0,
+        rows:      usize::MAX,
+        pending:   Vec::new(),
+        delivered: delivered.clone(),
+    };
+    let options = |output_mode| FileReaderOptions {
+        file_type_override: Some("csv".to_string(),),
+        output_mode,
+        read_config: ReadConfig::default().with_limits(ReadLimits {
+            max_bytes: Some(64 * 1024,),
+            ..Default::default()
+        },),
+        ..FileReaderOptions::default()
+    };
+
+    let delivered = Arc::new(AtomicUsize::new(0,),);
+    let source = Box::new(endless(&delivered,),);
+    let result =
+        read_reader_content_blocking(source, Path::new("-",), options(OutputMode::Stream,),)
+            .unwrap();
+    let DataReaderResult::Stream(stream, _,) = result else {
+        panic!("expected a record stream");
+    };
+    let error = stream.filter_map(Result::err,).next().expect("expected a limit error",);
+    assert!(matches!(error, DataReaderError::LimitExceeded { limit: Limit::Bytes(65536), .. }));
+    assert!(delivered.load(Ordering::SeqCst,) < 1024 * 1024);
+
+    let delivered = Arc::new(AtomicUsize::new(0,),);
+    let source = Box::new(endless(&delivered,),);
+    let result =
+        read_reader_content_blocking(source, Path::new("-",), options(OutputMode::Default,),);
+    assert!(matches!(
+        result,
+        Err(DataReaderError::LimitExceeded { limit: Limit::Bytes(65536), .. })
+    ));
+    assert!(delivered.load(Ordering::SeqCst,) < 1024 * 1024);
+}
+
+#[test]
+fn test_text_reader_checks_cancellation() {
+    use nc_reader::error::DataReaderError;
+    use nc_reader::reader::read_config::ReadConfig;
+    use nc_reader::reader::txt_reader::read_txt_content_with_config;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("notes.txt",);
+    std::fs::write(&file_path, "hello\n",).unwrap();
+
+    let config = ReadConfig::default();
+    config.cancel.cancel();
+    let result = read_txt_content_with_config(&file_path, None, &config,);
+    assert!(matches!(result, Err(DataReaderError::Cancelled { .. })));
+}
+
+#[tokio::test]
+async fn test_csv_stream_async() {
+    use futures::StreamExt;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("async.csv",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        writeln!(file, "id,value").unwrap();
+        for i in 0..5000 {
+            writeln!(file, "{},{}", i, i * 2).unwrap();
+        }
+    }
+
+    let (headers, mut stream,) =
+        nc_reader::reader::csv_reader::read_csv_stream_async(&file_path,).await.unwrap();
+    assert_eq!(headers, vec!["id".to_string(), "value".to_string()]);
+
+    let mut count = 0;
+    while let Some(record,) = stream.next().await {
+        let record = record.unwrap();
+        assert_eq!(record["id"], serde_json::Value::from(count));
+        count += 1;
+    }
+    assert_eq!(count, 5000);
+}
+
+#[tokio::test]
+async fn test_async_stream_early_drop() {
+    use futures::StreamExt;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("items.jsonl",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        for i in 0..10000 {
+            writeln!(file, "{{\"n\": {}}}", i).unwrap();
+        }
+    }
+
+    let stream = nc_reader::file_reader::read_file_to_async_stream(
+        &file_path,
+        nc_reader::file_reader::FileFormat::Json,
+    )
+    .await
+    .unwrap();
+    // Taking a prefix drops the receiver; the blocking reader must stop rather than hang.
+    let first: Vec<_,> = stream.take(3,).collect().await;
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[2].as_ref().unwrap()["n"], serde_json::Value::from(2));
+}
+
+#[tokio::test]
+async fn test_directory_streams_open_one_file_at_a_time() {
+    use nc_reader::file_reader::read_directory_content;
+    use nc_reader::output::OutputFormat;
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.csv",), "id\n1\n2\n",).unwrap();
+    std::fs::write(dir.path().join("b.csv",), "id\n3\n",).unwrap();
+    let options = FileReaderOptions {
+        output_mode: OutputMode::Stream,
+        ..FileReaderOptions::default()
+    };
+    let result = read_directory_content(dir.path(), options,).await.unwrap();
+    let DataReaderResult::DirectoryResults(entries, _,) = &result else {
+        panic!("Expected DirectoryResults, got {:?}", result);
+    };
+    assert!(entries.iter().all(|(_, entry,)| matches!(entry, DataReaderResult::Pending(..))));
+
+    // No file is open yet, so one removed before the writer gets to it is reported missing.
+    std::fs::remove_file(dir.path().join("b.csv",),).unwrap();
+    let mut output = Vec::new();
+    result.write_streaming(OutputFormat::Ndjson, &mut output,).unwrap();
+    let lines: Vec<serde_json::Value,> = String::from_utf8(output,)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line,).unwrap(),)
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1]["format"], "record");
+    assert_eq!(lines[1]["data"]["id"], serde_json::Value::from(2));
+    assert!(lines[2]["path"].as_str().unwrap().ends_with("b.csv"));
+    assert_eq!(lines[2]["format"], "error");
+    assert!(lines[2]["errors"][0]["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_streamed_directory_output_round_trips() {
+    use nc_reader::envelope::{ENVELOPE_VERSION, ResultEnvelope, ResultFormat};
+    use nc_reader::file_reader::read_directory_content;
+    use nc_reader::output::OutputFormat;
+
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("a.csv",), "id\n1\n2\n",).unwrap();
+    std::fs::write(dir.path().join("b.txt",), "notes",).unwrap();
+    let options = FileReaderOptions {
+        output_mode: OutputMode::Stream,
+        ..FileReaderOptions::default()
+    };
+    for format in [OutputFormat::Ndjson, OutputFormat::Yaml] {
+        let result = read_directory_content(dir.path(), options.clone(),).await.unwrap();
+        let mut output = Vec::new();
+        result.write_streaming(format, &mut output,).unwrap();
+        let output = String::from_utf8(output,).unwrap();
+        let documents: Vec<&str,> = match format {
+            OutputFormat::Yaml => output.split("---\n",).filter(|d| !d.is_empty(),).collect(),
+            _ => output.lines().collect(),
+        };
+        let envelopes: Vec<ResultEnvelope,> =
+            documents.iter().map(|d| ResultEnvelope::parse(d,).unwrap(),).collect();
+
+        assert_eq!(envelopes.len(), 3, "{}", output);
+        assert!(envelopes.iter().all(|e| e.version == ENVELOPE_VERSION));
+        for (envelope, id,) in envelopes[..2].iter().zip([1, 2],) {
+            assert_eq!(envelope.format, ResultFormat::Record);
+            assert!(envelope.path.as_ref().unwrap().ends_with("a.csv"));
+            assert_eq!(envelope.data["id"], serde_json::Value::from(id));
+        }
+        assert!(envelopes[2].path.as_ref().unwrap().ends_with("b.txt"));
+        let DataReaderResult::Text(text, _,) = envelopes[2].clone().into_result().unwrap() else {
+            panic!("Expected Text result");
+        };
+        assert_eq!(text.content, "notes");
+    }
+}
+
+#[test]
+fn test_nc_value_lossless_json() {
+    use nc_reader::record::NcValue;
+    use nc_schema::DataType;
+
+    let decimal = NcValue::Decimal {
+        unscaled: -12345678901234567890123,
+        scale:    4,
+    };
+    assert_eq!(decimal.to_json(), serde_json::json!("-1234567890123456789.0123"));
+    assert_eq!(decimal.data_type(), DataType::Number);
+
+    let small = NcValue::Decimal {
+        unscaled: 5,
+        scale:    3,
+    };
+    assert_eq!(small.to_string(), "0.005");
+
+    let ts = chrono::DateTime::parse_from_rfc3339("2024-03-01T12:30:00.123456789+02:00",).unwrap();
+    assert_eq!(
+        NcValue::Timestamp(ts,).to_json(),
+        serde_json::json!("2024-03-01T12:30:00.123456789+02:00")
+    );
+    assert_eq!(NcValue::Binary(vec![0xde, 0xad],).to_json(), serde_json::json!("dead"));
+    assert_eq!(NcValue::from(serde_json::json!(u64::MAX)), NcValue::BigInteger(u64::MAX as i128));
+}
+
+#[test]
+fn test_typed_stream_from_jsonl() {
+    use nc_reader::record::NcValue;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("typed.jsonl",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        writeln!(file, "{{\"id\": 1, \"score\": 2.5, \"tags\": [\"a\"]}}").unwrap();
+    }
+
+    let records: Vec<NcValue,> = nc_reader::file_reader::read_file_to_typed_stream(
+        &file_path,
+        nc_reader::file_reader::FileFormat::Json,
+    )
+    .unwrap()
+    .map(|r| r.unwrap(),)
+    .collect();
+    assert_eq!(records.len(), 1);
+    let NcValue::Object(fields,) = &records[0] else {
+        panic!("expected an object record");
+    };
+    assert_eq!(fields["id"], NcValue::Integer(1));
+    assert_eq!(fields["score"], NcValue::Float(2.5));
+    assert_eq!(fields["tags"], NcValue::Array(vec![NcValue::String("a".to_string())]));
+}
+
+#[test]
+fn test_typed_stream_from_csv_keeps_exact_decimals() {
+    use nc_reader::record::NcValue;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("typed.csv",);
+    std::fs::write(&file_path, "id,amount\n1,12345678901234567890.12\n",).unwrap();
+
+    let records: Vec<NcValue,> = nc_reader::file_reader::read_file_to_typed_stream(
+        &file_path,
+        nc_reader::file_reader::FileFormat::Csv,
+    )
+    .unwrap()
+    .map(|r| r.unwrap(),)
+    .collect();
+    let NcValue::Object(fields,) = &records[0] else {
+        panic!("expected an object record");
+    };
+    assert_eq!(fields["id"], NcValue::Integer(1));
+    assert_eq!(
+        fields["amount"],
+        NcValue::Decimal {
+            unscaled: 1234567890123456789012,
+            scale:    2,
+        }
+    );
+}
+
+#[test]
+fn test_typed_stream_from_xml_keeps_big_integers() {
+    use nc_reader::record::NcValue;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("typed.xml",);
+    std::fs::write(
+        &file_path,
+        "<rows><row id=\"1\"><big>99999999999999999999</big></row></rows>",
+    )
+    .unwrap();
+
+    let records: Vec<NcValue,> = nc_reader::file_reader::read_file_to_typed_stream(
+        &file_path,
+        nc_reader::file_reader::FileFormat::Xml,
+    )
+    .unwrap()
+    .map(|r| r.unwrap(),)
+    .collect();
+    let NcValue::Object(fields,) = &records[0] else {
+        panic!("expected an object record");
+    };
+    assert_eq!(fields["@id"], NcValue::Integer(1));
+    assert_eq!(fields["big"], NcValue::BigInteger(99999999999999999999));
+}
+
+#[test]
+fn test_csv_record_batches() {
+    use arrow::datatypes::DataType as ArrowType;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("batches.csv",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        writeln!(file, "id,name,score").unwrap();
+        for i in 0..5 {
+            writeln!(file, "{},name{},{}.5", i, i, i).unwrap();
+        }
+    }
+
+    let (schema, batches,) =
+        nc_reader::reader::csv_reader::read_csv_batches(&file_path, 2,).unwrap();
+    assert_eq!(schema.field(0,).name(), "id");
+    assert_eq!(schema.field(0,).data_type(), &ArrowType::Int64);
+    assert_eq!(schema.field(1,).data_type(), &ArrowType::Utf8);
+    assert_eq!(schema.field(2,).data_type(), &ArrowType::Float64);
+
+    let batches: Vec<_,> = batches.map(|b| b.unwrap(),).collect();
+    assert_eq!(batches.len(), 3);
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 5);
+}
+
+#[test]
+fn test_csv_record_batches_with_config() {
+    use arrow::datatypes::DataType as ArrowType;
+    use nc_reader::reader::coercion::ValueCoercion;
+    use nc_reader::reader::read_config::ReadConfig;
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("decimals.csv",);
+    std::fs::write(
+        &file_path,
+        "amount,big,flag\n12345678901234567890.12,99999999999999999999,Y\n1.5,1,N\n",
+    )
+    .unwrap();
+
+    let coercion = ValueCoercion::default()
+        .with_booleans(vec!["Y".to_string()], vec!["N".to_string()],)
+        .unwrap();
+    let config = ReadConfig::default().with_coercion(coercion,);
+    let (schema, batches,) =
+        nc_reader::reader::csv_reader::read_csv_batches_with_config(&file_path, 10, &config,)
+            .unwrap();
+    assert_eq!(schema.field(0,).data_type(), &ArrowType::Float64);
+    assert_eq!(schema.field(1,).data_type(), &ArrowType::Float64);
+    assert_eq!(schema.field(2,).data_type(), &ArrowType::Boolean);
+
+    let batches: Vec<_,> = batches.map(|b| b.unwrap(),).collect();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+}
+
+#[test]
+fn test_sqlite_record_batches() {
+    use arrow::array::{Array, AsArray};
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("batches.db",);
+    {
+        let conn = rusqlite::Connection::open(&file_path,).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE people (id INTEGER, name TEXT, height REAL);
+             INSERT INTO people VALUES (1, 'ada', 1.7), (2, 'alan', NULL), (3, NULL, 1.8);",
+        )
+        .unwrap();
+    }
+
+    let (schema, batches,) = nc_reader::file_reader::read_file_to_batches(
+        &file_path,
+        nc_reader::file_reader::FileFormat::Sqlite,
+        2,
+    )
+    .unwrap();
+    assert_eq!(schema.fields().len(), 3);
+
+    let batches: Vec<_,> = batches.map(|b| b.unwrap(),).collect();
+    assert_eq!(batches.len(), 2);
+    let ids = batches[0].column(0,).as_primitive::<arrow::datatypes::Int64Type>();
+    assert_eq!(ids.value(1,), 2);
+    assert!(batches[1].column(1,).is_null(0,));
+}
+
+#[test]
+fn test_sqlite_first_table_and_missing_table() {
+    use nc_reader::reader::sqlite_reader::{first_sqlite_table, read_sqlite_batches};
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("tables.db",);
+    {
+        let conn = rusqlite::Connection::open(&file_path,).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE zebra (id INTEGER);
+             CREATE TABLE apple (id INTEGER);",
+        )
+        .unwrap();
+    }
+
+    assert_eq!(first_sqlite_table(&file_path,).unwrap().as_deref(), Some("zebra"));
+    assert!(read_sqlite_batches(&file_path, "missing", 10,).is_err());
+}
+
+#[test]
+fn test_jsonl_parse_error_position() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("broken.jsonl",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        writeln!(file, "{{\"a\": 1}}").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "  {{\"a\": }}").unwrap();
+    }
+
+    let error = nc_reader::reader::json_reader::read_json_stream(&file_path,)
+        .unwrap()
+        .find_map(Result::err,)
+        .expect("expected a parse error",);
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.line, Some(3));
+    assert_eq!(position.record_index, Some(1));
+    assert!(position.column.is_some_and(|c| c > 2));
+    assert!(error.to_string().contains("line 3",));
+}
+
+#[test]
+fn test_csv_parse_error_position() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("broken.csv",);
+    {
+        let mut file = File::create(&file_path,).unwrap();
+        writeln!(file, "id,name").unwrap();
+        writeln!(file, "1,ada").unwrap();
+        writeln!(file, "2,alan,extra").unwrap();
+    }
+
+    let (_headers, mut stream,) =
+        nc_reader::reader::csv_reader::read_csv_stream(&file_path,).unwrap();
+    let error = stream.find_map(Result::err,).expect("expected a parse error",);
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.line, Some(3));
+    assert_eq!(position.record_index, Some(2));
+    assert_eq!(position.byte_offset, Some(14));
+}
+
+#[test]
+fn test_xml_parse_error_position() {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("broken.xml",);
+    std::fs::write(
+        &file_path,
+        "<rows>\n  <row><id>1</id></row>\n  <row><id>2</id></wrong>\n</rows>\n",
+    )
+    .unwrap();
+
+    let error = nc_reader::reader::xml_reader::create_xml_stream(&file_path,)
+        .unwrap()
+        .find_map(Result::err,)
+        .expect("expected a parse error",);
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.line, Some(3));
+    assert_eq!(position.record_index, Some(1));
+    assert!(position.column.is_some_and(|c| c > 2));
+    assert!(position.byte_offset.is_some_and(|offset| offset > 30));
+}
+
+#[test]
+fn test_yaml_and_toml_parse_error_positions() {
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("broken.yaml",);
+    std::fs::write(&yaml_path, "a: 1\nb: 2\n  c: 3\n",).unwrap();
+    let error = nc_reader::reader::yaml_reader::read_yaml_value(&yaml_path, None,).unwrap_err();
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.line, Some(3));
+    assert!(position.column.is_some() && position.byte_offset.is_some());
+
+    let toml_path = dir.path().join("broken.toml",);
+    std::fs::write(&toml_path, "a = 1\nb = = 2\n",).unwrap();
+    let error = nc_reader::reader::toml_reader::read_toml_value(&toml_path, None,).unwrap_err();
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.line, Some(2));
+    assert!(position.column.is_some_and(|c| c > 1));
+}
+
+#[test]
+fn test_parquet_error_position_names_row_group() {
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("broken.parquet",);
+    let ids = arrow::array::Int64Array::from_iter_values(0..1034,);
+    let batch = arrow::record_batch::RecordBatch::try_from_iter([(
+        "id",
+        Arc::new(ids,) as arrow::array::ArrayRef,
+    ),],)
+    .unwrap();
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(1024,)
+        .set_compression(Compression::UNCOMPRESSED,)
+        .set_dictionary_enabled(false,)
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(File::create(&file_path,).unwrap(), batch.schema(), Some(properties,),)
+            .unwrap();
+    writer.write(&batch,).unwrap();
+    writer.close().unwrap();
+
+    // Overwrite the page header of the second row group; the footer stays readable.
+    let offset = SerializedFileReader::new(File::open(&file_path,).unwrap(),)
+        .unwrap()
+        .metadata()
+        .row_group(1,)
+        .column(0,)
+        .data_page_offset() as usize;
+    let mut bytes = std::fs::read(&file_path,).unwrap();
+    bytes[offset..offset + 8].fill(0xFF,);
+    std::fs::write(&file_path, bytes,).unwrap();
+
+    let error = nc_reader::reader::parquet_reader::read_parquet_stream(&file_path,)
+        .unwrap()
+        .find_map(Result::err,)
+        .expect("expected a decode error",);
+    let position = error.position().expect("expected a position",);
+    assert_eq!(position.row_group, Some(1));
+    assert_eq!(position.record_index, Some(1024));
+}